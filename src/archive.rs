@@ -0,0 +1,292 @@
+//! Archiving of target directory contents before they are deleted.
+
+use crate::manifest::Manifest;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// Pack the generated documentation of the project (`target/doc` and `target/<triple>/doc`) into
+/// a zip archive in the given directory. The archive is named after the package name and version
+/// and the current date. Returns the path of the created archive or `None` if the project doesn't
+/// have any documentation.
+pub fn archive_docs(
+    project_path: &Path,
+    target_path: &Path,
+    archive_dir: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let mut doc_dirs = vec![PathBuf::from("doc")];
+    for entry in target_path.read_dir()?.filter_map(|it| it.ok()) {
+        if entry.file_type().is_ok_and(|t| t.is_dir()) && entry.path().join("doc").is_dir() {
+            doc_dirs.push(PathBuf::from(entry.file_name()).join("doc"));
+        }
+    }
+    doc_dirs.retain(|dir| target_path.join(dir).is_dir());
+
+    if doc_dirs.is_empty() {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(archive_dir)?;
+    let (archive_path, file) = create_unique_archive(
+        archive_dir,
+        &format!("{}-docs", archive_base_name(project_path)),
+        "zip",
+    )?;
+
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+    for dir in doc_dirs {
+        add_dir_to_zip(&mut zip, target_path, &dir)?;
+    }
+    zip.finish()?;
+
+    Ok(Some(archive_path))
+}
+
+/// The name of the index file in the archive directory, which records the archived targets
+pub const INDEX_FILE_NAME: &str = "index.json";
+
+/// An entry in the archive index
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// The canonicalized project path
+    pub project_path: PathBuf,
+    /// The file name of the archive, relative to the archive directory
+    pub archive: PathBuf,
+    /// The time when the archive was created in RFC 3339 format
+    pub created: String,
+    /// The size of the target directory before archiving
+    pub target_size: u64,
+}
+
+/// Pack the target directory of the project into a zstd compressed tar archive in the given
+/// directory. The returned entry should be recorded in the archive index using [`append_index`].
+/// The contents are stored in the `target` directory of the archive, even if the target directory
+/// of the project is redirected
+pub fn archive_target(
+    project_path: &Path,
+    target_path: &Path,
+    archive_dir: &Path,
+    target_size: u64,
+) -> io::Result<ArchiveEntry> {
+    std::fs::create_dir_all(archive_dir)?;
+    let (archive_path, file) = create_unique_archive(
+        archive_dir,
+        &format!("{}-target", archive_base_name(project_path)),
+        "tar.zst",
+    )?;
+
+    let result = (|| {
+        let encoder = zstd::Encoder::new(BufWriter::new(file), 3)?;
+        let mut builder = tar::Builder::new(encoder);
+        builder.follow_symlinks(false);
+        builder.append_dir_all("target", target_path)?;
+        builder.into_inner()?.finish()?.flush()
+    })();
+
+    if let Err(e) = result {
+        // Don't leave incomplete archives behind
+        let _ = std::fs::remove_file(&archive_path);
+        return Err(e);
+    }
+
+    Ok(ArchiveEntry {
+        project_path: crate::canonicalize_or_not(project_path),
+        archive: PathBuf::from(archive_path.file_name().expect("Path Error")),
+        created: chrono::Local::now().to_rfc3339(),
+        target_size,
+    })
+}
+
+/// Read the archive index of the given archive directory. A missing index is treated as empty.
+pub fn read_index(archive_dir: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    match std::fs::read_to_string(archive_dir.join(INDEX_FILE_NAME)) {
+        Ok(content) => serde_json::from_str(&content).map_err(io::Error::from),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write the archive index of the given archive directory. The index is first written to a
+/// temporary file and then moved into place, so that an interruption doesn't corrupt it.
+pub fn write_index(archive_dir: &Path, entries: &[ArchiveEntry]) -> io::Result<()> {
+    let tmp_path = archive_dir.join(format!("{}.tmp", INDEX_FILE_NAME));
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(entries)?)?;
+    std::fs::rename(tmp_path, archive_dir.join(INDEX_FILE_NAME))
+}
+
+/// Add the given entries to the archive index of the given archive directory
+pub fn append_index(archive_dir: &Path, new_entries: Vec<ArchiveEntry>) -> io::Result<()> {
+    let mut entries = read_index(archive_dir)?;
+    entries.extend(new_entries);
+    write_index(archive_dir, &entries)
+}
+
+/// Unpack the `target` directory of the archive into the redirected target directory of the
+/// project. Entries outside of the `target` directory are skipped
+fn unpack_redirected<R: io::Read>(
+    archive: &mut tar::Archive<R>,
+    target_path: &Path,
+) -> io::Result<()> {
+    std::fs::create_dir_all(target_path)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let Ok(relative) = path.strip_prefix("target") else {
+            continue;
+        };
+        if relative
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            continue;
+        }
+        let dest = target_path.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        entry.unpack(&dest)?;
+    }
+    Ok(())
+}
+
+/// Restore the most recently archived target directory of the given project from the archive
+/// directory. If `remove_archive` is set, the archive and its index entry are deleted afterwards.
+pub fn unarchive(
+    project_path: &Path,
+    archive_dir: &Path,
+    remove_archive: bool,
+    force: bool,
+) -> io::Result<()> {
+    let project_path = crate::canonicalize_or_not(project_path);
+    let mut entries = read_index(archive_dir)?;
+
+    let Some(idx) = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.project_path == project_path)
+        .max_by(|(_, a), (_, b)| a.created.cmp(&b.created))
+        .map(|(idx, _)| idx)
+    else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No archive found for '{}' in '{}'",
+                crate::pretty_format_path(&project_path),
+                archive_dir.display()
+            ),
+        ));
+    };
+
+    let target_path = crate::cargo_config::target_dir(&project_path)
+        .unwrap_or_else(|| project_path.join("target"));
+    let target_is_empty = target_path
+        .read_dir()
+        .map_or(true, |mut rd| rd.next().is_none());
+    if !force && !target_is_empty {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "'{}' already exists. Use --force to restore the archive anyway",
+                crate::pretty_format_path(&target_path)
+            ),
+        ));
+    }
+
+    let archive_path = archive_dir.join(&entries[idx].archive);
+    println!(
+        "Restoring {} from {}",
+        crate::pretty_format_path(&target_path),
+        archive_path.display()
+    );
+
+    let decoder = zstd::Decoder::new(File::open(&archive_path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    archive.set_preserve_mtime(true);
+    if target_path == project_path.join("target") {
+        archive.unpack(&project_path)?;
+    } else {
+        unpack_redirected(&mut archive, &target_path)?;
+    }
+
+    if remove_archive {
+        entries.remove(idx);
+        write_index(archive_dir, &entries)?;
+        std::fs::remove_file(&archive_path)?;
+    }
+
+    println!("Archive restored");
+    Ok(())
+}
+
+/// Recursively add the directory `base/rel` to the archive using the relative paths as names
+fn add_dir_to_zip<W: io::Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    base: &Path,
+    rel: &Path,
+) -> io::Result<()> {
+    let options = SimpleFileOptions::default().large_file(true);
+    zip.add_directory(zip_entry_name(rel), options)?;
+
+    for entry in base.join(rel).read_dir()?.filter_map(|it| it.ok()) {
+        let rel = rel.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            add_dir_to_zip(zip, base, &rel)?;
+        } else if file_type.is_file() {
+            zip.start_file(zip_entry_name(&rel), options)?;
+            io::copy(&mut File::open(entry.path())?, zip)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn zip_entry_name(rel: &Path) -> String {
+    rel.to_string_lossy().replace('\\', "/")
+}
+
+/// The base name for archives of the given project, consisting of the package name and version if
+/// available, falling back to the project directory name
+pub fn archive_base_name(project_path: &Path) -> String {
+    let manifest = Manifest::read(project_path).unwrap_or_default();
+    let (name, version) = manifest.package_name_version();
+    let name = name.map(str::to_string).unwrap_or_else(|| {
+        project_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    });
+
+    match version {
+        Some(version) => format!("{}-{}", name, version),
+        None => name,
+    }
+}
+
+/// Create a new archive file that doesn't overwrite an existing one, using the current date and a
+/// counter to distinguish archives of projects with the same name
+fn create_unique_archive(
+    archive_dir: &Path,
+    base_name: &str,
+    extension: &str,
+) -> io::Result<(PathBuf, File)> {
+    let date = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut path = archive_dir.join(format!("{}-{}.{}", base_name, date, extension));
+    let mut counter = 1;
+    loop {
+        match File::create_new(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                path =
+                    archive_dir.join(format!("{}-{}-{}.{}", base_name, date, counter, extension));
+                counter += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
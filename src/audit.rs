@@ -0,0 +1,132 @@
+//! The audit log, which records everything that is deleted during a run. The log is a JSON Lines
+//! file with one record per line, new runs are appended to it.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+#[derive(Serialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum Record<'a> {
+    /// The start of a run
+    Run { time: String, version: &'static str },
+    /// A deleted file
+    Deleted {
+        path: &'a Path,
+        size: u64,
+        modified: String,
+    },
+    /// The outcome of cleaning a target directory
+    Target {
+        path: &'a Path,
+        size: u64,
+        files: u64,
+        error: Option<String>,
+    },
+    /// An executable that was moved out of the target directory before it was deleted
+    Preserved {
+        path: &'a Path,
+        sha256: Option<String>,
+    },
+}
+
+pub struct AuditLog {
+    writer: Mutex<BufWriter<File>>,
+    /// Include the hashes of the preserved executables
+    hashes: bool,
+}
+
+impl AuditLog {
+    /// Open the audit log for appending and record the start of the run
+    pub fn open(path: &Path, hashes: bool) -> io::Result<Self> {
+        let file = File::options().create(true).append(true).open(path)?;
+        let log = Self {
+            writer: Mutex::new(BufWriter::new(file)),
+            hashes,
+        };
+        log.write(&Record::Run {
+            time: chrono::Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION"),
+        })?;
+        Ok(log)
+    }
+
+    fn write(&self, record: &Record) -> io::Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+
+    /// Record the files that were deleted from the target directory. The files are listed before
+    /// the deletion, so after a failed deletion only the files that are actually gone are recorded.
+    pub fn record_target(
+        &self,
+        target_path: &Path,
+        files: &[(PathBuf, u64, SystemTime)],
+        result: &io::Result<(u64, u64)>,
+    ) -> io::Result<()> {
+        let (mut total_size, mut file_count) = (0, 0);
+        for (path, size, modified) in files {
+            if result.is_err() && path.symlink_metadata().is_ok() {
+                continue;
+            }
+            self.record_deleted_file(path, *size, *modified)?;
+            total_size += size;
+            file_count += 1;
+        }
+
+        self.write(&Record::Target {
+            path: &crate::canonicalize_or_not(target_path),
+            size: total_size,
+            files: file_count,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        })
+    }
+
+    pub fn record_deleted_file(
+        &self,
+        path: &Path,
+        size: u64,
+        modified: SystemTime,
+    ) -> io::Result<()> {
+        self.write(&Record::Deleted {
+            path,
+            size,
+            modified: chrono::DateTime::<chrono::Local>::from(modified).to_rfc3339(),
+        })
+    }
+
+    pub fn record_preserved(&self, path: &Path) -> io::Result<()> {
+        let sha256 = if self.hashes {
+            Some(hash_file(path)?)
+        } else {
+            None
+        };
+        self.write(&Record::Preserved { path, sha256 })
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let len = file.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buf[..len]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
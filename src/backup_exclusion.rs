@@ -0,0 +1,42 @@
+use std::{io, path::Path, process::Command};
+
+/// Mark the given directory as excluded from the operating systems backup facilities.
+///
+/// On macOS this sets the sticky Time Machine exclusion (`tmutil addexclusion`), which stores the
+/// `com.apple.metadata:com_apple_backup_excludeItem` attribute on the directory itself, so the
+/// exclusion moves with the directory. On Windows the directory is marked as not content indexed,
+/// which is what File History and the search indexer use to skip directories.
+pub fn exclude_from_backup(dir: &Path) -> io::Result<()> {
+    let mut cmd = exclusion_command(dir)?;
+    let output = cmd.output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn exclusion_command(dir: &Path) -> io::Result<Command> {
+    let mut cmd = Command::new("tmutil");
+    cmd.arg("addexclusion").arg(dir);
+    Ok(cmd)
+}
+
+#[cfg(windows)]
+fn exclusion_command(dir: &Path) -> io::Result<Command> {
+    let mut cmd = Command::new("attrib");
+    cmd.arg("+I").arg(dir);
+    Ok(cmd)
+}
+
+#[cfg(not(any(target_os = "macos", windows)))]
+fn exclusion_command(_dir: &Path) -> io::Result<Command> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "backup exclusion is only supported on macOS and Windows",
+    ))
+}
@@ -0,0 +1,269 @@
+//! The `bench-delete` subcommand, which measures how fast the available deletion backends delete
+//! a synthetic target directory on the storage of the given directory. The synthetic targets are
+//! generated inside of a new directory that is removed afterwards, so no existing files are
+//! touched.
+
+use crate::{mounts, output::outln, pretty_format_path, storage, wsl};
+use colored::Colorize;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The number of files per crate in the synthetic target. Most of them are incremental
+/// compilation files, like in real targets
+const FILES_PER_CRATE: u64 = 20;
+
+/// Generate a synthetic debug target with about the given number of files. Returns the number of
+/// files that were created
+fn generate_target(root: &Path, files: u64) -> io::Result<u64> {
+    let profile = root.join("debug");
+    let small = vec![0u8; 4 * 1024];
+    let rlib = vec![0u8; 64 * 1024];
+
+    let mut created = 0;
+    for i in 0..files.div_ceil(FILES_PER_CRATE) {
+        let name = format!("crate{}-{:016x}", i, i.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let hash = name
+            .rsplit_once('-')
+            .map(|(_, hash)| hash)
+            .unwrap_or_default();
+
+        let fingerprint = profile.join(".fingerprint").join(&name);
+        fs::create_dir_all(&fingerprint)?;
+        for file in [
+            "lib-crate",
+            "lib-crate.json",
+            "dep-lib-crate",
+            "invoked.timestamp",
+        ] {
+            fs::write(fingerprint.join(file), &small[..64])?;
+        }
+
+        let deps = profile.join("deps");
+        fs::create_dir_all(&deps)?;
+        fs::write(deps.join(format!("libcrate{}-{}.rlib", i, hash)), &rlib)?;
+        fs::write(deps.join(format!("libcrate{}-{}.rmeta", i, hash)), &small)?;
+        fs::write(deps.join(format!("crate{}-{}.d", i, hash)), &small[..256])?;
+
+        let session = profile.join("incremental").join(&name).join("s-session");
+        fs::create_dir_all(&session)?;
+        for j in 0..FILES_PER_CRATE - 7 {
+            fs::write(session.join(format!("{:03}.o", j)), &small)?;
+        }
+        created += FILES_PER_CRATE;
+    }
+    Ok(created)
+}
+
+type Backend = Box<dyn Fn(&Path) -> io::Result<()>>;
+
+/// The deletion backends available on this platform with their names. The first ones are the
+/// backend that is used for cleaning with different numbers of threads
+fn backends(thread_counts: &[usize]) -> Vec<(String, Backend)> {
+    let mut backends: Vec<(String, Backend)> = Vec::new();
+    for &threads in thread_counts {
+        backends.push((
+            format!(
+                "remove_dir_all, {} thread{}",
+                threads,
+                if threads == 1 { "" } else { "s" }
+            ),
+            Box::new(move |dir: &Path| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(io::Error::other)?
+                    .install(|| remove_dir_all::remove_dir_all(dir))
+            }),
+        ));
+    }
+
+    backends.push((
+        "std::fs::remove_dir_all".to_string(),
+        Box::new(|dir: &Path| fs::remove_dir_all(dir)),
+    ));
+
+    #[cfg(target_os = "macos")]
+    backends.push((
+        "removefile".to_string(),
+        Box::new(|dir: &Path| crate::macos::remove_dir_all(dir)),
+    ));
+
+    backends.push((
+        if cfg!(windows) {
+            "rmdir /s /q".to_string()
+        } else {
+            "rm -rf".to_string()
+        },
+        Box::new(|dir: &Path| {
+            let status = if cfg!(windows) {
+                Command::new("cmd")
+                    .args(["/c", "rmdir", "/s", "/q"])
+                    .arg(dir)
+                    .status()?
+            } else {
+                Command::new("rm").arg("-rf").arg(dir).status()?
+            };
+            if !status.success() || dir.exists() {
+                return Err(io::Error::other("The deletion command failed"));
+            }
+            Ok(())
+        }),
+    ));
+
+    if wsl::is_wsl() {
+        backends.push((
+            "Windows rmdir through WSL".to_string(),
+            Box::new(wsl::delete_natively),
+        ));
+    }
+    backends
+}
+
+/// The number of times each backend is measured. The median is reported, since the first deletion
+/// of a freshly generated tree is sometimes slowed down by writeback
+const RUNS: usize = 3;
+
+/// Measure the median time the backend takes to delete a synthetic target. Returns the time and
+/// the number of deleted files. Targets that failed to delete are removed with the benchmark
+/// directory
+fn measure(bench_dir: &Path, files: u64, backend: &Backend) -> io::Result<(Duration, u64)> {
+    let mut times = Vec::new();
+    let mut created = 0;
+    for _ in 0..RUNS {
+        let target = bench_dir.join("target");
+        created = generate_target(&target, files)?;
+
+        let start = Instant::now();
+        backend(&target)?;
+        times.push(start.elapsed());
+        if target.exists() {
+            return Err(io::Error::other("the target still exists"));
+        }
+    }
+    times.sort_unstable();
+    Ok((times[RUNS / 2], created))
+}
+
+/// Removes the benchmark directory when the benchmark ends, even if it failed
+struct BenchDir(PathBuf);
+
+impl Drop for BenchDir {
+    fn drop(&mut self) {
+        if self.0.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.0) {
+                eprintln!(
+                    "Failed to remove the benchmark directory {}: {}",
+                    pretty_format_path(&self.0),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Run the deletion benchmark with synthetic targets of the given number of files in a new
+/// directory inside of `dir`, and recommend the settings for the fastest backend
+pub fn bench_delete(dir: &Path, files: u64) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", pretty_format_path(dir)),
+        ));
+    }
+    let bench_dir = BenchDir(dir.join(format!("cargo-clean-all-bench-{}", std::process::id())));
+    // Fails if the directory already exists, so that nothing that wasn't generated is deleted
+    fs::create_dir(&bench_dir.0)?;
+
+    let mounts = mounts::mounts();
+    let default_threads = storage::default_threads(&mounts, std::slice::from_ref(&bench_dir.0));
+    let mut thread_counts = vec![1, 2, 4, num_cpus::get(), default_threads];
+    thread_counts.sort_unstable();
+    thread_counts.dedup();
+
+    outln!(
+        "Benchmarking the deletion of synthetic targets with {} files in {}",
+        files,
+        pretty_format_path(dir)
+    );
+
+    let mut results = Vec::new();
+    for (idx, (name, backend)) in backends(&thread_counts).into_iter().enumerate() {
+        match measure(&bench_dir.0, files, &backend) {
+            Ok((elapsed, created)) => {
+                outln!(
+                    "  {:<32} {:>10.2?} ({:.0} files/s)",
+                    name,
+                    elapsed,
+                    created as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+                );
+                results.push((name, elapsed, thread_counts.get(idx).copied()));
+            }
+            Err(e) => outln!("  {:<32} failed: {}", name, e),
+        }
+    }
+
+    // Renaming the target and deleting it in the background only hides the deletion time. It is
+    // measured for comparison, since the deletion still competes with everything else for the disk
+    let target = bench_dir.0.join("target-rename");
+    let created = generate_target(&target, files)?;
+    let start = Instant::now();
+    let renamed = bench_dir.0.join("target-rename.deleting");
+    fs::rename(&target, &renamed)?;
+    let rename_time = start.elapsed();
+    let background = thread::spawn(move || remove_dir_all::remove_dir_all(renamed));
+    let deletion = background.join().expect("The background deletion panicked");
+    let total_time = start.elapsed();
+    match deletion {
+        Ok(()) => outln!(
+            "  {:<32} {:>10.2?} until renamed, {:.2?} until deleted ({:.0} files/s)",
+            "rename + background deletion",
+            rename_time,
+            total_time,
+            created as f64 / total_time.as_secs_f64().max(f64::EPSILON)
+        ),
+        Err(e) => outln!("  {:<32} failed: {}", "rename + background deletion", e),
+    }
+
+    recommend(&results, default_threads);
+    Ok(())
+}
+
+/// Recommend the settings for the fastest backend. The results contain the thread counts of the
+/// runs of the backend that is used for cleaning
+fn recommend(results: &[(String, Duration, Option<usize>)], default_threads: usize) {
+    let Some((fastest, _, _)) = results.iter().min_by_key(|(_, elapsed, _)| *elapsed) else {
+        return;
+    };
+    outln!("\nFastest backend: {}", fastest.bold());
+
+    // Only the thread count and the native Windows deletion in WSL can be selected for cleaning
+    if fastest.starts_with("Windows rmdir") {
+        outln!("Recommended: use --windows-native-delete for targets on Windows drives");
+        return;
+    }
+    let Some(best_threads) = results
+        .iter()
+        .filter(|(_, _, threads)| threads.is_some())
+        .min_by_key(|(_, elapsed, _)| *elapsed)
+        .and_then(|(_, _, threads)| *threads)
+    else {
+        return;
+    };
+    if best_threads == default_threads {
+        outln!(
+            "Recommended: keep the automatic --delete-threads, which uses {} threads here",
+            default_threads
+        );
+    } else {
+        outln!(
+            "Recommended: --delete-threads {} (the automatic value is {} threads here)",
+            best_threads,
+            default_threads
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Detection of the builds that are running in a target directory. While building, cargo holds an
+//! exclusive lock on the `.cargo-lock` file in the profile directory, so a target is in use if
+//! that lock can't be acquired.
+
+use crate::prune::profile_dirs;
+use std::{
+    fs::{File, TryLockError},
+    path::Path,
+};
+
+/// Whether the lock of the profile directory is currently held by another process
+fn is_held(lock_file: &Path) -> bool {
+    let Ok(file) = File::open(lock_file) else {
+        return false;
+    };
+    match file.try_lock_shared() {
+        // The lock is released again when the file is closed
+        Ok(()) => false,
+        Err(TryLockError::WouldBlock) => true,
+        Err(TryLockError::Error(_)) => false,
+    }
+}
+
+/// Whether cargo is currently building in one of the profile directories of the target
+pub fn is_locked(target_path: &Path) -> bool {
+    profile_dirs(target_path)
+        .iter()
+        .any(|profile_dir| is_held(&profile_dir.join(".cargo-lock")))
+}
@@ -0,0 +1,217 @@
+//! Statistics about the registry and git caches in the cargo home directory, similar to the ones
+//! shown by cargo-cache, and the garbage collection of those caches by cargo itself.
+
+use crate::{
+    i18n::{self, tr},
+    output::{errln, outln},
+    pretty_format_path, ProjectTargetAnalysis,
+};
+use colored::Colorize;
+use regex::Regex;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The cargo home directory, which is `$CARGO_HOME` or `.cargo` in the home directory by default,
+/// like `%USERPROFILE%\.cargo` on Windows. A relative `$CARGO_HOME` is resolved against the
+/// working directory, like cargo does
+pub fn cargo_home() -> Option<PathBuf> {
+    match cargo_home_from_env() {
+        Some(dir) if dir.is_relative() => std::env::current_dir().ok().map(|cwd| cwd.join(dir)),
+        Some(dir) => Some(dir),
+        None => std::env::home_dir().map(|home| home.join(".cargo")),
+    }
+}
+
+/// The cargo home directory set using `$CARGO_HOME`, if any
+pub fn cargo_home_from_env() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The sizes of the caches in the cargo home directory
+pub struct CacheStats {
+    pub cargo_home: PathBuf,
+    /// `registry/index`
+    pub registry_index: u64,
+    /// The downloaded `.crate` files in `registry/cache`
+    pub crate_archives: u64,
+    pub crate_archive_count: u64,
+    /// The extracted crate sources in `registry/src`
+    pub registry_sources: u64,
+    /// The bare git repositories in `git/db`
+    pub git_db: u64,
+    /// The checked out git dependencies in `git/checkouts`
+    pub git_checkouts: u64,
+}
+
+impl CacheStats {
+    pub fn total(&self) -> u64 {
+        self.registry_index
+            + self.crate_archives
+            + self.registry_sources
+            + self.git_db
+            + self.git_checkouts
+    }
+}
+
+fn dir_size(path: &Path) -> (u64, u64) {
+    let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(path);
+    (size, file_count)
+}
+
+/// Compute the sizes of the caches in the cargo home directory
+pub fn cache_stats() -> Option<CacheStats> {
+    let cargo_home = cargo_home()?;
+    if !cargo_home.is_dir() {
+        return None;
+    }
+
+    let (crate_archives, crate_archive_count) = dir_size(&cargo_home.join("registry/cache"));
+    Some(CacheStats {
+        registry_index: dir_size(&cargo_home.join("registry/index")).0,
+        crate_archives,
+        crate_archive_count,
+        registry_sources: dir_size(&cargo_home.join("registry/src")).0,
+        git_db: dir_size(&cargo_home.join("git/db")).0,
+        git_checkouts: dir_size(&cargo_home.join("git/checkouts")).0,
+        cargo_home,
+    })
+}
+
+/// Print the cache statistics together with the total Rust related disk usage, which includes
+/// the given size of the target directories
+pub fn print_cache_stats(targets_size: u64) {
+    let Some(stats) = cache_stats() else {
+        outln!("\nNo cargo home directory found");
+        return;
+    };
+
+    outln!(
+        "\nCargo caches in {}: {}",
+        pretty_format_path(&stats.cargo_home),
+        bytefmt::format(stats.total()).bold()
+    );
+    outln!(
+        "  Registry index:    {}",
+        bytefmt::format(stats.registry_index)
+    );
+    outln!(
+        "  Crate archives:    {} ({} files)",
+        bytefmt::format(stats.crate_archives),
+        stats.crate_archive_count
+    );
+    outln!(
+        "  Registry sources:  {}",
+        bytefmt::format(stats.registry_sources)
+    );
+    outln!("  Git repositories:  {}", bytefmt::format(stats.git_db));
+    outln!(
+        "  Git checkouts:     {}",
+        bytefmt::format(stats.git_checkouts)
+    );
+    outln!(
+        "Total Rust related disk usage (targets and caches): {}",
+        bytefmt::format(targets_size + stats.total()).bold()
+    );
+}
+
+/// What the garbage collection of cargo removed or would remove
+pub struct GcSummary {
+    pub file_count: u64,
+    pub size: u64,
+}
+
+/// Run the garbage collection of the global caches that is built into cargo using `cargo clean
+/// gc`, so that the caches are cleaned the same way cargo would do by itself. Returns `None` if
+/// the installed cargo doesn't support it, which is currently the case for all stable releases.
+pub fn cargo_gc(dry_run: bool) -> io::Result<Option<GcSummary>> {
+    // When running as cargo subcommand, the cargo that invoked it is used
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command.args(["clean", "gc", "-Zgc"]);
+    if dry_run {
+        command.arg("--dry-run");
+    }
+    let output = command.output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        if stderr.contains("nightly") || stderr.contains("unrecognized subcommand") {
+            return Ok(None);
+        }
+        return Err(io::Error::other(format!(
+            "cargo clean gc failed: {}",
+            stderr.lines().next().unwrap_or_default()
+        )));
+    }
+
+    // The summary looks like `Removed 12 files, 3.4MiB total`, or starts with `Summary` for a
+    // dry run. The size is left out when nothing was removed
+    let summary_re =
+        Regex::new(r"(?:Removed|Summary) (\d+) files?(?:, ([\d.]+)([KMGT]i)?B total)?")
+            .expect("Invalid regex");
+    let Some(captures) = summary_re.captures(&stderr) else {
+        return Ok(Some(GcSummary {
+            file_count: 0,
+            size: 0,
+        }));
+    };
+    let file_count = captures[1].parse().unwrap_or(0);
+    let size = captures
+        .get(2)
+        .map_or(0.0, |size| size.as_str().parse().unwrap_or(0.0))
+        * match captures.get(3).map(|unit| unit.as_str()) {
+            Some("Ki") => 1024f64,
+            Some("Mi") => 1024f64.powi(2),
+            Some("Gi") => 1024f64.powi(3),
+            Some("Ti") => 1024f64.powi(4),
+            _ => 1.0,
+        };
+    Ok(Some(GcSummary {
+        file_count,
+        size: size as u64,
+    }))
+}
+
+/// Run the garbage collection of cargo and report what it removed. Returns `None` if it is not
+/// supported or failed
+pub fn report_cargo_gc(dry_run: bool) -> Option<GcSummary> {
+    match cargo_gc(dry_run) {
+        Ok(Some(summary)) => {
+            let size = i18n::format_size(summary.size);
+            let message = if dry_run {
+                tr!(
+                    "Cargo's garbage collection would remove {} files ({}) from the global caches",
+                    summary.file_count,
+                    size
+                )
+            } else {
+                tr!(
+                    "Cargo's garbage collection removed {} files ({}) from the global caches",
+                    summary.file_count,
+                    size
+                )
+            };
+            outln!("{}", message);
+            Some(summary)
+        }
+        Ok(None) => {
+            outln!(
+                "{}",
+                tr!("The installed cargo doesn't support `cargo clean gc` yet, so the global caches are not cleaned")
+            );
+            None
+        }
+        Err(e) => {
+            errln!(
+                "{}",
+                tr!("Failed to run the garbage collection of cargo: {}", e)
+            );
+            None
+        }
+    }
+}
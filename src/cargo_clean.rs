@@ -0,0 +1,44 @@
+//! Cleaning the projects by running `cargo clean` in them for `--use-cargo-clean`, instead of
+//! deleting the target directories directly.
+
+use std::{io, path::Path, process::Command};
+
+/// Run `cargo clean` in the project, once for each of the profiles or once for all of them if
+/// none are given. The packages are passed on as `-p`, so only their artifacts are cleaned
+pub fn cargo_clean(
+    project_path: &Path,
+    profiles: &[String],
+    packages: &[String],
+) -> io::Result<()> {
+    // When running as cargo subcommand, the cargo that invoked it is used
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let profiles: Vec<Option<&String>> = if profiles.is_empty() {
+        vec![None]
+    } else {
+        profiles.iter().map(Some).collect()
+    };
+
+    for profile in profiles {
+        let mut command = Command::new(&cargo);
+        command.arg("clean").current_dir(project_path);
+        if let Some(profile) = profile {
+            command.args(["--profile", profile]);
+        }
+        for package in packages {
+            command.args(["-p", package]);
+        }
+        let output = command.output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(io::Error::other(format!(
+                "cargo clean failed: {}",
+                stderr
+                    .lines()
+                    .find(|line| line.starts_with("error"))
+                    .or(stderr.lines().next())
+                    .unwrap_or_default()
+            )));
+        }
+    }
+    Ok(())
+}
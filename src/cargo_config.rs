@@ -0,0 +1,60 @@
+//! The target directories that projects redirect using `build.target-dir` in their cargo config
+//! files. Like cargo, the `.cargo/config.toml` files in the project and all of its parent
+//! directories are searched, followed by the one in the cargo home directory. The closest one
+//! that sets the target directory wins.
+
+use crate::cargo_cache::cargo_home;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize)]
+struct CargoConfig {
+    build: Option<BuildConfig>,
+}
+
+#[derive(Deserialize)]
+struct BuildConfig {
+    #[serde(rename = "target-dir")]
+    target_dir: Option<PathBuf>,
+}
+
+/// The config file in the `.cargo` directory. If both exist, cargo uses the one without extension
+fn config_file(cargo_dir: &Path) -> PathBuf {
+    let legacy = cargo_dir.join("config");
+    if legacy.is_file() {
+        legacy
+    } else {
+        cargo_dir.join("config.toml")
+    }
+}
+
+/// The target directory set in the config file. Relative paths are relative to the parent of the
+/// `.cargo` directory that contains the file
+fn configured_target_dir(config_file: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(config_file).ok()?;
+    let config: CargoConfig = toml::from_str(&content).ok()?;
+    let target_dir = config.build?.target_dir?;
+    let base = config_file.parent()?.parent()?;
+    Some(base.join(target_dir))
+}
+
+/// The target directory of the project if it is redirected by a cargo config file. `None` means
+/// that the project uses `target` in the project directory. The config files are not used when
+/// the target directory is set by the environment, which overrides them
+pub fn target_dir(project_path: &Path) -> Option<PathBuf> {
+    if ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|dir| !dir.is_empty()))
+    {
+        return None;
+    }
+
+    let project_path = crate::canonicalize_or_not(project_path);
+    let home_cargo_dir = cargo_home();
+    project_path
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        // The cargo home is usually `~/.cargo` and therefore one of the searched directories
+        .chain(home_cargo_dir)
+        .find_map(|cargo_dir| configured_target_dir(&config_file(&cargo_dir)))
+}
@@ -0,0 +1,1094 @@
+//! The cleanup phase, which preserves the requested artifacts and deletes the target directories
+//! of the selected projects.
+
+use crate::{
+    archive, audit::AuditLog, cargo_clean, elevate, journal::Journal, mounts, mounts::Mount,
+    output, preserve, pretty_format_path, privileges, prune, safety, storage, target_filter,
+    target_filter::TargetFilter, toolchain, trim, worker_pool, wsl, AppArgs, ProjectTargetAnalysis,
+};
+use clap::ValueEnum;
+use dialoguer::console::Term;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The number of threads that read the metadata of the targets ahead of the deletion
+const PREFETCH_THREADS: usize = 4;
+
+/// The start of the names that targets are renamed to by --background-delete. The scan skips
+/// these directories, in case a run was aborted before they were deleted
+pub const RENAMED_TARGET_PREFIX: &str = "target.cargo-clean-all-deleting";
+
+/// The order in which the selected projects are cleaned
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DeleteOrder {
+    /// The largest targets first
+    BiggestFirst,
+    /// The targets that were modified the longest time ago first
+    OldestFirst,
+    /// The order of the listing
+    AsListed,
+}
+
+/// The outcome of the cleanup phase
+pub struct CleanupResult {
+    /// Projects that could not be cleaned together with the error that occurred
+    pub failed: Vec<(ProjectTargetAnalysis, io::Error)>,
+    /// The number of projects that were cleaned successfully
+    pub cleaned: usize,
+    /// The number of selected projects that were skipped when confirming each project
+    pub skipped: usize,
+    /// The number of selected projects that were not cleaned, since the time limit was reached
+    pub timed_out: usize,
+    /// The number of selected projects that were not cleaned, since the cleaned ones already freed
+    /// up the --max-free limit
+    pub max_free_kept: usize,
+    /// The size of the deleted target directories
+    pub freed_size: u64,
+    /// The freed size per processed project, including the partially cleaned ones, with the path of
+    /// the project and of its target directory
+    pub freed_per_project: Vec<(PathBuf, PathBuf, u64)>,
+    /// The number of deleted files
+    pub deleted_files: u64,
+    /// The time spent deleting the target directories
+    pub deletion_duration: Duration,
+}
+
+/// A change of the state of a project during the cleanup, which is used to report the progress
+/// per project
+pub enum ProjectEvent<'a> {
+    /// The deletion of the target started
+    Started,
+    /// The project was processed. What it freed or why it failed is recorded in the result so far
+    Finished(&'a CleanupResult),
+}
+
+/// The answer to the per-project confirmation prompt
+enum Confirmation {
+    Yes,
+    No,
+    All,
+    Quit,
+}
+
+/// Preserve the artifacts of the selected projects as requested by the args and delete their
+/// target directories afterwards
+pub fn cleanup(
+    args: &AppArgs,
+    selected: &[ProjectTargetAnalysis],
+    journal: Option<&mut Journal>,
+) -> CleanupResult {
+    cleanup_with_progress(args, selected, journal, &|_, _| ())
+}
+
+/// Like `cleanup`, but report the state of each project to `on_progress`. Projects that are not
+/// processed, because the time limit was reached or the cleanup was quit, are not reported
+pub fn cleanup_with_progress(
+    args: &AppArgs,
+    selected: &[ProjectTargetAnalysis],
+    mut journal: Option<&mut Journal>,
+    on_progress: &dyn Fn(&ProjectTargetAnalysis, ProjectEvent),
+) -> CleanupResult {
+    let needs_preservation =
+        args.executable || args.keep_wasm || args.archive_docs.is_some() || args.archive.is_some();
+
+    // Nothing is deleted without a record of it when an audit log is requested
+    let audit_log = match args
+        .audit_log
+        .as_ref()
+        .map(|path| AuditLog::open(path, args.audit_hashes))
+        .transpose()
+    {
+        Ok(audit_log) => audit_log,
+        Err(e) => {
+            return CleanupResult {
+                failed: selected
+                    .iter()
+                    .map(|p| {
+                        let e = io::Error::new(
+                            e.kind(),
+                            format!("Opening the audit log failed: {}", e),
+                        );
+                        (p.clone(), e)
+                    })
+                    .collect(),
+                cleaned: 0,
+                skipped: 0,
+                timed_out: 0,
+                max_free_kept: 0,
+                freed_size: 0,
+                freed_per_project: Vec::new(),
+                deleted_files: 0,
+                deletion_duration: Duration::ZERO,
+            }
+        }
+    };
+    let audit_log = audit_log.as_ref();
+
+    let mut ordered: Vec<&ProjectTargetAnalysis> = selected.iter().collect();
+    match args.delete_order {
+        DeleteOrder::BiggestFirst => ordered.sort_by_key(|p| std::cmp::Reverse(p.total_size())),
+        DeleteOrder::OldestFirst => ordered.sort_by_key(|p| p.last_modified),
+        DeleteOrder::AsListed => (),
+    }
+
+    let delete_threads = match args.delete_threads {
+        0 => {
+            let targets: Vec<PathBuf> = selected.iter().map(|p| p.target_path.clone()).collect();
+            storage::default_threads(&mounts::mounts(), &targets)
+        }
+        threads => threads,
+    };
+
+    // Serializes the updates of the archive index, since the archives are created in parallel
+    let index_lock = Mutex::new(());
+
+    // Projects where preserving or archiving artifacts failed. Those are not cleaned to avoid
+    // losing data and reported as failed cleanups
+    let preserve_failures = Mutex::new(Vec::new());
+
+    // Refuse to touch target directories that point outside of their project or are dangerous to
+    // delete before anything is moved out of them
+    let mut unsafe_targets = Vec::new();
+    for project in selected {
+        let target_path = &project.target_path;
+        let check =
+            safety::check_deletion_path(target_path, args.min_target_depth).and_then(|_| {
+                if project.shared_target {
+                    safety::check_shared_target(args, needs_preservation)
+                } else if args.force || project.target_path != project.project_path.join("target") {
+                    // Target directories that are redirected by the cargo config are meant to be
+                    // somewhere else
+                    Ok(())
+                } else {
+                    safety::check_target_inside_project(&project.project_path)
+                }
+            });
+        if let Err(e) = check {
+            unsafe_targets.push((project.clone(), e));
+        }
+    }
+    let is_unsafe = |project: &ProjectTargetAnalysis| {
+        unsafe_targets
+            .iter()
+            .any(|(p, _)| p.project_path == project.project_path)
+    };
+
+    // Saves the executables and wasm artifacts in another folder before cleaning the target folder.
+    // When confirming each project, this is done right before deleting the project instead, so
+    // that nothing is moved for projects that are not cleaned.
+    if needs_preservation && !args.confirm_each {
+        let preserve_progress = progress_bar(selected.len(), "Preserving artifacts ");
+
+        worker_pool::for_each_parallel(selected, delete_threads, |project| {
+            if is_unsafe(project) {
+                preserve_progress.inc(1);
+                return;
+            }
+            preserve_progress.set_message(pretty_format_path(&project.project_path));
+            if let Err(e) =
+                preserve_project(args, project, &preserve_progress, &index_lock, audit_log)
+            {
+                preserve_failures.lock().unwrap().push((project.clone(), e));
+            }
+            preserve_progress.inc(1);
+        });
+
+        preserve_progress.finish_and_clear();
+    }
+
+    let mut failed = preserve_failures.into_inner().unwrap();
+    failed.extend(unsafe_targets);
+
+    let mut result = CleanupResult {
+        failed,
+        cleaned: 0,
+        skipped: 0,
+        timed_out: 0,
+        max_free_kept: 0,
+        freed_size: 0,
+        freed_per_project: Vec::new(),
+        deleted_files: 0,
+        deletion_duration: Duration::ZERO,
+    };
+
+    // The parallel deletion of remove_dir_all runs on the rayon thread pool, so a dedicated pool is
+    // used to control the number of deletion threads
+    let delete_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(delete_threads)
+        .build()
+        .expect("Failed to create the deletion thread pool");
+
+    // Used to find the targets on Windows drives, which are deleted using Windows itself when
+    // running in WSL
+    let wsl_mounts = if args.windows_native_delete && wsl::is_wsl() {
+        mounts::mounts()
+    } else {
+        Vec::new()
+    };
+
+    // Reading the metadata of the files ahead of the deletion warms up the filesystem caches (and
+    // on Windows the antivirus verdicts), which makes the deletion itself a lot faster
+    let prefetch_cancelled = Arc::new(AtomicBool::new(false));
+    let prefetch_thread = args.prefetch_metadata.then(|| {
+        let targets: Vec<PathBuf> = ordered.iter().map(|p| p.target_path.clone()).collect();
+        let cancelled = prefetch_cancelled.clone();
+        thread::spawn(move || {
+            worker_pool::for_each_parallel(&targets, PREFETCH_THREADS, |target| {
+                prefetch_metadata(target, &cancelled)
+            })
+        })
+    });
+
+    let filter = args.target_filter();
+    let clean_progress = progress_bar(selected.len(), "");
+    let mut confirm_all = !args.confirm_each;
+    // Projects whose deletion failed due to missing permissions
+    let mut permission_denied = Vec::new();
+
+    // The targets that were renamed and are deleted on the deletion pool in the background, with
+    // the files for the audit log. The results are sent with the index into this list
+    let mut background = Vec::new();
+    let (background_tx, background_rx) = mpsc::channel();
+
+    // Marks the project as processed in the journal, so that resuming doesn't touch it again. A
+    // target that is deleted in the background is only recorded as renamed until it is deleted,
+    // so that resuming finishes its deletion
+    let mut complete = |tgt: &ProjectTargetAnalysis,
+                        renamed: Option<&Path>,
+                        result: &CleanupResult| {
+        if let Some(journal) = journal.as_deref_mut() {
+            let update = match renamed {
+                Some(renamed) => journal.rename(&tgt.project_path, renamed),
+                None => journal.complete(&tgt.project_path),
+            };
+            if let Err(e) = update {
+                clean_progress.suspend(|| eprintln!("Error updating the cleanup journal: {}", e));
+            }
+        }
+        if renamed.is_none() {
+            on_progress(tgt, ProjectEvent::Finished(result));
+        }
+        clean_progress.inc(1);
+    };
+
+    // The number of files in the projects from each position to the end, which is used for the
+    // estimated remaining time, since the deletion time mostly depends on the number of files
+    let mut remaining_files: Vec<u64> = ordered
+        .iter()
+        .rev()
+        .scan(0, |sum, p| {
+            *sum += p.file_count;
+            Some(*sum)
+        })
+        .collect();
+    remaining_files.reverse();
+
+    // The deletions of multiple projects run on their own threads and send their outcome back,
+    // so that the results, the journal and the audit log are only updated here
+    let parallel_projects = usize::from(args.parallel_projects);
+    let (done_tx, done_rx) = mpsc::channel::<Deletion>();
+    let mut running = 0;
+    // The deletion duration is the time while at least one deletion is running
+    let mut busy_since: Option<Instant> = None;
+
+    thread::scope(|scope| {
+        for (idx, tgt) in ordered.into_iter().enumerate() {
+            // Wait for a free slot first, so that the checks below see the finished deletions
+            while running >= parallel_projects {
+                let deletion = done_rx.recv().expect("A deletion thread panicked");
+                running -= 1;
+                if running == 0 {
+                    result.deletion_duration += busy_since
+                        .take()
+                        .map_or(Duration::ZERO, |since| since.elapsed());
+                }
+                let tgt = deletion.tgt;
+                record_deletion(
+                    deletion,
+                    &mut result,
+                    &mut permission_denied,
+                    &mut background,
+                    audit_log,
+                    &clean_progress,
+                );
+                complete(tgt, None, &result);
+            }
+
+            if args.deadline_passed() {
+                result.timed_out = selected.len() - clean_progress.position() as usize - running;
+                break;
+            }
+
+            // No further deletions are scheduled once enough space is freed. The deletions that
+            // are still running are not counted yet, so they may free up more than the limit
+            if args
+                .max_free
+                .is_some_and(|max_free| result.freed_size >= max_free)
+            {
+                result.max_free_kept += 1;
+                complete(tgt, None, &result);
+                continue;
+            }
+
+            if result
+                .failed
+                .iter()
+                .any(|(failed, _)| failed.project_path == tgt.project_path)
+            {
+                complete(tgt, None, &result);
+                continue;
+            }
+
+            let path = pretty_format_path(&tgt.project_path);
+            match estimate_remaining(&result, remaining_files[idx]) {
+                Some(eta) => {
+                    clean_progress.set_message(format!("{} (ETA {})", path, format_eta(eta)))
+                }
+                None => clean_progress.set_message(path),
+            }
+
+            if !confirm_all {
+                match confirm_project(tgt, &clean_progress) {
+                    Confirmation::Yes => (),
+                    Confirmation::All => confirm_all = true,
+                    Confirmation::No => {
+                        result.skipped += 1;
+                        complete(tgt, None, &result);
+                        continue;
+                    }
+                    Confirmation::Quit => {
+                        result.skipped +=
+                            selected.len() - clean_progress.position() as usize - running;
+                        break;
+                    }
+                }
+            }
+
+            if needs_preservation && args.confirm_each {
+                if let Err(e) = preserve_project(args, tgt, &clean_progress, &index_lock, audit_log)
+                {
+                    result.failed.push((tgt.clone(), e));
+                    complete(tgt, None, &result);
+                    continue;
+                }
+            }
+
+            let target_path = &tgt.target_path;
+            let background_delete = args.background_delete
+                && !args.deletes_artifacts()
+                && filter.deletes_all()
+                && mounts::mount_of(&wsl_mounts, target_path)
+                    .is_none_or(|m| !wsl::is_windows_drive(m));
+            on_progress(tgt, ProjectEvent::Started);
+            if background_delete {
+                let audited_files = audit_log
+                    .map(|_| {
+                        target_filter::deleted_files(
+                            &crate::canonicalize_or_not(target_path),
+                            &filter,
+                        )
+                    })
+                    .unwrap_or_default();
+                // Renaming only fails if the target is in use, which is handled by the regular
+                // deletion
+                if let Ok(renamed) = rename_for_deletion(target_path, idx) {
+                    let (tx, id, fix_permissions) = (
+                        background_tx.clone(),
+                        background.len(),
+                        args.fix_permissions,
+                    );
+                    let job = renamed.clone();
+                    delete_pool.spawn(move || {
+                        let _ = tx.send((id, remove_target(&job, fix_permissions)));
+                    });
+                    // The freed size is corrected when the deletion in the background fails. The
+                    // files are only counted once they are deleted, since renaming doesn't tell
+                    // anything about the deletion rate of the estimated remaining time
+                    let deletion = Deletion {
+                        tgt,
+                        renamed: Some(renamed.clone()),
+                        audited_files,
+                        result: Ok((tgt.size, 0)),
+                    };
+                    record_deletion(
+                        deletion,
+                        &mut result,
+                        &mut permission_denied,
+                        &mut background,
+                        audit_log,
+                        &clean_progress,
+                    );
+                    complete(tgt, Some(&renamed), &result);
+                    continue;
+                }
+            }
+
+            running += 1;
+            busy_since.get_or_insert_with(Instant::now);
+            let done_tx = done_tx.clone();
+            let (filter, wsl_mounts, delete_pool) = (&filter, &wsl_mounts, &delete_pool);
+            scope.spawn(move || {
+                let deletion = delete_pool
+                    .install(|| delete_target(args, filter, wsl_mounts, tgt, audit_log.is_some()));
+                let _ = done_tx.send(deletion);
+            });
+        }
+
+        while running > 0 {
+            let deletion = done_rx.recv().expect("A deletion thread panicked");
+            running -= 1;
+            let tgt = deletion.tgt;
+            record_deletion(
+                deletion,
+                &mut result,
+                &mut permission_denied,
+                &mut background,
+                audit_log,
+                &clean_progress,
+            );
+            complete(tgt, None, &result);
+        }
+        result.deletion_duration += busy_since.map_or(Duration::ZERO, |since| since.elapsed());
+    });
+
+    clean_progress.finish_and_clear();
+
+    drop(background_tx);
+    if !background.is_empty() {
+        let background_start = Instant::now();
+        wait_for_background(
+            &mut result,
+            &background,
+            background_rx,
+            audit_log,
+            journal,
+            on_progress,
+        );
+        result.deletion_duration += background_start.elapsed();
+    }
+
+    prefetch_cancelled.store(true, Ordering::Relaxed);
+    if let Some(prefetch_thread) = prefetch_thread {
+        let _ = prefetch_thread.join();
+    }
+
+    // The elevated deletion removes the whole target, so it can't be used when keeping some of
+    // its contents
+    if args.elevate
+        && !args.moves_to_trash()
+        && filter.deletes_all()
+        && !args.deletes_artifacts()
+        && !permission_denied.is_empty()
+        && !privileges::is_elevated()
+    {
+        retry_elevated(&mut result, &permission_denied, audit_log);
+    }
+
+    result
+}
+
+/// Delete the targets of the projects that failed due to missing permissions with elevated
+/// privileges. Each target has to be confirmed explicitly, even with --yes
+fn retry_elevated(
+    result: &mut CleanupResult,
+    permission_denied: &[PathBuf],
+    audit_log: Option<&AuditLog>,
+) {
+    let (retried, mut failed): (Vec<_>, Vec<_>) = std::mem::take(&mut result.failed)
+        .into_iter()
+        .partition(|(p, _)| permission_denied.contains(&p.project_path));
+
+    for (project, e) in retried {
+        let target_path = &project.target_path;
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Deleting {} failed: {}\nDelete it with elevated privileges?",
+                pretty_format_path(target_path),
+                e
+            ))
+            .default(false)
+            .wait_for_newline(true)
+            .interact()
+            .unwrap_or(false);
+        if !confirmed {
+            failed.push((project, e));
+            continue;
+        }
+
+        let deletion_start = Instant::now();
+        let (left_size, _, left_files) = ProjectTargetAnalysis::recursive_scan_target(target_path);
+        let audited_files = audit_log
+            .map(|_| {
+                target_filter::deleted_files(
+                    &crate::canonicalize_or_not(target_path),
+                    &Default::default(),
+                )
+            })
+            .unwrap_or_default();
+        let deletion = elevate::remove_dir_all(target_path).map(|_| (left_size, left_files));
+        if let Some(audit_log) = audit_log {
+            if let Err(e) = audit_log.record_target(target_path, &audited_files, &deletion) {
+                eprintln!("Error writing the audit log: {}", e);
+            }
+        }
+        match deletion {
+            Ok((freed_size, deleted_files)) => {
+                let freed_before = result.freed_size;
+                result.cleaned += 1;
+                result.freed_size += freed_size;
+                result.deleted_files += deleted_files;
+                delete_other_outputs(&project, result, audit_log, &ProgressBar::hidden());
+                // The failed attempt already recorded what it freed before failing
+                let freed = result.freed_size - freed_before;
+                match result
+                    .freed_per_project
+                    .iter_mut()
+                    .find(|(path, _, _)| *path == project.project_path)
+                {
+                    Some((_, _, project_freed)) => *project_freed += freed,
+                    None => result.freed_per_project.push((
+                        project.project_path.clone(),
+                        project.target_path.clone(),
+                        freed,
+                    )),
+                }
+            }
+            Err(e) => failed.push((project, e)),
+        }
+        result.deletion_duration += deletion_start.elapsed();
+    }
+
+    result.failed = failed;
+}
+
+/// Estimate the time the deletion of the remaining files takes, based on the deletion rate so far
+fn estimate_remaining(result: &CleanupResult, remaining_files: u64) -> Option<Duration> {
+    if result.deleted_files == 0 {
+        return None;
+    }
+    let secs_per_file = result.deletion_duration.as_secs_f64() / result.deleted_files as f64;
+    Some(Duration::from_secs_f64(
+        secs_per_file * remaining_files as f64,
+    ))
+}
+
+/// Format the estimated time like `1h 05m`, `3m 20s` or `12s`
+fn format_eta(eta: Duration) -> String {
+    let secs = eta.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Recursively read the metadata of all files in the directory, until cancelled
+fn prefetch_metadata(dir: &Path, cancelled: &AtomicBool) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.filter_map(|it| it.ok()) {
+        if cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        // The metadata is queried using the path instead of the directory entry, since on Windows
+        // the entry metadata comes from the directory listing and doesn't touch the file itself
+        let path = entry.path();
+        if std::fs::symlink_metadata(&path).is_ok_and(|md| md.is_dir()) {
+            prefetch_metadata(&path, cancelled);
+        }
+    }
+}
+
+/// The outcome of deleting the target of a project
+struct Deletion<'a> {
+    tgt: &'a ProjectTargetAnalysis,
+    /// The path the target was renamed to, if it is deleted in the background
+    renamed: Option<PathBuf>,
+    /// The deleted files for the audit log
+    audited_files: Vec<(PathBuf, u64, SystemTime)>,
+    /// The freed size and the number of deleted files
+    result: io::Result<(u64, u64)>,
+}
+
+/// Delete the target of the project, or only the requested parts of it
+fn delete_target<'a>(
+    args: &AppArgs,
+    filter: &TargetFilter,
+    wsl_mounts: &[Mount],
+    tgt: &'a ProjectTargetAnalysis,
+    audited: bool,
+) -> Deletion<'a> {
+    let target_path = &tgt.target_path;
+    let artifacts = if args.prune_outdated_deps {
+        prune::outdated_artifacts(&crate::canonicalize_or_not(&tgt.project_path), target_path)
+    } else if let Some(budget) = args.per_project_budget {
+        Ok(trim::trimmed_artifacts(target_path, budget))
+    } else if let Some(days) = args.prune_older_than {
+        let since = SystemTime::now() - Duration::from_secs(u64::from(days) * 60 * 60 * 24);
+        Ok(trim::stale_artifacts(target_path, since))
+    } else if let Some(mode) = args.prune_toolchains {
+        toolchain::kept_versions(mode, &tgt.project_path)
+            .map(|kept| prune::foreign_toolchain_artifacts(target_path, &kept))
+    } else {
+        Ok(Vec::new())
+    };
+    let audited_files = match &artifacts {
+        _ if !audited => Vec::new(),
+        _ if !args.deletes_artifacts() => {
+            target_filter::deleted_files(&crate::canonicalize_or_not(target_path), filter)
+        }
+        Ok(artifacts) => prune::artifact_files(artifacts),
+        Err(_) => Vec::new(),
+    };
+    let result = if args.deletes_artifacts() {
+        artifacts.and_then(|artifacts| prune::delete_artifacts(&artifacts))
+    } else if args.use_cargo_clean {
+        // cargo doesn't report what it deleted, so the rest of the target is measured afterwards
+        cargo_clean::cargo_clean(
+            &tgt.project_path,
+            &args.profiles,
+            &args.cargo_clean_packages,
+        )
+        .map(|_| {
+            let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(target_path);
+            (
+                tgt.size.saturating_sub(size),
+                tgt.file_count.saturating_sub(file_count),
+            )
+        })
+    } else if !filter.deletes_all() {
+        target_filter::clean_target_except(target_path, filter)
+    } else if args.moves_to_trash() {
+        move_to_trash(target_path).map(|_| (tgt.size, tgt.file_count))
+    } else if mounts::mount_of(wsl_mounts, target_path).is_some_and(wsl::is_windows_drive) {
+        wsl::delete_natively(target_path).map(|_| (tgt.size, tgt.file_count))
+    } else {
+        remove_target(target_path, args.fix_permissions).map(|_| (tgt.size, tgt.file_count))
+    };
+    Deletion {
+        tgt,
+        renamed: None,
+        audited_files,
+        result,
+    }
+}
+
+/// Add the outcome of a deletion to the result and the audit log, and delete the other outputs of
+/// the project if the target was deleted
+fn record_deletion<'a>(
+    deletion: Deletion<'a>,
+    result: &mut CleanupResult,
+    permission_denied: &mut Vec<PathBuf>,
+    background: &mut Vec<BackgroundDeletion<'a>>,
+    audit_log: Option<&AuditLog>,
+    clean_progress: &ProgressBar,
+) {
+    let Deletion {
+        tgt,
+        renamed,
+        audited_files,
+        result: deletion,
+    } = deletion;
+    let freed_before = result.freed_size;
+    let target_path = &tgt.target_path;
+    match (renamed, audit_log) {
+        (Some(renamed), _) => background.push((tgt, renamed, audited_files)),
+        (None, Some(audit_log)) => {
+            if let Err(e) = audit_log.record_target(target_path, &audited_files, &deletion) {
+                report_audit_error(&e, clean_progress);
+            }
+        }
+        (None, None) => (),
+    }
+    match deletion {
+        Ok((freed_size, deleted_files)) => {
+            result.cleaned += 1;
+            result.freed_size += freed_size;
+            result.deleted_files += deleted_files;
+
+            delete_other_outputs(tgt, result, audit_log, clean_progress);
+        }
+        // A recursive deletion usually deletes a part of the target before failing, so the
+        // remaining contents are scanned again to account for what was actually freed
+        Err(e) => {
+            let (left_size, _, left_files) =
+                ProjectTargetAnalysis::recursive_scan_target(target_path);
+            result.freed_size += tgt.size.saturating_sub(left_size);
+            result.deleted_files += tgt.file_count.saturating_sub(left_files);
+            if e.kind() == io::ErrorKind::PermissionDenied {
+                permission_denied.push(tgt.project_path.clone());
+            }
+
+            let e = io::Error::new(
+                e.kind(),
+                format!("{} ({} left over)", e, bytefmt::format(left_size)),
+            );
+            result.failed.push((tgt.clone(), e));
+        }
+    }
+    result.freed_per_project.push((
+        tgt.project_path.clone(),
+        tgt.target_path.clone(),
+        result.freed_size - freed_before,
+    ));
+}
+
+/// Delete the coverage files and web outputs of a project whose target was deleted
+fn delete_other_outputs(
+    tgt: &ProjectTargetAnalysis,
+    result: &mut CleanupResult,
+    audit_log: Option<&AuditLog>,
+    clean_progress: &ProgressBar,
+) {
+    for (path, size) in tgt.coverage_files.iter() {
+        let modified = path.metadata().and_then(|md| md.modified());
+        match std::fs::remove_file(path) {
+            Ok(()) => {
+                result.freed_size += size;
+                result.deleted_files += 1;
+                if let Some(audit_log) = audit_log {
+                    let modified = modified.unwrap_or(SystemTime::UNIX_EPOCH);
+                    if let Err(e) = audit_log.record_deleted_file(path, *size, modified) {
+                        report_audit_error(&e, clean_progress);
+                    }
+                }
+            }
+            Err(e) => clean_progress.suspend(|| {
+                eprintln!(
+                    "Error deleting coverage file: '{}'  {}",
+                    pretty_format_path(path),
+                    e
+                )
+            }),
+        }
+    }
+
+    for (dir, size, file_count) in tgt.web_outputs.iter() {
+        let audited_files = audit_log
+            .map(|_| target_filter::deleted_files(dir, &Default::default()))
+            .unwrap_or_default();
+        let deletion = remove_dir_all::remove_dir_all(dir).map(|_| (*size, *file_count));
+        if let Some(audit_log) = audit_log {
+            if let Err(e) = audit_log.record_target(dir, &audited_files, &deletion) {
+                report_audit_error(&e, clean_progress);
+            }
+        }
+        match deletion {
+            Ok(_) => {
+                result.freed_size += size;
+                result.deleted_files += file_count;
+            }
+            Err(e) => clean_progress.suspend(|| {
+                eprintln!(
+                    "Error deleting web output directory: '{}'  {}",
+                    pretty_format_path(dir),
+                    e
+                )
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "trash")]
+fn move_to_trash(target_path: &Path) -> io::Result<()> {
+    trash::delete(target_path)
+        .map_err(|e| io::Error::other(format!("Moving to the trash failed: {}", e)))
+}
+
+#[cfg(not(feature = "trash"))]
+fn move_to_trash(_target_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Built without support for the trash",
+    ))
+}
+
+/// Rename the target to a new sibling, so that the project can be built again right away while the
+/// renamed target is deleted in the background. The index of the target in the cleanup keeps the
+/// names unique, since redirected targets of multiple projects can share a parent directory
+fn rename_for_deletion(target_path: &Path, idx: usize) -> io::Result<PathBuf> {
+    let renamed = target_path.with_file_name(format!(
+        "{}-{}-{}",
+        RENAMED_TARGET_PREFIX,
+        std::process::id(),
+        idx
+    ));
+    std::fs::rename(target_path, &renamed)?;
+    Ok(renamed)
+}
+
+/// The deletion of the renamed targets that were left over by an interrupted cleanup
+#[derive(Default)]
+pub struct LeftoverDeletion {
+    freed_size: u64,
+    deleted_files: u64,
+    duration: Duration,
+}
+
+impl LeftoverDeletion {
+    /// Delete the renamed target. Failures are only reported, since the leftover doesn't belong to
+    /// any of the cleaned projects anymore
+    pub fn delete(&mut self, renamed: &Path, fix_permissions: bool) {
+        let start = Instant::now();
+        let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(renamed);
+        match remove_target(renamed, fix_permissions) {
+            Ok(()) => {
+                self.freed_size += size;
+                self.deleted_files += file_count;
+            }
+            // It was deleted before the interruption
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => {
+                let (left_size, _, left_files) =
+                    ProjectTargetAnalysis::recursive_scan_target(renamed);
+                self.freed_size += size.saturating_sub(left_size);
+                self.deleted_files += file_count.saturating_sub(left_files);
+                eprintln!(
+                    "Error deleting the leftover {}: {}",
+                    pretty_format_path(renamed),
+                    e
+                );
+            }
+        }
+        self.duration += start.elapsed();
+    }
+
+    /// Add the freed space to the result of the cleanup
+    pub fn add_to(&self, result: &mut CleanupResult) {
+        result.freed_size += self.freed_size;
+        result.deleted_files += self.deleted_files;
+        result.deletion_duration += self.duration;
+    }
+}
+
+/// A target that is deleted in the background with its renamed path and the deleted files for the
+/// audit log
+type BackgroundDeletion<'a> = (
+    &'a ProjectTargetAnalysis,
+    PathBuf,
+    Vec<(PathBuf, u64, SystemTime)>,
+);
+
+/// Wait until the renamed targets are deleted in the background. Failed deletions are reported
+/// with the remaining contents of the renamed target, which is not touched again
+fn wait_for_background(
+    result: &mut CleanupResult,
+    background: &[BackgroundDeletion],
+    results: mpsc::Receiver<(usize, io::Result<()>)>,
+    audit_log: Option<&AuditLog>,
+    mut journal: Option<&mut Journal>,
+    on_progress: &dyn Fn(&ProjectTargetAnalysis, ProjectEvent),
+) {
+    let progress = progress_bar(background.len(), "Deleting in the background ");
+    for (id, deletion) in results {
+        let (tgt, renamed, audited_files) = &background[id];
+        progress.set_message(pretty_format_path(&tgt.project_path));
+        let deletion = match deletion {
+            Ok(()) => {
+                result.deleted_files += tgt.file_count;
+                Ok((tgt.size, tgt.file_count))
+            }
+            Err(e) => {
+                let (left_size, _, left_files) =
+                    ProjectTargetAnalysis::recursive_scan_target(renamed);
+                let left_size = left_size.min(tgt.size);
+                result.cleaned -= 1;
+                result.freed_size -= left_size;
+                result.deleted_files += tgt.file_count.saturating_sub(left_files);
+                if let Some((_, _, freed)) = result
+                    .freed_per_project
+                    .iter_mut()
+                    .find(|(path, _, _)| *path == tgt.project_path)
+                {
+                    *freed = freed.saturating_sub(left_size);
+                }
+                let e = io::Error::new(
+                    e.kind(),
+                    format!(
+                        "{} ({} left over in {})",
+                        e,
+                        bytefmt::format(left_size),
+                        pretty_format_path(renamed)
+                    ),
+                );
+                Err(e)
+            }
+        };
+        if let Some(audit_log) = audit_log {
+            let target_path = &tgt.target_path;
+            if let Err(e) = audit_log.record_target(target_path, audited_files, &deletion) {
+                report_audit_error(&e, &progress);
+            }
+        }
+        if let Err(e) = deletion {
+            result.failed.push(((*tgt).clone(), e));
+        }
+        if let Some(journal) = journal.as_deref_mut() {
+            if let Err(e) = journal.complete_renamed(&tgt.project_path) {
+                progress.suspend(|| eprintln!("Error updating the cleanup journal: {}", e));
+            }
+        }
+        on_progress(tgt, ProjectEvent::Finished(result));
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+}
+
+/// Delete the whole target directory. On macOS the native bulk removal is used first, if that
+/// fails the remaining entries are deleted one by one. With `fix_permissions`, a deletion that
+/// fails due to missing permissions is retried after making the remaining entries accessible
+fn remove_target(target_path: &Path, fix_permissions: bool) -> io::Result<()> {
+    match remove_dir_tree(target_path) {
+        Err(e) if fix_permissions && e.kind() == io::ErrorKind::PermissionDenied => {
+            make_owner_accessible(target_path);
+            remove_dir_tree(target_path)
+        }
+        result => result,
+    }
+}
+
+fn remove_dir_tree(target_path: &Path) -> io::Result<()> {
+    #[cfg(target_os = "macos")]
+    if crate::macos::remove_dir_all(target_path).is_ok() {
+        return Ok(());
+    }
+    remove_dir_all::remove_dir_all(target_path)
+}
+
+/// Recursively add the owner read, write and execute permissions (u+rwx) to the directories and
+/// the owner read and write permissions to the files, since some build scripts create read-only
+/// output directories that can't be deleted otherwise. Errors are ignored, since the retried
+/// deletion reports them anyway
+#[cfg(unix)]
+fn make_owner_accessible(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Ok(md) = std::fs::symlink_metadata(path) else {
+        return;
+    };
+    let required = if md.is_dir() {
+        0o700
+    } else if md.is_file() {
+        0o600
+    } else {
+        return;
+    };
+    let mode = md.permissions().mode();
+    if mode & required != required {
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode | required));
+    }
+
+    if md.is_dir() {
+        if let Ok(entries) = path.read_dir() {
+            for entry in entries.filter_map(|it| it.ok()) {
+                make_owner_accessible(&entry.path());
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn make_owner_accessible(_path: &Path) {}
+
+/// Preserve and archive the artifacts of a single project as requested by the args
+fn preserve_project(
+    args: &AppArgs,
+    project: &ProjectTargetAnalysis,
+    pb: &ProgressBar,
+    index_lock: &Mutex<()>,
+    audit_log: Option<&AuditLog>,
+) -> io::Result<()> {
+    if args.executable {
+        let preserved = preserve::preserve_executables(
+            &project.project_path,
+            &project.target_path,
+            pb,
+            args.verbose,
+        );
+        if let Some(audit_log) = audit_log {
+            for path in preserved {
+                if let Err(e) = audit_log.record_preserved(&path) {
+                    report_audit_error(&e, pb);
+                }
+            }
+        }
+    }
+    if args.keep_wasm {
+        preserve::preserve_wasm(
+            &project.project_path,
+            &project.target_path,
+            pb,
+            args.verbose,
+        );
+    }
+    if let Some(archive_dir) = &args.archive_docs {
+        archive::archive_docs(&project.project_path, &project.target_path, archive_dir)
+            .map_err(|e| io::Error::new(e.kind(), format!("Archiving docs failed: {}", e)))?;
+    }
+    if let Some(archive_dir) = &args.archive {
+        let entry = archive::archive_target(
+            &project.project_path,
+            &project.target_path,
+            archive_dir,
+            project.size,
+        )
+        .map_err(|e| io::Error::new(e.kind(), format!("Archiving target failed: {}", e)))?;
+
+        let _guard = index_lock.lock().unwrap();
+        if let Err(e) = archive::append_index(archive_dir, vec![entry.clone()]) {
+            // Without an index entry the archive can't be restored, so it is removed again
+            let _ = std::fs::remove_file(archive_dir.join(&entry.archive));
+            return Err(io::Error::new(
+                e.kind(),
+                format!("Updating the archive index failed: {}", e),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn report_audit_error(e: &io::Error, pb: &ProgressBar) {
+    pb.suspend(|| eprintln!("Error writing the audit log: {}", e));
+}
+
+/// Ask whether the given project should be cleaned
+fn confirm_project(project: &ProjectTargetAnalysis, pb: &ProgressBar) -> Confirmation {
+    pb.suspend(|| {
+        let term = Term::stderr();
+        loop {
+            let _ = term.write_str(&format!("Clean {}? [y]es, [n]o, [a]ll, [q]uit: ", project));
+            let answer = term.read_char();
+            let _ = term.write_line("");
+            match answer.map(|c| c.to_ascii_lowercase()) {
+                Ok('y') => return Confirmation::Yes,
+                Ok('n') => return Confirmation::No,
+                Ok('a') => return Confirmation::All,
+                Ok('q') | Err(_) => return Confirmation::Quit,
+                Ok(_) => continue,
+            }
+        }
+    })
+}
+
+fn progress_bar(len: usize, prefix: &str) -> ProgressBar {
+    ProgressBar::with_draw_target(Some(len as u64), output::progress_target()).with_style(
+        ProgressStyle::with_template(&format!(
+            "[{{elapsed}}] {}[{{bar:}}] {{pos}}/{{len}}: {{msg}}",
+            prefix
+        ))
+        .expect("Invalid template syntax")
+        .progress_chars("#>-"),
+    )
+}
@@ -0,0 +1,288 @@
+//! The per-user configuration directory with the config file and the persistent ignore list that
+//! are stored in it.
+
+use crate::AppArgs;
+use clap::{parser::ValueSource, ArgMatches};
+use serde::Deserialize;
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// Name of the persistent ignore list in the config directory. It contains one project path per
+/// line
+pub const IGNORE_LIST_FILE_NAME: &str = "ignore.txt";
+
+/// Name of the config file in the config directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// The config directory of cargo-clean-all, which is `$XDG_CONFIG_HOME/cargo-clean-all` (or
+/// `~/.config/cargo-clean-all`) on Linux, `~/Library/Application Support/cargo-clean-all` on macOS
+/// and `%APPDATA%\cargo-clean-all` on Windows
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::home_dir().map(|home| home.join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::home_dir().map(|home| home.join(".config")))
+    };
+    base.map(|dir| dir.join("cargo-clean-all"))
+}
+
+/// Read the projects that were permanently ignored. A missing ignore list is treated as empty
+pub fn read_ignore_list() -> io::Result<Vec<PathBuf>> {
+    let Some(path) = config_dir().map(|dir| dir.join(IGNORE_LIST_FILE_NAME)) else {
+        return Ok(Vec::new());
+    };
+
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(PathBuf::from)
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Append the given projects to the persistent ignore list. The paths are canonicalized so that
+/// they match regardless of the directory cargo-clean-all is started in
+pub fn append_ignore_list(projects: &[PathBuf]) -> io::Result<()> {
+    let dir = config_dir().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "The config directory could not be determined",
+        )
+    })?;
+    std::fs::create_dir_all(&dir)?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(IGNORE_LIST_FILE_NAME))?;
+
+    for project in projects {
+        writeln!(file, "{}", crate::canonicalize_or_not(project).display())?;
+    }
+    Ok(())
+}
+
+/// The defaults for the options of a run. Options that are given on the command line take
+/// precedence over the config file, which takes precedence over the built-in defaults. The
+/// `ignore`, `skip` and `skip-names` lists are added to the ones given on the command line
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The directories that are scanned when no DIR is given
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Default for `--keep-days`
+    pub keep_days: Option<u32>,
+    /// Default for `--keep-size`
+    #[serde(default, deserialize_with = "crate::policy::deserialize_opt_size")]
+    pub keep_size: Option<u64>,
+    /// Added to `--ignore`
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Added to `--skip`
+    #[serde(default)]
+    pub skip: Vec<String>,
+    /// Added to `--skip-name`
+    #[serde(default)]
+    pub skip_names: Vec<String>,
+    /// Default for `--no-default-skips`
+    pub no_default_skips: Option<bool>,
+    /// Default for `--scan-threads`
+    pub scan_threads: Option<usize>,
+    /// Default for `--delete-threads`
+    pub delete_threads: Option<usize>,
+    /// Default for `--yes`
+    pub yes: Option<bool>,
+}
+
+impl Config {
+    /// Use the values of the config for the options that were not given on the command line
+    pub fn apply(&self, args: &mut AppArgs, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        // The roots only replace the default directory, not the other ways to choose the projects
+        let chooses_projects = args.all_users || args.no_scan || args.projects_from.is_some();
+        if !self.roots.is_empty() && !from_cli("root_dirs") && !chooses_projects {
+            args.root_dirs = self
+                .roots
+                .iter()
+                .map(|root| expand_home(root).to_string_lossy().into_owned())
+                .collect();
+        }
+        if let Some(keep_days) = self.keep_days.filter(|_| !from_cli("keep_last_modified")) {
+            args.keep_last_modified = keep_days;
+        }
+        if let Some(keep_size) = self.keep_size.filter(|_| !from_cli("keep_size")) {
+            args.keep_size = keep_size;
+        }
+        let expand = |dirs: &[String]| {
+            dirs.iter()
+                .map(|dir| expand_home(dir).to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+        args.ignore.extend(expand(&self.ignore));
+        args.skip.extend(expand(&self.skip));
+        args.skip_names.extend(self.skip_names.iter().cloned());
+        if let Some(no_default_skips) = self
+            .no_default_skips
+            .filter(|_| !from_cli("no_default_skips"))
+        {
+            args.no_default_skips = no_default_skips;
+        }
+        if let Some(threads) = self.scan_threads.filter(|_| !from_cli("scan_threads")) {
+            args.scan_threads = threads;
+        }
+        if let Some(threads) = self.delete_threads.filter(|_| !from_cli("delete_threads")) {
+            args.delete_threads = threads;
+        }
+        // `--yes` can't be turned off on the command line, so `--confirm-each`, which conflicts
+        // with it, does that instead
+        if let Some(yes) = self.yes.filter(|_| !from_cli("yes")) {
+            args.yes = yes && !args.confirm_each;
+        }
+    }
+}
+
+/// Expand a leading `~` to the home directory, since the paths in the config file and the answers
+/// of `init` are not expanded by a shell
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), std::env::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// The path of the config file
+pub fn config_file() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+}
+
+/// Read the config file. Returns `None` if there is none
+pub fn read_config() -> io::Result<Option<Config>> {
+    let Some(path) = config_file() else {
+        return Ok(None);
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).map(Some).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// The units that sizes are written with, from the largest to the smallest
+const SIZE_UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("KB", 1_000),
+];
+
+/// Render the config file with comments that explain the options. Options without a value are
+/// written as comments, so that they can be enabled later
+fn render_config(roots: &[PathBuf], keep_days: u32, keep_size: u64) -> String {
+    let roots = match roots {
+        [] => "# roots = [\"~/projects\"]".to_string(),
+        roots => format!(
+            "roots = {}",
+            toml::Value::Array(
+                roots
+                    .iter()
+                    .map(|root| toml::Value::String(root.to_string_lossy().into_owned()))
+                    .collect(),
+            )
+        ),
+    };
+    let keep_days = match keep_days {
+        0 => "# keep-days = 30".to_string(),
+        days => format!("keep-days = {}", days),
+    };
+    // Sizes are written with the largest unit that doesn't round them
+    let keep_size = match SIZE_UNITS
+        .iter()
+        .find(|(_, unit)| keep_size.is_multiple_of(*unit))
+    {
+        _ if keep_size == 0 => "# keep-size = \"100MB\"".to_string(),
+        Some((name, unit)) => format!("keep-size = \"{}{}\"", keep_size / unit, name),
+        None => format!("keep-size = {}", keep_size),
+    };
+
+    format!(
+        "# The defaults of cargo-clean-all. Options that are given on the command line take\n\
+         # precedence. Permanently ignored projects are listed in {}\n\
+         \n\
+         # The directories that are scanned when no directory is given\n\
+         {}\n\
+         \n\
+         # Ignore projects that have been compiled in the last number of days\n\
+         {}\n\
+         \n\
+         # Ignore projects with a target directory smaller than the size\n\
+         {}\n\
+         \n\
+         # Directories that are added to --ignore and --skip\n\
+         # ignore = [\"~/work\"]\n\
+         # skip = [\"~/.local\"]\n\
+         \n\
+         # Names of directories that are skipped wherever they are, in addition to the built-in\n\
+         # list of build and cache directories of other languages\n\
+         # skip-names = [\"dist\"]\n\
+         # no-default-skips = false\n\
+         \n\
+         # The number of threads for scanning and deleting, 0 selects them automatically\n\
+         # scan-threads = 0\n\
+         # delete-threads = 0\n\
+         \n\
+         # Clean without asking for confirmation\n\
+         # yes = false\n",
+        IGNORE_LIST_FILE_NAME, roots, keep_days, keep_size
+    )
+}
+
+/// Write a new config file. Fails if there already is one, so that nothing is overwritten by
+/// accident
+pub fn write_config(roots: &[PathBuf], keep_days: u32, keep_size: u64) -> io::Result<PathBuf> {
+    let path = config_file().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "The config directory could not be determined",
+        )
+    })?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => io::Error::new(
+                e.kind(),
+                format!("{} already exists", crate::pretty_format_path(&path)),
+            ),
+            _ => e,
+        })?;
+    file.write_all(render_config(roots, keep_days, keep_size).as_bytes())?;
+    Ok(path)
+}
@@ -0,0 +1,43 @@
+//! Detection of the coverage data files (`*.profraw` and `*.gcda`) that coverage tools like
+//! llvm-cov leave in the project directories outside of the target directory.
+
+use std::path::{Path, PathBuf};
+
+const COVERAGE_EXTENSIONS: &[&str] = &["profraw", "gcda"];
+
+/// Recursively find the coverage files in the project directory together with their sizes. The
+/// target directory, `.git` and nested projects with their own target directory are not searched.
+pub fn find_coverage_files(project_path: &Path) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    find_in_dir(project_path, &mut files);
+    files
+}
+
+fn find_in_dir(dir: &Path, files: &mut Vec<(PathBuf, u64)>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+
+    for entry in entries.filter_map(|it| it.ok()) {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            let is_nested_project =
+                path.join("Cargo.toml").is_file() && path.join("target").is_dir();
+            if name != "target" && name != ".git" && !is_nested_project {
+                find_in_dir(&path, files);
+            }
+        } else if file_type.is_file()
+            && path
+                .extension()
+                .is_some_and(|ext| COVERAGE_EXTENSIONS.iter().any(|c| ext == *c))
+        {
+            let size = entry.metadata().map(|md| md.len()).unwrap_or(0);
+            files.push((path, size));
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! The `daemon` subcommand, which offers scanning and cleaning as JSON-RPC 2.0 API over HTTP on a
+//! unix socket. Editor extensions and other tools can scan once and clean the found projects
+//! later, instead of running and parsing the CLI for every operation.
+//!
+//! The requests are posted to `/` and support the following methods:
+//! - `scan` with `{"args": [...]}`: Scan using the given CLI arguments and the config file like a
+//!   normal run and return the found projects together with whether they are selected. Paths
+//!   should be absolute, since they are resolved relative to the working directory of the daemon
+//! - `list`: Return the projects of the last scan that were not cleaned yet
+//! - `clean` with `{"paths": [...]}`: Clean the given projects of the last scan using the cleanup
+//!   options of its arguments
+
+use crate::{
+    cleanup, config, i18n, instance_lock, journal::Journal, json::ProjectInfo, output,
+    pretty_format_path, AppArgs, Cli, ProjectTargetAnalysis, RunMode,
+};
+use clap::{CommandFactory, FromArgMatches};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    io,
+    os::unix::{fs::PermissionsExt, net::UnixStream},
+    path::PathBuf,
+    sync::{atomic::Ordering, Mutex},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Name of the default socket in the config directory
+const SOCKET_FILE_NAME: &str = "daemon.sock";
+
+// The JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The code of errors that occurred while executing a method
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ScanParams {
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CleanParams {
+    paths: Vec<PathBuf>,
+}
+
+struct LastScan {
+    /// The arguments of the scan, which also determine how the projects are cleaned
+    args: Vec<String>,
+    projects: Vec<(ProjectTargetAnalysis, bool)>,
+}
+
+#[derive(Default)]
+struct Daemon {
+    last_scan: Mutex<Option<LastScan>>,
+    /// Only one scan or cleanup can run at a time, since they use process-global state like the
+    /// scan state, the quiet flag, the locale and whether symlinks are followed
+    busy: Mutex<()>,
+}
+
+/// Parse the CLI arguments of a normal run and apply the config file to them. Everything that
+/// would need a terminal is disabled
+fn parse_args(args: &[String]) -> Result<AppArgs, RpcError> {
+    let invalid =
+        |e: clap::Error| RpcError::new(INVALID_PARAMS, format!("Invalid arguments: {}", e));
+    let matches = Cli::command()
+        .try_get_matches_from(
+            std::iter::once("cargo-clean-all").chain(args.iter().map(String::as_str)),
+        )
+        .map_err(invalid)?;
+    let cli = Cli::from_arg_matches(&matches).map_err(invalid)?;
+    if cli.command.is_some() {
+        return Err(RpcError::new(
+            INVALID_PARAMS,
+            "Subcommands are not supported",
+        ));
+    }
+
+    let mut args = cli.args;
+    match config::read_config() {
+        Ok(Some(config)) => config.apply(&mut args, &matches),
+        Ok(None) => (),
+        Err(e) => {
+            return Err(RpcError::new(
+                SERVER_ERROR,
+                format!("Failed to read the config file: {}", e),
+            ))
+        }
+    }
+    args.interactive = false;
+    args.confirm_each = false;
+    args.elevate = false;
+    #[cfg(feature = "tui")]
+    {
+        args.tui = false;
+    }
+    // Invalid combinations would exit the process otherwise
+    crate::check_args(&args).map_err(|e| RpcError::new(INVALID_PARAMS, e))?;
+    Ok(args)
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+}
+
+impl Daemon {
+    fn scan(&self, params: ScanParams) -> Result<Value, RpcError> {
+        let args = parse_args(&params.args)?;
+        if crate::refuses_root(&args) {
+            return Err(RpcError::new(SERVER_ERROR, crate::ROOT_REFUSAL));
+        }
+        // Invalid scan roots would exit the process otherwise
+        crate::scan_roots(&args).map_err(|e| RpcError::new(SERVER_ERROR, e))?;
+
+        let _busy = self.busy.lock().unwrap();
+        let mut projects = Vec::new();
+        crate::clean(args, &params.args, RunMode::Collect(&mut projects));
+
+        let infos: Vec<_> = projects
+            .iter()
+            .map(|(p, selected)| ProjectInfo::new(p, *selected))
+            .collect();
+        *self.last_scan.lock().unwrap() = Some(LastScan {
+            args: params.args,
+            projects,
+        });
+        Ok(json!({ "projects": infos }))
+    }
+
+    fn list(&self) -> Result<Value, RpcError> {
+        let last_scan = self.last_scan.lock().unwrap();
+        let last_scan = last_scan
+            .as_ref()
+            .ok_or_else(|| RpcError::new(SERVER_ERROR, "No scan was done yet"))?;
+        let infos: Vec<_> = last_scan
+            .projects
+            .iter()
+            .map(|(p, selected)| ProjectInfo::new(p, *selected))
+            .collect();
+        Ok(json!({ "projects": infos }))
+    }
+
+    fn clean(&self, params: CleanParams) -> Result<Value, RpcError> {
+        let _busy = self.busy.lock().unwrap();
+        let (raw_args, chosen) = {
+            let last_scan = self.last_scan.lock().unwrap();
+            let last_scan = last_scan
+                .as_ref()
+                .ok_or_else(|| RpcError::new(SERVER_ERROR, "No scan was done yet"))?;
+
+            let mut chosen = Vec::new();
+            for path in &params.paths {
+                let path = crate::canonicalize_or_not(path);
+                let (project, _) = last_scan
+                    .projects
+                    .iter()
+                    .find(|(p, _)| crate::canonicalize_or_not(&p.project_path) == path)
+                    .ok_or_else(|| {
+                        RpcError::new(
+                            INVALID_PARAMS,
+                            format!(
+                                "{} was not found by the last scan",
+                                pretty_format_path(&path)
+                            ),
+                        )
+                    })?;
+                if project.policy.never_clean {
+                    return Err(RpcError::new(
+                        INVALID_PARAMS,
+                        format!(
+                            "{} is configured to never be cleaned",
+                            pretty_format_path(&path)
+                        ),
+                    ));
+                }
+                // A project that is requested twice would otherwise be deleted twice in parallel
+                if !chosen
+                    .iter()
+                    .any(|c: &ProjectTargetAnalysis| c.target_path == project.target_path)
+                {
+                    chosen.push(project.clone());
+                }
+            }
+            (last_scan.args.clone(), chosen)
+        };
+
+        let args = parse_args(&raw_args)?;
+        if args.dry_run {
+            return Err(RpcError::new(
+                SERVER_ERROR,
+                "The last scan was a dry run. Not doing any cleanup",
+            ));
+        }
+        let _lock = instance_lock::lock().map_err(|e| RpcError::new(SERVER_ERROR, e))?;
+
+        // The state of the last request may differ from the one of the scan
+        output::set_quiet(true);
+        crate::FOLLOW_SYMLINKS.store(args.follow_symlinks, Ordering::Relaxed);
+        if let Some(locale) = args.lang {
+            i18n::set_locale(locale);
+        }
+
+        let mut journal = Journal::start(&raw_args, &chosen);
+        let result = cleanup::cleanup(&args, &chosen, journal.as_mut());
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+
+        // The cleaned projects are removed from the last scan
+        let failed: Vec<_> = result
+            .failed
+            .iter()
+            .map(|(p, e)| {
+                json!({ "path": pretty_format_path(&p.project_path), "error": e.to_string() })
+            })
+            .collect();
+        if let Some(last_scan) = self.last_scan.lock().unwrap().as_mut() {
+            last_scan.projects.retain(|(p, _)| {
+                !chosen.iter().any(|c| c.project_path == p.project_path)
+                    || result
+                        .failed
+                        .iter()
+                        .any(|(f, _)| f.project_path == p.project_path)
+            });
+        }
+
+        Ok(json!({
+            "cleaned": result.cleaned,
+            "freed": result.freed_size,
+            "deleted_files": result.deleted_files,
+            "failed": failed,
+        }))
+    }
+
+    fn handle(&self, mut request: Request) {
+        if *request.method() != Method::Post {
+            respond(request, 405, "Only POST requests are supported".to_string());
+            return;
+        }
+
+        let mut body = String::new();
+        if let Err(e) = request.as_reader().read_to_string(&mut body) {
+            respond(request, 400, format!("Reading the request failed: {}", e));
+            return;
+        }
+
+        let (id, result) = match serde_json::from_str::<RpcRequest>(&body) {
+            Ok(rpc) => {
+                let result = match rpc.method.as_str() {
+                    "scan" => params(rpc.params).and_then(|p| self.scan(p)),
+                    "list" => self.list(),
+                    "clean" => params(rpc.params).and_then(|p| self.clean(p)),
+                    method => Err(RpcError::new(
+                        METHOD_NOT_FOUND,
+                        format!("Unknown method {}", method),
+                    )),
+                };
+                (rpc.id, result)
+            }
+            Err(e) => (Value::Null, Err(RpcError::new(PARSE_ERROR, e))),
+        };
+
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": e.code, "message": e.message },
+            }),
+        };
+        respond(request, 200, response.to_string());
+    }
+}
+
+fn respond(request: Request, status: u16, body: String) {
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes("Content-Type", "application/json").expect("Invalid header"),
+        );
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send the response: {}", e);
+    }
+}
+
+/// Serve the API on the socket until the program is stopped. Each request is handled in its own
+/// thread, so that listing works while a cleanup is running
+pub fn run(socket: Option<PathBuf>) -> io::Result<()> {
+    let path = match socket {
+        Some(path) => path,
+        None => config::config_dir()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "The config directory could not be determined",
+                )
+            })?
+            .join(SOCKET_FILE_NAME),
+    };
+
+    // A socket that nobody listens on is left over from a daemon that was stopped
+    if path.exists() {
+        if UnixStream::connect(&path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("A daemon is already listening on {}", path.display()),
+            ));
+        }
+        std::fs::remove_file(&path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Only the current user may scan and clean through the daemon. The socket is created with
+    // these permissions, so that nobody can connect before they are set
+    let umask = unsafe { libc::umask(0o177) };
+    let server = Server::http_unix(&path).map_err(io::Error::other);
+    unsafe { libc::umask(umask) };
+    let server = server?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    println!("Listening on {}, press Ctrl+C to stop", path.display());
+
+    let daemon = Daemon::default();
+    thread::scope(|scope| {
+        for request in server.incoming_requests() {
+            let daemon = &daemon;
+            scope.spawn(move || daemon.handle(request));
+        }
+    });
+    Ok(())
+}
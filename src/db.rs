@@ -0,0 +1,237 @@
+//! The SQLite database of the `--db` option, which keeps a snapshot of every scan together with the
+//! result of the cleanup. A database can be shared by multiple machines and is meant to be read by
+//! other tools, so the schema only uses plain columns:
+//!
+//! - `runs`: One row per run with the `host`, the `started` time in seconds since the unix epoch,
+//!   the CLI `args` as JSON array and whether it was a `dry_run` or a `partial_scan`. The totals of
+//!   the cleanup (`cleaned`, `failed`, `freed`, `deleted_files` and `deletion_ms`) are `NULL` for
+//!   runs that didn't clean anything
+//! - `projects`: The projects found by the run (`run_id`) with the canonical `path`, the `size` and
+//!   `file_count` of the target, its `last_modified` time, the `toolchains` as JSON array and
+//!   whether the project was `selected`
+//! - `cleanups`: The processed projects of the run with the `freed` size and the `error` if the
+//!   cleanup failed
+
+use crate::{
+    canonicalize_or_not, cleanup::CleanupResult, pretty_format_path, ProjectTargetAnalysis,
+};
+use rusqlite::{params, Connection};
+use std::{
+    io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The version of the schema, stored as `user_version` of the database
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    host TEXT NOT NULL,
+    started INTEGER NOT NULL,
+    args TEXT NOT NULL,
+    dry_run INTEGER NOT NULL,
+    partial_scan INTEGER NOT NULL,
+    cleaned INTEGER,
+    failed INTEGER,
+    freed INTEGER,
+    deleted_files INTEGER,
+    deletion_ms INTEGER
+);
+CREATE TABLE IF NOT EXISTS projects (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    file_count INTEGER NOT NULL,
+    last_modified INTEGER NOT NULL,
+    toolchains TEXT NOT NULL,
+    selected INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS cleanups (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    path TEXT NOT NULL,
+    freed INTEGER NOT NULL,
+    error TEXT
+);
+CREATE INDEX IF NOT EXISTS projects_run ON projects(run_id);
+CREATE INDEX IF NOT EXISTS projects_path ON projects(path);
+CREATE INDEX IF NOT EXISTS cleanups_run ON cleanups(run_id);
+";
+
+/// How long to wait for other machines or tools that are writing to the database at the same time
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A run that was recorded in the database
+pub struct Db {
+    conn: Connection,
+    run_id: i64,
+}
+
+fn db_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(format!("SQLite error: {}", e))
+}
+
+fn unix_secs(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+fn path_string(path: &Path) -> String {
+    pretty_format_path(&canonicalize_or_not(path))
+}
+
+/// The name of this machine, which distinguishes the runs of multiple machines in a shared database
+fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        // SAFETY: The buffer is valid for its length and the name is only read from it after
+        // the call succeeded
+        if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } == 0 {
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            return String::from_utf8_lossy(&buf[..len]).into_owned();
+        }
+    }
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Open the database and create the tables if they don't exist yet
+fn open(path: &Path) -> io::Result<Connection> {
+    let conn = Connection::open(path).map_err(db_error)?;
+    conn.busy_timeout(BUSY_TIMEOUT).map_err(db_error)?;
+
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .map_err(db_error)?;
+    if version > SCHEMA_VERSION {
+        return Err(io::Error::other(format!(
+            "The database was created by a newer version (schema version {})",
+            version
+        )));
+    }
+    conn.execute_batch(SCHEMA).map_err(db_error)?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+        .map_err(db_error)?;
+    Ok(conn)
+}
+
+impl Db {
+    /// Record a new run with the projects it found
+    pub fn record_scan(
+        path: &Path,
+        raw_args: &[String],
+        dry_run: bool,
+        partial_scan: bool,
+        selected: &[ProjectTargetAnalysis],
+        ignored: &[ProjectTargetAnalysis],
+    ) -> io::Result<Self> {
+        let mut conn = open(path)?;
+        let tx = conn.transaction().map_err(db_error)?;
+
+        tx.execute(
+            "INSERT INTO runs (host, started, args, dry_run, partial_scan) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                hostname(),
+                unix_secs(SystemTime::now()),
+                serde_json::to_string(raw_args)?,
+                dry_run,
+                partial_scan
+            ],
+        )
+        .map_err(db_error)?;
+        let run_id = tx.last_insert_rowid();
+
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO projects (run_id, path, size, file_count, last_modified, \
+                     toolchains, selected) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                )
+                .map_err(db_error)?;
+            for (project, is_selected) in selected
+                .iter()
+                .map(|p| (p, true))
+                .chain(ignored.iter().map(|p| (p, false)))
+            {
+                let toolchains: Vec<_> =
+                    project.toolchains.iter().map(|tc| tc.to_string()).collect();
+                insert
+                    .execute(params![
+                        run_id,
+                        path_string(&project.project_path),
+                        project.total_size() as i64,
+                        project.file_count as i64,
+                        unix_secs(project.last_modified),
+                        serde_json::to_string(&toolchains)?,
+                        is_selected
+                    ])
+                    .map_err(db_error)?;
+            }
+        }
+        tx.commit().map_err(db_error)?;
+
+        Ok(Self { conn, run_id })
+    }
+
+    /// Add the result of the cleanup to the recorded run
+    pub fn record_cleanup(&mut self, result: &CleanupResult) -> io::Result<()> {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        tx.execute(
+            "UPDATE runs SET cleaned = ?1, failed = ?2, freed = ?3, deleted_files = ?4, \
+             deletion_ms = ?5 WHERE id = ?6",
+            params![
+                result.cleaned as i64,
+                result.failed.len() as i64,
+                result.freed_size as i64,
+                result.deleted_files as i64,
+                result.deletion_duration.as_millis() as i64,
+                self.run_id
+            ],
+        )
+        .map_err(db_error)?;
+
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO cleanups (run_id, path, freed, error) VALUES (?1, ?2, ?3, ?4)",
+                )
+                .map_err(db_error)?;
+            let error_of = |path: &Path| {
+                result
+                    .failed
+                    .iter()
+                    .find(|(p, _)| p.project_path == path)
+                    .map(|(_, e)| e.to_string())
+            };
+            for (path, _, freed) in &result.freed_per_project {
+                insert
+                    .execute(params![
+                        self.run_id,
+                        path_string(path),
+                        *freed as i64,
+                        error_of(path)
+                    ])
+                    .map_err(db_error)?;
+            }
+            // Projects that failed before anything was deleted
+            for (project, e) in result.failed.iter().filter(|(p, _)| {
+                !result
+                    .freed_per_project
+                    .iter()
+                    .any(|(path, _, _)| *path == p.project_path)
+            }) {
+                insert
+                    .execute(params![
+                        self.run_id,
+                        path_string(&project.project_path),
+                        0,
+                        e.to_string()
+                    ])
+                    .map_err(db_error)?;
+            }
+        }
+        tx.commit().map_err(db_error)
+    }
+}
@@ -0,0 +1,44 @@
+//! The `doctor` subcommand, which shows the locations and the environment that are used, to help
+//! diagnosing why caches or configuration files are not found.
+
+use crate::{cargo_cache, config, journal::Journal, output::outln, pretty_format_path};
+use std::path::Path;
+
+/// The path together with the notes about it, like where it comes from
+fn describe(path: Option<&Path>, mut notes: Vec<&str>) -> String {
+    let Some(path) = path else {
+        return "unknown".to_string();
+    };
+    if !path.exists() {
+        notes.push("does not exist");
+    }
+    if notes.is_empty() {
+        pretty_format_path(path)
+    } else {
+        format!("{} ({})", pretty_format_path(path), notes.join(", "))
+    }
+}
+
+/// Print the resolved locations
+pub fn doctor() {
+    outln!(
+        "Config directory: {}",
+        describe(config::config_dir().as_deref(), Vec::new())
+    );
+
+    let source = if cargo_cache::cargo_home_from_env().is_some() {
+        "from CARGO_HOME"
+    } else {
+        "default"
+    };
+    outln!(
+        "Cargo home:       {}",
+        describe(cargo_cache::cargo_home().as_deref(), vec![source])
+    );
+
+    match Journal::load() {
+        Ok(Some(journal)) => outln!("Interrupted cleanup started at {}", journal.started),
+        Ok(None) => (),
+        Err(e) => outln!("The cleanup journal can't be read: {}", e),
+    }
+}
@@ -0,0 +1,38 @@
+//! Deletion of directories with elevated privileges, used for targets that can't be deleted by
+//! the current user, like the root owned targets created by builds in Docker containers.
+
+use std::{io, path::Path, process::Command};
+
+/// Recursively delete the directory using `sudo` on Unix or an elevated `rmdir` on Windows, which
+/// shows the UAC prompt
+pub fn remove_dir_all(dir: &Path) -> io::Result<()> {
+    let status = if cfg!(windows) {
+        // Single quotes are escaped by doubling them inside the PowerShell string
+        let dir = dir.display().to_string().replace('\'', "''");
+        Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!(
+                    "Start-Process -FilePath cmd.exe -ArgumentList '/c rmdir /s /q \"{}\"' -Verb RunAs -Wait -WindowStyle Hidden",
+                    dir
+                ),
+            ])
+            .status()?
+    } else {
+        Command::new("sudo")
+            .args(["rm", "-rf", "--"])
+            .arg(dir)
+            .status()?
+    };
+
+    // Neither the UAC prompt nor rmdir reliably report failures using the exit code, so the
+    // result is checked as well
+    if !status.success() || dir.exists() {
+        return Err(io::Error::other(format!(
+            "Deleting '{}' with elevated privileges failed",
+            dir.display()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,372 @@
+//! A small expression language that is used to select projects by their properties.
+//!
+//! Example: `size > 2GiB && age_days > 60 && path ~ "experiments"`
+//!
+//! Supported fields are `size` (bytes of the target dir), `age_days` (days since the last
+//! modification of the target dir), `name` (the project directory name) and `path` (the project
+//! path). Numbers can be compared using `<`, `<=`, `>`, `>=`, `==` and `!=`. Sizes can be written
+//! with units like `10MB` or `2GiB`. Strings can be compared using `==` and `!=`, or matched
+//! against a regular expression using `~` and `!~`. Comparisons can be combined with `&&`, `||`
+//! and `!` and grouped using parentheses.
+
+use regex::Regex;
+
+/// The properties of a project that can be referenced in an expression
+pub struct ExprFields<'a> {
+    pub size: u64,
+    pub age_days: f64,
+    pub name: &'a str,
+    pub path: &'a str,
+}
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Num(NumField, CmpOp, f64),
+    Str(StrField, CmpOp, String),
+    Match(StrField, bool, Regex),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum NumField {
+    Size,
+    AgeDays,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum StrField {
+    Name,
+    Path,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn cmp<T: PartialOrd + ?Sized>(self, a: &T, b: &T) -> bool {
+        match self {
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+        }
+    }
+}
+
+impl Expr {
+    /// Parse an expression from a string. This is intended to be used as a clap value parser.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(tok) => Err(format!("Unexpected token '{}'", tok)),
+        }
+    }
+
+    /// Evaluate the expression for the given project properties
+    pub fn matches(&self, fields: &ExprFields) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(fields) && b.matches(fields),
+            Expr::Or(a, b) => a.matches(fields) || b.matches(fields),
+            Expr::Not(e) => !e.matches(fields),
+            Expr::Num(field, op, value) => {
+                let actual = match field {
+                    NumField::Size => fields.size as f64,
+                    NumField::AgeDays => fields.age_days,
+                };
+                op.cmp(&actual, value)
+            }
+            Expr::Str(field, op, value) => op.cmp(field.get(fields), value.as_str()),
+            Expr::Match(field, negated, re) => re.is_match(field.get(fields)) != *negated,
+        }
+    }
+}
+
+impl StrField {
+    fn get<'a>(self, fields: &ExprFields<'a>) -> &'a str {
+        match self {
+            StrField::Name => fields.name,
+            StrField::Path => fields.path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Op(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(s) => write!(f, "{}", s),
+            Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::Op(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+const OPERATORS: &[&str] = &[
+    "&&", "||", "<=", ">=", "==", "!=", "!~", "<", ">", "~", "!", "(", ")",
+];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c == '"' || c == '\'' {
+            let end = rest[1..]
+                .find(c)
+                .ok_or_else(|| "Unterminated string literal".to_string())?;
+            tokens.push(Token::Str(rest[1..end + 1].to_string()));
+            rest = &rest[end + 2..];
+        } else if c.is_alphanumeric() || c == '_' || c == '.' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            return Err(format!("Unexpected character '{}'", c));
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let tok = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| "Unexpected end of expression".to_string())?;
+        self.pos += 1;
+        Ok(tok)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_and()?;
+        while self.eat("||") {
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.parse_and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.parse_unary()?;
+        while self.eat("&&") {
+            lhs = Expr::And(Box::new(lhs), Box::new(self.parse_unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat("(") {
+            let expr = self.parse_or()?;
+            if !self.eat(")") {
+                return Err("Expected ')'".to_string());
+            }
+            return Ok(expr);
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let field = match self.next()? {
+            Token::Ident(field) => field,
+            tok => return Err(format!("Expected a field name, found '{}'", tok)),
+        };
+        let op = match self.next()? {
+            Token::Op(op) => op,
+            tok => return Err(format!("Expected an operator, found '{}'", tok)),
+        };
+        let value = match self.next()? {
+            Token::Ident(v) | Token::Str(v) => v,
+            tok => return Err(format!("Expected a value, found '{}'", tok)),
+        };
+
+        match field.as_str() {
+            "size" | "age_days" => {
+                let cmp = parse_cmp_op(op)
+                    .ok_or_else(|| format!("Operator '{}' can't be used with '{}'", op, field))?;
+                let (field, value) = if field == "size" {
+                    (NumField::Size, bytefmt::parse(value.as_str())? as f64)
+                } else {
+                    let days = value
+                        .parse()
+                        .map_err(|_| format!("Invalid number of days '{}'", value))?;
+                    (NumField::AgeDays, days)
+                };
+                Ok(Expr::Num(field, cmp, value))
+            }
+            "name" | "path" => {
+                let field = if field == "name" {
+                    StrField::Name
+                } else {
+                    StrField::Path
+                };
+                match op {
+                    "~" | "!~" => {
+                        let re = Regex::new(&value).map_err(|e| e.to_string())?;
+                        Ok(Expr::Match(field, op == "!~", re))
+                    }
+                    "==" => Ok(Expr::Str(field, CmpOp::Eq, value)),
+                    "!=" => Ok(Expr::Str(field, CmpOp::Ne, value)),
+                    _ => Err(format!("Operator '{}' can't be used with strings", op)),
+                }
+            }
+            _ => Err(format!(
+                "Unknown field '{}'. Valid fields are size, age_days, name and path",
+                field
+            )),
+        }
+    }
+}
+
+fn parse_cmp_op(op: &str) -> Option<CmpOp> {
+    Some(match op {
+        "<" => CmpOp::Lt,
+        "<=" => CmpOp::Le,
+        ">" => CmpOp::Gt,
+        ">=" => CmpOp::Ge,
+        "==" => CmpOp::Eq,
+        "!=" => CmpOp::Ne,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn fields(size: u64, age_days: f64) -> ExprFields<'static> {
+        ExprFields {
+            size,
+            age_days,
+            name: "my-project",
+            path: "/home/user/experiments/my-project",
+        }
+    }
+
+    fn eval(input: &str, fields: &ExprFields) -> bool {
+        Expr::parse(input).unwrap().matches(fields)
+    }
+
+    fn parse_err(input: &str) -> String {
+        Expr::parse(input).unwrap_err()
+    }
+
+    #[test]
+    fn and_binds_stronger_than_or() {
+        // Parsed as `size > 1GiB || (age_days > 60 && name == "other")`
+        let expr = r#"size > 1GiB || age_days > 60 && name == "other""#;
+        assert!(eval(expr, &fields(2 * GIB, 0.0)));
+        assert!(!eval(expr, &fields(0, 90.0)));
+
+        // Parsed as `(name == "other" && age_days > 60) || size > 1GiB`
+        let expr = r#"name == "other" && age_days > 60 || size > 1GiB"#;
+        assert!(eval(expr, &fields(2 * GIB, 0.0)));
+    }
+
+    #[test]
+    fn parentheses_override_precedence() {
+        let expr = r#"(size > 1GiB || age_days > 60) && name == "other""#;
+        assert!(!eval(expr, &fields(2 * GIB, 0.0)));
+        assert!(eval(
+            r#"(size > 1GiB || age_days > 60) && name == "my-project""#,
+            &fields(0, 90.0)
+        ));
+        assert!(eval("!(size > 1GiB)", &fields(0, 0.0)));
+        assert!(!eval("!(size > 1GiB)", &fields(2 * GIB, 0.0)));
+    }
+
+    #[test]
+    fn sizes_with_units() {
+        assert!(eval("size == 2GiB", &fields(2 * GIB, 0.0)));
+        assert!(eval("size >= 2GiB", &fields(2 * GIB, 0.0)));
+        assert!(!eval("size > 2GiB", &fields(2 * GIB, 0.0)));
+        assert!(eval("size < 2GiB", &fields(2 * GIB - 1, 0.0)));
+        assert!(eval("size == 10MB", &fields(10_000_000, 0.0)));
+        assert!(eval("age_days <= 1.5", &fields(0, 1.5)));
+    }
+
+    #[test]
+    fn regex_match() {
+        let f = fields(0, 0.0);
+        assert!(eval(r#"path ~ "experiments""#, &f));
+        assert!(eval(r#"name ~ "^my-""#, &f));
+        assert!(!eval(r#"name ~ "^project""#, &f));
+        assert!(eval(r#"name !~ "^project""#, &f));
+        assert!(!eval(r#"path !~ "experiments""#, &f));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(parse_err("(size > 1GiB"), "Expected ')'");
+        assert_eq!(parse_err("size > 1GiB)"), "Unexpected token ')'");
+        assert_eq!(parse_err("size > 1GiB &"), "Unexpected character '&'");
+        assert_eq!(parse_err("size >"), "Unexpected end of expression");
+        assert_eq!(parse_err("size > )"), "Expected a value, found ')'");
+        assert_eq!(parse_err("> 1GiB"), "Expected a field name, found '>'");
+        assert_eq!(parse_err("size 1GiB"), "Expected an operator, found '1GiB'");
+        assert_eq!(parse_err(r#"name ~ "abc"#), "Unterminated string literal");
+        assert_eq!(
+            parse_err("size ~ 1GiB"),
+            "Operator '~' can't be used with 'size'"
+        );
+        assert_eq!(
+            parse_err(r#"name < "abc""#),
+            "Operator '<' can't be used with strings"
+        );
+        assert_eq!(parse_err("age_days > old"), "Invalid number of days 'old'");
+        assert_eq!(
+            parse_err("owner == me"),
+            "Unknown field 'owner'. Valid fields are size, age_days, name and path"
+        );
+        assert!(Expr::parse(r#"name ~ "(""#).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+//! The git state of the projects for `--keep-dirty`, which keeps the projects with work that is
+//! not committed or not pushed yet, and `--keep-committed-days`, which keeps the recently
+//! committed ones.
+
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, SystemTime},
+};
+
+/// Whether the project has uncommitted changes, including untracked files, or commits that are
+/// not pushed to the upstream branch. Only the changes inside of the project directory count,
+/// since the repository can contain other projects. Returns `None` if the project is not in a git
+/// repository or git is not available
+pub fn has_unsaved_work(project_path: &Path) -> Option<bool> {
+    let output = Command::new("git")
+        .args(["status", "--porcelain=v2", "--branch", "--", "."])
+        .current_dir(project_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let status = String::from_utf8_lossy(&output.stdout);
+
+    let mut has_upstream = false;
+    for line in status.lines() {
+        match line.strip_prefix("# ") {
+            Some(header) if header.starts_with("branch.upstream ") => has_upstream = true,
+            // Like `branch.ab +2 -0` for two unpushed commits
+            Some(header) if header.starts_with("branch.ab ") && !header.contains("+0 ") => {
+                return Some(true);
+            }
+            Some(_) => (),
+            None => return Some(true),
+        }
+    }
+    Some(!has_upstream && has_remotes(project_path))
+}
+
+/// Whether the repository has any remotes. A branch without upstream is only unpushed if there is
+/// a remote to push it to
+fn has_remotes(project_path: &Path) -> bool {
+    Command::new("git")
+        .arg("remote")
+        .current_dir(project_path)
+        .output()
+        .is_ok_and(|output| output.status.success() && !output.stdout.is_empty())
+}
+
+/// The time of the last commit of the repository that contains the project, which is the commit
+/// date of `HEAD`. Returns `None` if the project is not in a git repository or it has no commits
+pub fn last_commit_time(project_path: &Path) -> Option<SystemTime> {
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%ct"])
+        .current_dir(project_path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let secs: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
@@ -0,0 +1,586 @@
+//! Translations of the user facing output of the cleaning workflow, together with locale specific
+//! formatting of sizes and dates.
+//!
+//! The English messages are used as the keys of the translations, so messages without a
+//! translation fall back to English. Placeholders are written as `{}` and are filled in order, or
+//! as `{0}`, `{1}`, ... when a translation needs a different order.
+
+use std::{fmt::Display, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+impl Locale {
+    /// Parse a language tag like `de`, `de-AT` or a locale like `de_DE.UTF-8`. Returns `None` for
+    /// unsupported languages
+    pub fn parse(s: &str) -> Option<Self> {
+        let lang = s
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match lang.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            "es" => Some(Locale::Es),
+            _ => None,
+        }
+    }
+
+    /// Detect the locale from the `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables
+    fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::parse(&value))
+            .unwrap_or(Locale::En)
+    }
+
+    fn translations(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Locale::En => &[],
+            Locale::De => DE,
+            Locale::Fr => FR,
+            Locale::Es => ES,
+        }
+    }
+}
+
+/// Parse the value of `--lang`
+pub fn parse_locale(s: &str) -> Result<Locale, String> {
+    Locale::parse(s).ok_or_else(|| {
+        format!(
+            "Unsupported language '{}', supported are: en, de, fr, es",
+            s
+        )
+    })
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Use the given locale instead of the one from the environment. This only has an effect before
+/// anything was translated
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+fn locale() -> Locale {
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+/// Get the translation of the message for the current locale
+pub fn translate(message: &'static str) -> &'static str {
+    locale()
+        .translations()
+        .iter()
+        .find(|(en, _)| *en == message)
+        .map_or(message, |(_, translated)| translated)
+}
+
+/// Fill the `{}` and `{N}` placeholders of the template with the arguments
+pub fn format(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next_arg = 0;
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+
+        let idx = match rest[1..end].parse::<usize>() {
+            Ok(idx) => idx,
+            Err(_) if end == 1 => {
+                next_arg += 1;
+                next_arg - 1
+            }
+            // Not a placeholder
+            Err(_) => {
+                out.push('{');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        if let Some(arg) = args.get(idx) {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Translate the message and fill in the arguments, like `format!`
+macro_rules! tr {
+    ($message:literal) => {
+        $crate::i18n::translate($message).to_string()
+    };
+    ($message:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format(
+            $crate::i18n::translate($message),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+
+pub(crate) use tr;
+
+/// Format the size like `bytefmt::format`, with the decimal separator of the current locale
+pub fn format_size(size: u64) -> String {
+    let formatted = bytefmt::format(size);
+    match locale() {
+        Locale::En => formatted,
+        Locale::De | Locale::Fr | Locale::Es => formatted.replace('.', ","),
+    }
+}
+
+/// Format the date and time in the usual short format of the current locale
+pub fn format_datetime(datetime: &chrono::DateTime<chrono::Local>) -> String {
+    let format = match locale() {
+        Locale::En => "%Y-%m-%d %H:%M",
+        Locale::De => "%d.%m.%Y %H:%M",
+        Locale::Fr | Locale::Es => "%d/%m/%Y %H:%M",
+    };
+    datetime.format(format).to_string()
+}
+
+const DE: &[(&str, &str)] = &[
+    ("Scanning for projects in {}", "Suche nach Projekten in {}"),
+    (
+        "Scanning for projects in {} ({}), press Ctrl+C to stop early",
+        "Suche nach Projekten in {} ({}), Strg+C beendet die Suche vorzeitig",
+    ),
+    (
+        "{} directories, {} projects, {} in target directories",
+        "{} Verzeichnisse, {} Projekte, {} in target-Verzeichnissen",
+    ),
+    (
+        "Scan stopped early after {}, the results are partial",
+        "Suche vorzeitig beendet nach {}, die Ergebnisse sind unvollständig",
+    ),
+    ("Analyzing targets", "Analysiere target-Verzeichnisse"),
+    (
+        "Select projects to clean",
+        "Zu bereinigende Projekte auswählen",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}",
+        "{}/{} Projekte ausgewählt, die Bereinigung gibt {} frei",
+    ),
+    (
+        "Select projects to clean (partial scan)",
+        "Zu bereinigende Projekte auswählen (unvollständige Suche)",
+    ),
+    ("Nothing selected", "Nichts ausgewählt"),
+    (
+        "[↑↓] move, [space] toggle, [a] toggle all, [p] ignore permanently, [d] details, \
+         [enter] confirm, [esc] cancel",
+        "[↑↓] bewegen, [Leertaste] umschalten, [a] alle umschalten, [p] dauerhaft ignorieren, \
+         [d] Details, [Enter] bestätigen, [Esc] abbrechen",
+    ),
+    ("(ignore permanently)", "(dauerhaft ignorieren)"),
+    (
+        "Ignoring the following project directories:",
+        "Die folgenden Projektverzeichnisse werden ignoriert:",
+    ),
+    (
+        "Selected the following project directories for cleaning:",
+        "Die folgenden Projektverzeichnisse werden bereinigt:",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}. Keeping: {}",
+        "{}/{} Projekte ausgewählt, die Bereinigung gibt {} frei. Behalten: {}",
+    ),
+    (
+        "Selected {}/{} projects of a partial scan, cleaning will free: {}. Keeping: {}",
+        "{}/{} Projekte einer unvollständigen Suche ausgewählt, die Bereinigung gibt {} frei. \
+         Behalten: {}",
+    ),
+    (
+        "Dry run. Not doing any cleanup",
+        "Probelauf. Es wird nichts bereinigt",
+    ),
+    (
+        "Clean the project directories shown above?",
+        "Die oben angezeigten Projektverzeichnisse bereinigen?",
+    ),
+    ("Cleanup cancelled", "Bereinigung abgebrochen"),
+    ("Starting cleanup...", "Starte Bereinigung..."),
+    ("Failed to clean {}", "Bereinigung von {} fehlgeschlagen"),
+    ("Error: {}", "Fehler: {}"),
+    (
+        "Skipped {} of the selected projects",
+        "{} der ausgewählten Projekte übersprungen",
+    ),
+    (
+        "The time limit was reached, {} of the selected projects were not cleaned",
+        "Die Zeitbegrenzung wurde erreicht, {} der ausgewählten Projekte wurden nicht bereinigt",
+    ),
+    (
+        "Projects cleaned. Reclaimed {} of disk space",
+        "Projekte bereinigt. {} Speicherplatz freigegeben",
+    ),
+    (
+        "The targets were moved to the trash, the space is freed when the trash is emptied",
+        "Die Target-Verzeichnisse wurden in den Papierkorb verschoben, der Speicherplatz wird beim Leeren des Papierkorbs freigegeben",
+    ),
+    (
+        "Scanning took {}, analysis took {}",
+        "Suche dauerte {}, Analyse dauerte {}",
+    ),
+    (
+        "Scanning took {}, analysis took {}, deletion took {} ({} files/s, {}/s)",
+        "Suche dauerte {}, Analyse dauerte {}, Löschen dauerte {} ({} Dateien/s, {}/s)",
+    ),
+    ("Cargo's garbage collection would remove {} files ({}) from the global caches", "Die Garbage Collection von Cargo würde {} Dateien ({}) aus den globalen Caches entfernen"),
+    ("Cargo's garbage collection removed {} files ({}) from the global caches", "Die Garbage Collection von Cargo hat {} Dateien ({}) aus den globalen Caches entfernt"),
+    ("Failed to run the garbage collection of cargo: {}", "Die Garbage Collection von Cargo konnte nicht ausgeführt werden: {}"),
+    ("The installed cargo doesn't support `cargo clean gc` yet, so the global caches are not cleaned", "Das installierte Cargo unterstützt `cargo clean gc` noch nicht, daher werden die globalen Caches nicht bereinigt"),
+    ("{} projects were not processed, the cleanup can be continued using `cargo clean-all resume`", "{} Projekte wurden nicht verarbeitet, die Bereinigung kann mit `cargo clean-all resume` fortgesetzt werden"),
+    ("There is no interrupted cleanup to resume", "Es gibt keine unterbrochene Bereinigung zum Fortsetzen"),
+    ("Resuming the cleanup started at {}", "Setze die um {} gestartete Bereinigung fort"),
+    ("the cleanup was started", "die Bereinigung gestartet wurde"),
+    ("None of the remaining projects can be cleaned", "Keines der verbleibenden Projekte kann bereinigt werden"),
+    ("Finishing the deletion of the renamed targets:", "Schließe das Löschen der umbenannten target-Verzeichnisse ab:"),
+    ("Cleaning the remaining project directories:", "Die verbleibenden Projektverzeichnisse werden bereinigt:"),
+    ("Cleaning {} projects will free: {}", "Die Bereinigung von {} Projekten gibt {} frei"),
+    ("The targets take up {}, which is within the budget of {}", "Die target-Verzeichnisse belegen {} und liegen damit im Budget von {}"),
+    ("The targets take up {}, cleaning the {} least recently used projects to get to {} within the budget of {}", "Die target-Verzeichnisse belegen {}, die {} am längsten unbenutzten Projekte werden bereinigt, um mit {} im Budget von {} zu liegen"),
+    ("The budget can't be met, since the remaining projects are protected by the selection criteria", "Das Budget kann nicht eingehalten werden, da die übrigen Projekte durch die Auswahlkriterien geschützt sind"),
+    ("The cleanup stops once {} are freed (--max-free), so not all selected projects may be cleaned", "Die Bereinigung endet, sobald {} freigegeben sind (--max-free), daher werden eventuell nicht alle ausgewählten Projekte bereinigt"),
+    ("Not cleaning {} more projects, since the cleaned ones already freed up the --max-free limit", "{} weitere Projekte werden nicht bereinigt, da die bereinigten bereits das Limit von --max-free freigegeben haben"),
+    ("Running in a container, target directories per mount:", "Ausführung in einem Container, target-Verzeichnisse pro Einhängepunkt:"),
+    ("Target directories per disk:", "target-Verzeichnisse pro Datenträger:"),
+    ("unknown mount", "unbekannter Einhängepunkt"),
+    (", free: {}", ", frei: {}"),
+    (" (container filesystem, kept unless --clean-overlay is used)", " (Dateisystem des Containers, wird ohne --clean-overlay behalten)"),
+    ("{}: {} in {} projects, selected: {}{}{}", "{}: {} in {} Projekten, ausgewählt: {}{}{}"),
+    ("Reclaimed per disk:", "Freigegeben pro Datenträger:"),
+    (", free now: {}", ", jetzt frei: {}"),
+    ("Reclaimable space per user:", "Freigebbarer Speicherplatz pro Benutzer:"),
+    ("{}: {} in {} projects, selected: {}", "{}: {} in {} Projekten, ausgewählt: {}"),
+    ("Reclaimable space per scanned directory:", "Freigebbarer Speicherplatz pro durchsuchtem Verzeichnis:"),
+    (", scanned in {}", ", durchsucht in {}"),
+    ("{}: {} in {} projects, selected: {}{}", "{}: {} in {} Projekten, ausgewählt: {}{}"),
+    ("Skipping {}, since its target directory doesn't exist", "{} wird übersprungen, da sein target-Verzeichnis nicht existiert"),
+    ("Skipping {}, since it was built after {}", "{} wird übersprungen, da es gebaut wurde, nachdem {}"),
+    ("Applying the plan created at {}", "Wende den um {} erstellten Plan an"),
+    ("the plan was created", "der Plan erstellt wurde"),
+    ("None of the planned projects can be cleaned", "Keines der geplanten Projekte kann bereinigt werden"),
+    ("Cleaning the following project directories:", "Die folgenden Projektverzeichnisse werden bereinigt:"),
+    ("Target statistics of {} projects:", "Statistik der target-Verzeichnisse von {} Projekten:"),
+    ("Size: p50 {}, p90 {}, max {}", "Größe: p50 {}, p90 {}, max. {}"),
+    ("Age:  p50 {} days, p90 {} days, max {} days", "Alter: p50 {} Tage, p90 {} Tage, max. {} Tage"),
+    ("Untouched for 90+ days: {} projects, {}", "Seit 90+ Tagen unverändert: {} Projekte, {}"),
+    ("Age distribution:", "Verteilung nach Alter:"),
+    ("Size distribution:", "Verteilung nach Größe:"),
+    ("< 1 day", "< 1 Tag"),
+    ("1-7 days", "1-7 Tage"),
+    ("7-30 days", "7-30 Tage"),
+    ("30-90 days", "30-90 Tage"),
+    ("90-365 days", "90-365 Tage"),
+    ("> 1 year", "> 1 Jahr"),
+    ("{} projects, {}", "{} Projekte, {}"),
+    ("Projects grouped by {}:", "Projekte gruppiert nach {}:"),
+    ("Size per profile:", "Größe pro Profil:"),
+    ("{}: {} in {} projects", "{}: {} in {} Projekten"),
+    ("Largest files in the targets:", "Größte Dateien in den target-Verzeichnissen:"),
+];
+
+const FR: &[(&str, &str)] = &[
+    (
+        "Scanning for projects in {}",
+        "Recherche de projets dans {}",
+    ),
+    (
+        "Scanning for projects in {} ({}), press Ctrl+C to stop early",
+        "Recherche de projets dans {} ({}), Ctrl+C pour arrêter la recherche",
+    ),
+    (
+        "{} directories, {} projects, {} in target directories",
+        "{} répertoires, {} projets, {} dans les répertoires target",
+    ),
+    (
+        "Scan stopped early after {}, the results are partial",
+        "Recherche arrêtée après {}, les résultats sont partiels",
+    ),
+    ("Analyzing targets", "Analyse des répertoires target"),
+    (
+        "Select projects to clean",
+        "Sélectionner les projets à nettoyer",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}",
+        "{}/{} projets sélectionnés, le nettoyage libérera : {}",
+    ),
+    (
+        "Select projects to clean (partial scan)",
+        "Sélectionner les projets à nettoyer (recherche partielle)",
+    ),
+    ("Nothing selected", "Rien n'est sélectionné"),
+    (
+        "[↑↓] move, [space] toggle, [a] toggle all, [p] ignore permanently, [d] details, \
+         [enter] confirm, [esc] cancel",
+        "[↑↓] déplacer, [espace] basculer, [a] tout basculer, [p] ignorer définitivement, \
+         [d] détails, [entrée] confirmer, [échap] annuler",
+    ),
+    ("(ignore permanently)", "(ignorer définitivement)"),
+    (
+        "Ignoring the following project directories:",
+        "Les répertoires de projet suivants sont ignorés :",
+    ),
+    (
+        "Selected the following project directories for cleaning:",
+        "Les répertoires de projet suivants seront nettoyés :",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}. Keeping: {}",
+        "{}/{} projets sélectionnés, le nettoyage libérera : {}. Conservé : {}",
+    ),
+    (
+        "Selected {}/{} projects of a partial scan, cleaning will free: {}. Keeping: {}",
+        "{}/{} projets d'une recherche partielle sélectionnés, le nettoyage libérera : {}. \
+         Conservé : {}",
+    ),
+    (
+        "Dry run. Not doing any cleanup",
+        "Simulation. Aucun nettoyage n'est effectué",
+    ),
+    (
+        "Clean the project directories shown above?",
+        "Nettoyer les répertoires de projet affichés ci-dessus ?",
+    ),
+    ("Cleanup cancelled", "Nettoyage annulé"),
+    ("Starting cleanup...", "Début du nettoyage..."),
+    ("Failed to clean {}", "Échec du nettoyage de {}"),
+    ("Error: {}", "Erreur : {}"),
+    (
+        "Skipped {} of the selected projects",
+        "{} des projets sélectionnés ont été ignorés",
+    ),
+    (
+        "The time limit was reached, {} of the selected projects were not cleaned",
+        "La limite de temps a été atteinte, {} des projets sélectionnés n'ont pas été nettoyés",
+    ),
+    (
+        "Projects cleaned. Reclaimed {} of disk space",
+        "Projets nettoyés. {} d'espace disque libéré",
+    ),
+    (
+        "The targets were moved to the trash, the space is freed when the trash is emptied",
+        "Les répertoires target ont été déplacés dans la corbeille, l'espace sera libéré en vidant la corbeille",
+    ),
+    (
+        "Scanning took {}, analysis took {}",
+        "Recherche : {}, analyse : {}",
+    ),
+    (
+        "Scanning took {}, analysis took {}, deletion took {} ({} files/s, {}/s)",
+        "Recherche : {}, analyse : {}, suppression : {} ({} fichiers/s, {}/s)",
+    ),
+    ("Cargo's garbage collection would remove {} files ({}) from the global caches", "Le ramasse-miettes de cargo supprimerait {} fichiers ({}) des caches globaux"),
+    ("Cargo's garbage collection removed {} files ({}) from the global caches", "Le ramasse-miettes de cargo a supprimé {} fichiers ({}) des caches globaux"),
+    ("Failed to run the garbage collection of cargo: {}", "Impossible d'exécuter le ramasse-miettes de cargo : {}"),
+    ("The installed cargo doesn't support `cargo clean gc` yet, so the global caches are not cleaned", "Le cargo installé ne prend pas encore en charge `cargo clean gc`, les caches globaux ne sont donc pas nettoyés"),
+    ("{} projects were not processed, the cleanup can be continued using `cargo clean-all resume`", "{} projets n'ont pas été traités, le nettoyage peut être repris avec `cargo clean-all resume`"),
+    ("There is no interrupted cleanup to resume", "Il n'y a aucun nettoyage interrompu à reprendre"),
+    ("Resuming the cleanup started at {}", "Reprise du nettoyage commencé le {}"),
+    ("the cleanup was started", "le début du nettoyage"),
+    ("None of the remaining projects can be cleaned", "Aucun des projets restants ne peut être nettoyé"),
+    ("Finishing the deletion of the renamed targets:", "Fin de la suppression des répertoires target renommés :"),
+    ("Cleaning the remaining project directories:", "Les répertoires de projet restants seront nettoyés :"),
+    ("Cleaning {} projects will free: {}", "Le nettoyage de {} projets libérera : {}"),
+    ("The targets take up {}, which is within the budget of {}", "Les répertoires target occupent {}, ce qui respecte le budget de {}"),
+    ("The targets take up {}, cleaning the {} least recently used projects to get to {} within the budget of {}", "Les répertoires target occupent {}, nettoyage des {} projets les moins récemment utilisés pour atteindre {} dans le budget de {}"),
+    ("The budget can't be met, since the remaining projects are protected by the selection criteria", "Le budget ne peut pas être respecté, car les projets restants sont protégés par les critères de sélection"),
+    ("The cleanup stops once {} are freed (--max-free), so not all selected projects may be cleaned", "Le nettoyage s'arrête dès que {} sont libérés (--max-free), tous les projets sélectionnés ne seront donc peut-être pas nettoyés"),
+    ("Not cleaning {} more projects, since the cleaned ones already freed up the --max-free limit", "{} autres projets ne sont pas nettoyés, car les projets nettoyés ont déjà libéré la limite de --max-free"),
+    ("Running in a container, target directories per mount:", "Exécution dans un conteneur, répertoires target par point de montage :"),
+    ("Target directories per disk:", "Répertoires target par disque :"),
+    ("unknown mount", "point de montage inconnu"),
+    (", free: {}", ", libre : {}"),
+    (" (container filesystem, kept unless --clean-overlay is used)", " (système de fichiers du conteneur, conservé sauf avec --clean-overlay)"),
+    ("{}: {} in {} projects, selected: {}{}{}", "{} : {} dans {} projets, sélectionné : {}{}{}"),
+    ("Reclaimed per disk:", "Libéré par disque :"),
+    (", free now: {}", ", libre maintenant : {}"),
+    ("Reclaimable space per user:", "Espace récupérable par utilisateur :"),
+    ("{}: {} in {} projects, selected: {}", "{} : {} dans {} projets, sélectionné : {}"),
+    ("Reclaimable space per scanned directory:", "Espace récupérable par répertoire analysé :"),
+    (", scanned in {}", ", analysé en {}"),
+    ("{}: {} in {} projects, selected: {}{}", "{} : {} dans {} projets, sélectionné : {}{}"),
+    ("Skipping {}, since its target directory doesn't exist", "{} est ignoré, car son répertoire target n'existe pas"),
+    ("Skipping {}, since it was built after {}", "{} est ignoré, car il a été construit après {}"),
+    ("Applying the plan created at {}", "Application du plan créé le {}"),
+    ("the plan was created", "la création du plan"),
+    ("None of the planned projects can be cleaned", "Aucun des projets prévus ne peut être nettoyé"),
+    ("Cleaning the following project directories:", "Les répertoires de projet suivants seront nettoyés :"),
+    ("Target statistics of {} projects:", "Statistiques des répertoires target de {} projets :"),
+    ("Size: p50 {}, p90 {}, max {}", "Taille : p50 {}, p90 {}, max {}"),
+    ("Age:  p50 {} days, p90 {} days, max {} days", "Âge :  p50 {} jours, p90 {} jours, max {} jours"),
+    ("Untouched for 90+ days: {} projects, {}", "Inchangés depuis 90+ jours : {} projets, {}"),
+    ("Age distribution:", "Répartition par âge :"),
+    ("Size distribution:", "Répartition par taille :"),
+    ("< 1 day", "< 1 jour"),
+    ("1-7 days", "1-7 jours"),
+    ("7-30 days", "7-30 jours"),
+    ("30-90 days", "30-90 jours"),
+    ("90-365 days", "90-365 jours"),
+    ("> 1 year", "> 1 an"),
+    ("{} projects, {}", "{} projets, {}"),
+    ("Projects grouped by {}:", "Projets regroupés par {} :"),
+    ("Size per profile:", "Taille par profil :"),
+    ("{}: {} in {} projects", "{} : {} dans {} projets"),
+    ("Largest files in the targets:", "Plus gros fichiers dans les répertoires target :"),
+];
+
+const ES: &[(&str, &str)] = &[
+    ("Scanning for projects in {}", "Buscando proyectos en {}"),
+    (
+        "Scanning for projects in {} ({}), press Ctrl+C to stop early",
+        "Buscando proyectos en {} ({}), pulse Ctrl+C para detener la búsqueda",
+    ),
+    (
+        "{} directories, {} projects, {} in target directories",
+        "{} directorios, {} proyectos, {} en directorios target",
+    ),
+    (
+        "Scan stopped early after {}, the results are partial",
+        "Búsqueda detenida tras {}, los resultados son parciales",
+    ),
+    ("Analyzing targets", "Analizando directorios target"),
+    (
+        "Select projects to clean",
+        "Seleccione los proyectos a limpiar",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}",
+        "{}/{} proyectos seleccionados, la limpieza liberará: {}",
+    ),
+    (
+        "Select projects to clean (partial scan)",
+        "Seleccione los proyectos a limpiar (búsqueda parcial)",
+    ),
+    ("Nothing selected", "No se ha seleccionado nada"),
+    (
+        "[↑↓] move, [space] toggle, [a] toggle all, [p] ignore permanently, [d] details, \
+         [enter] confirm, [esc] cancel",
+        "[↑↓] mover, [espacio] alternar, [a] alternar todos, [p] ignorar permanentemente, \
+         [d] detalles, [intro] confirmar, [esc] cancelar",
+    ),
+    ("(ignore permanently)", "(ignorar permanentemente)"),
+    (
+        "Ignoring the following project directories:",
+        "Se ignoran los siguientes directorios de proyecto:",
+    ),
+    (
+        "Selected the following project directories for cleaning:",
+        "Se limpiarán los siguientes directorios de proyecto:",
+    ),
+    (
+        "Selected {}/{} projects, cleaning will free: {}. Keeping: {}",
+        "{}/{} proyectos seleccionados, la limpieza liberará: {}. Se conservan: {}",
+    ),
+    (
+        "Selected {}/{} projects of a partial scan, cleaning will free: {}. Keeping: {}",
+        "{}/{} proyectos de una búsqueda parcial seleccionados, la limpieza liberará: {}. \
+         Se conservan: {}",
+    ),
+    (
+        "Dry run. Not doing any cleanup",
+        "Simulación. No se limpia nada",
+    ),
+    (
+        "Clean the project directories shown above?",
+        "¿Limpiar los directorios de proyecto mostrados arriba?",
+    ),
+    ("Cleanup cancelled", "Limpieza cancelada"),
+    ("Starting cleanup...", "Iniciando la limpieza..."),
+    ("Failed to clean {}", "No se pudo limpiar {}"),
+    ("Error: {}", "Error: {}"),
+    (
+        "Skipped {} of the selected projects",
+        "Se omitieron {} de los proyectos seleccionados",
+    ),
+    (
+        "The time limit was reached, {} of the selected projects were not cleaned",
+        "Se alcanzó el límite de tiempo, {} de los proyectos seleccionados no se limpiaron",
+    ),
+    (
+        "Projects cleaned. Reclaimed {} of disk space",
+        "Proyectos limpiados. Se liberaron {} de espacio en disco",
+    ),
+    (
+        "The targets were moved to the trash, the space is freed when the trash is emptied",
+        "Los directorios target se movieron a la papelera, el espacio se libera al vaciar la papelera",
+    ),
+    (
+        "Scanning took {}, analysis took {}",
+        "Búsqueda: {}, análisis: {}",
+    ),
+    (
+        "Scanning took {}, analysis took {}, deletion took {} ({} files/s, {}/s)",
+        "Búsqueda: {}, análisis: {}, eliminación: {} ({} archivos/s, {}/s)",
+    ),
+    ("Cargo's garbage collection would remove {} files ({}) from the global caches", "La recolección de basura de cargo eliminaría {} archivos ({}) de las cachés globales"),
+    ("Cargo's garbage collection removed {} files ({}) from the global caches", "La recolección de basura de cargo eliminó {} archivos ({}) de las cachés globales"),
+    ("Failed to run the garbage collection of cargo: {}", "No se pudo ejecutar la recolección de basura de cargo: {}"),
+    ("The installed cargo doesn't support `cargo clean gc` yet, so the global caches are not cleaned", "El cargo instalado aún no admite `cargo clean gc`, por lo que las cachés globales no se limpian"),
+    ("{} projects were not processed, the cleanup can be continued using `cargo clean-all resume`", "{} proyectos no se procesaron, la limpieza puede continuarse con `cargo clean-all resume`"),
+    ("There is no interrupted cleanup to resume", "No hay ninguna limpieza interrumpida que continuar"),
+    ("Resuming the cleanup started at {}", "Continuando la limpieza iniciada el {}"),
+    ("the cleanup was started", "el inicio de la limpieza"),
+    ("None of the remaining projects can be cleaned", "Ninguno de los proyectos restantes se puede limpiar"),
+    ("Finishing the deletion of the renamed targets:", "Terminando la eliminación de los directorios target renombrados:"),
+    ("Cleaning the remaining project directories:", "Se limpiarán los directorios de proyecto restantes:"),
+    ("Cleaning {} projects will free: {}", "La limpieza de {} proyectos liberará: {}"),
+    ("The targets take up {}, which is within the budget of {}", "Los directorios target ocupan {}, dentro del presupuesto de {}"),
+    ("The targets take up {}, cleaning the {} least recently used projects to get to {} within the budget of {}", "Los directorios target ocupan {}, se limpian los {} proyectos usados hace más tiempo para llegar a {} dentro del presupuesto de {}"),
+    ("The budget can't be met, since the remaining projects are protected by the selection criteria", "No se puede cumplir el presupuesto, ya que los proyectos restantes están protegidos por los criterios de selección"),
+    ("The cleanup stops once {} are freed (--max-free), so not all selected projects may be cleaned", "La limpieza se detiene en cuanto se liberen {} (--max-free), por lo que puede que no se limpien todos los proyectos seleccionados"),
+    ("Not cleaning {} more projects, since the cleaned ones already freed up the --max-free limit", "No se limpian {} proyectos más, ya que los limpiados ya liberaron el límite de --max-free"),
+    ("Running in a container, target directories per mount:", "Ejecutando en un contenedor, directorios target por punto de montaje:"),
+    ("Target directories per disk:", "Directorios target por disco:"),
+    ("unknown mount", "punto de montaje desconocido"),
+    (", free: {}", ", libre: {}"),
+    (" (container filesystem, kept unless --clean-overlay is used)", " (sistema de archivos del contenedor, se conserva salvo con --clean-overlay)"),
+    ("{}: {} in {} projects, selected: {}{}{}", "{}: {} en {} proyectos, seleccionado: {}{}{}"),
+    ("Reclaimed per disk:", "Liberado por disco:"),
+    (", free now: {}", ", libre ahora: {}"),
+    ("Reclaimable space per user:", "Espacio recuperable por usuario:"),
+    ("{}: {} in {} projects, selected: {}", "{}: {} en {} proyectos, seleccionado: {}"),
+    ("Reclaimable space per scanned directory:", "Espacio recuperable por directorio analizado:"),
+    (", scanned in {}", ", analizado en {}"),
+    ("{}: {} in {} projects, selected: {}{}", "{}: {} en {} proyectos, seleccionado: {}{}"),
+    ("Skipping {}, since its target directory doesn't exist", "Se omite {}, ya que su directorio target no existe"),
+    ("Skipping {}, since it was built after {}", "Se omite {}, ya que se compiló después de {}"),
+    ("Applying the plan created at {}", "Aplicando el plan creado el {}"),
+    ("the plan was created", "la creación del plan"),
+    ("None of the planned projects can be cleaned", "Ninguno de los proyectos planificados se puede limpiar"),
+    ("Cleaning the following project directories:", "Se limpiarán los siguientes directorios de proyecto:"),
+    ("Target statistics of {} projects:", "Estadísticas de los directorios target de {} proyectos:"),
+    ("Size: p50 {}, p90 {}, max {}", "Tamaño: p50 {}, p90 {}, máx. {}"),
+    ("Age:  p50 {} days, p90 {} days, max {} days", "Edad:  p50 {} días, p90 {} días, máx. {} días"),
+    ("Untouched for 90+ days: {} projects, {}", "Sin cambios desde hace 90+ días: {} proyectos, {}"),
+    ("Age distribution:", "Distribución por edad:"),
+    ("Size distribution:", "Distribución por tamaño:"),
+    ("< 1 day", "< 1 día"),
+    ("1-7 days", "1-7 días"),
+    ("7-30 days", "7-30 días"),
+    ("30-90 days", "30-90 días"),
+    ("90-365 days", "90-365 días"),
+    ("> 1 year", "> 1 año"),
+    ("{} projects, {}", "{} proyectos, {}"),
+    ("Projects grouped by {}:", "Proyectos agrupados por {}:"),
+    ("Size per profile:", "Tamaño por perfil:"),
+    ("{}: {} in {} projects", "{}: {} en {} proyectos"),
+    ("Largest files in the targets:", "Archivos más grandes en los directorios target:"),
+];
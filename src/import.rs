@@ -0,0 +1,136 @@
+//! Import of the ignore lists of other cleaning tools into the persistent ignore list.
+//!
+//! kondo doesn't have a config file, its ignored directories are passed using `-I` or
+//! `--ignored-dirs`. Those are usually kept in a shell alias, so the kondo import searches the
+//! shell config files for kondo invocations.
+
+use crate::{config, output::outln, pretty_format_path};
+use clap::ValueEnum;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The tools that ignore lists can be imported from
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum ImportSource {
+    /// The `-I` and `--ignored-dirs` arguments of kondo invocations and aliases in shell config
+    /// files
+    Kondo,
+    /// A plain list with one directory per line, as used by many other tools
+    List,
+}
+
+/// The shell config files that are searched for kondo aliases when no file is given
+const SHELL_CONFIG_FILES: &[&str] = &[
+    ".bashrc",
+    ".bash_aliases",
+    ".zshrc",
+    ".profile",
+    ".config/fish/config.fish",
+];
+
+/// Import the ignored directories from the given source into the persistent ignore list.
+/// Directories that are already ignored are skipped.
+pub fn import_config(source: ImportSource, file: Option<&Path>) -> io::Result<()> {
+    let files: Vec<PathBuf> = match (source, file) {
+        (_, Some(file)) => vec![file.to_path_buf()],
+        (ImportSource::Kondo, None) => {
+            let home = std::env::home_dir().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "The home directory could not be determined",
+                )
+            })?;
+            SHELL_CONFIG_FILES
+                .iter()
+                .map(|f| home.join(f))
+                .filter(|f| f.is_file())
+                .collect()
+        }
+        (ImportSource::List, None) => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Importing a list requires the list file",
+            ))
+        }
+    };
+
+    let mut dirs = Vec::new();
+    for file in files {
+        let content = std::fs::read_to_string(&file)?;
+        let found = match source {
+            ImportSource::Kondo => parse_kondo_ignored_dirs(&content),
+            ImportSource::List => parse_list(&content),
+        };
+        outln!(
+            "Found {} ignored directories in {}",
+            found.len(),
+            pretty_format_path(&file)
+        );
+        dirs.extend(found);
+    }
+
+    let existing: Vec<_> = config::read_ignore_list()?
+        .iter()
+        .map(crate::canonicalize_or_not)
+        .collect();
+    let mut new_dirs: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        let canonical = crate::canonicalize_or_not(&dir);
+        if !existing.contains(&canonical) && !new_dirs.contains(&canonical) {
+            new_dirs.push(canonical);
+        }
+    }
+
+    config::append_ignore_list(&new_dirs)?;
+    for dir in new_dirs.iter() {
+        outln!("Permanently ignoring {}", pretty_format_path(dir));
+    }
+    outln!("Imported {} new ignored directories", new_dirs.len());
+    Ok(())
+}
+
+/// Parse a list with one directory per line. Empty lines and `#` comments are skipped
+fn parse_list(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(expand_home)
+        .collect()
+}
+
+/// Find the `-I <dir>` and `--ignored-dirs <dir>` arguments on the lines that invoke kondo
+fn parse_kondo_ignored_dirs(content: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for line in content.lines().filter(|l| l.contains("kondo")) {
+        let unquote = |w: &str| w.trim_matches(|c| c == '\'' || c == '"').to_string();
+        let mut words = line.split_whitespace().map(unquote);
+
+        while let Some(word) = words.next() {
+            let dir = match word.as_str() {
+                "-I" | "--ignored-dirs" => words.next(),
+                _ => word
+                    .strip_prefix("--ignored-dirs=")
+                    .or_else(|| word.strip_prefix("-I"))
+                    .map(unquote),
+            };
+            if let Some(dir) = dir.filter(|d| !d.is_empty()) {
+                dirs.push(expand_home(&dir));
+            }
+        }
+    }
+    dirs
+}
+
+/// Expand a leading `~` or `$HOME` to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    let rest = path
+        .strip_prefix("~")
+        .or_else(|| path.strip_prefix("$HOME"));
+    match (rest, std::env::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+        _ => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,105 @@
+//! The `init` subcommand, which creates the config file by asking for the scan roots, the keep
+//! thresholds and the projects to ignore permanently, and `--init-config`, which writes it from
+//! the given options without asking.
+
+use crate::{canonicalize_or_not, config, output::outln, pretty_format_path, AppArgs};
+use dialoguer::Input;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Fail if there already is a config file, before asking any questions
+fn ensure_no_config() -> io::Result<()> {
+    match config::config_file().filter(|path| path.exists()) {
+        Some(path) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, edit it or remove it to start over",
+                pretty_format_path(&path)
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Ask for directories until an empty answer is given
+fn ask_dirs(prompt: &str) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    loop {
+        let answer: String = Input::new()
+            .with_prompt(prompt)
+            .allow_empty(true)
+            .validate_with(|answer: &String| {
+                if answer.is_empty() || config::expand_home(answer).is_dir() {
+                    Ok(())
+                } else {
+                    Err("Not a directory")
+                }
+            })
+            .interact_text()
+            .map_err(io::Error::other)?;
+        if answer.is_empty() {
+            return Ok(dirs);
+        }
+        dirs.push(canonicalize_or_not(config::expand_home(&answer)));
+    }
+}
+
+/// Write the config file and add the ignored directories to the persistent ignore list
+fn finish(
+    roots: &[PathBuf],
+    keep_days: u32,
+    keep_size: u64,
+    ignored: &[PathBuf],
+) -> io::Result<()> {
+    let path = config::write_config(roots, keep_days, keep_size)?;
+    outln!("Wrote the config file {}", pretty_format_path(&path));
+    if !ignored.is_empty() {
+        config::append_ignore_list(ignored)?;
+        outln!(
+            "Added {} directories to the persistent ignore list",
+            ignored.len()
+        );
+    }
+    Ok(())
+}
+
+/// Create the config file interactively
+pub fn init() -> io::Result<()> {
+    ensure_no_config()?;
+    outln!("Creating the config file. Options given on the command line still take precedence");
+
+    let roots = ask_dirs("Directory to scan when no directory is given (empty to finish)")?;
+    let keep_days: u32 = Input::new()
+        .with_prompt("Keep the projects compiled in the last number of days")
+        .default(0)
+        .interact_text()
+        .map_err(io::Error::other)?;
+    let keep_size: String = Input::new()
+        .with_prompt("Keep the targets smaller than (like 100MB)")
+        .default("0".to_string())
+        .validate_with(|size: &String| bytefmt::parse(size.as_str()).map(|_| ()))
+        .interact_text()
+        .map_err(io::Error::other)?;
+    let keep_size = bytefmt::parse(keep_size).map_err(io::Error::other)?;
+    let ignored = ask_dirs("Project or directory to ignore permanently (empty to finish)")?;
+
+    finish(&roots, keep_days, keep_size, &ignored)
+}
+
+/// Create the config file from the options of a run without asking. The roots are only written
+/// if directories were given, and the `--ignore` directories are ignored permanently
+pub fn init_from_args(args: &AppArgs, roots_given: bool) -> io::Result<()> {
+    ensure_no_config()?;
+    let roots: Vec<PathBuf> = if roots_given {
+        args.root_dirs
+            .iter()
+            .map(|dir| canonicalize_or_not(Path::new(dir)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let ignored: Vec<PathBuf> = args.ignore.iter().map(PathBuf::from).collect();
+    finish(&roots, args.keep_last_modified, args.keep_size, &ignored)
+}
@@ -0,0 +1,91 @@
+//! The local projects that binaries were installed from using `cargo install --path`. Those are
+//! read from the files that cargo uses to track the installed crates, `.crates2.json` and
+//! `.crates.toml` in the cargo home directory.
+
+use crate::cargo_cache::cargo_home;
+use serde::Deserialize;
+use std::{collections::HashMap, path::PathBuf};
+
+#[derive(Deserialize)]
+struct Crates2 {
+    /// The installed packages keyed by their package id
+    installs: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct Crates1 {
+    /// The installed binaries keyed by the package id
+    v1: HashMap<String, toml::Value>,
+}
+
+/// The canonicalized paths of the projects that installed packages were built from
+pub fn installed_project_paths() -> Vec<PathBuf> {
+    let Some(home) = cargo_home() else {
+        return Vec::new();
+    };
+
+    let mut package_ids: Vec<String> = Vec::new();
+    if let Ok(content) = std::fs::read_to_string(home.join(".crates2.json")) {
+        match serde_json::from_str::<Crates2>(&content) {
+            Ok(crates) => package_ids.extend(crates.installs.into_keys()),
+            Err(e) => eprintln!(
+                "Failed to parse the installed crates in .crates2.json: {}",
+                e
+            ),
+        }
+    }
+    if let Ok(content) = std::fs::read_to_string(home.join(".crates.toml")) {
+        match toml::from_str::<Crates1>(&content) {
+            Ok(crates) => package_ids.extend(crates.v1.into_keys()),
+            Err(e) => eprintln!(
+                "Failed to parse the installed crates in .crates.toml: {}",
+                e
+            ),
+        }
+    }
+
+    let mut paths: Vec<PathBuf> = package_ids
+        .iter()
+        .filter_map(|id| source_path(id))
+        .map(crate::canonicalize_or_not)
+        .collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Extract the local path from a package id like `foo 0.1.0 (path+file:///home/user/foo)`.
+/// Packages that were installed from a registry or git repository don't have a local path
+fn source_path(package_id: &str) -> Option<PathBuf> {
+    let source = package_id.rsplit_once(" (")?.1.strip_suffix(')')?;
+    let path = percent_decode(source.strip_prefix("path+file://")?);
+
+    // Windows paths look like `/C:/Users/...` in file URLs
+    match path.strip_prefix('/') {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => Some(PathBuf::from(rest)),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+/// Decode the `%20` style escapes of a URL path
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let decoded = (b == b'%')
+            .then(|| tail.get(..2))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
@@ -0,0 +1,83 @@
+//! The per-user lock that prevents two cleanups from running at the same time, for example a
+//! scheduled one and a manual one, which would scan and delete the same targets simultaneously.
+
+use crate::config;
+use std::{
+    fs::{File, TryLockError},
+    io::{self, Read, Seek, Write},
+};
+
+/// Name of the lock file in the config directory. It contains the process id of the instance that
+/// holds the lock
+const LOCK_FILE_NAME: &str = "lock";
+
+/// The held lock, which is released when it is dropped or the process exits
+pub struct InstanceLock {
+    _file: Option<File>,
+}
+
+/// Take the lock or exit with an error if another instance holds it. If the lock can't be taken
+/// for other reasons, the run continues without it after a warning.
+pub fn lock_or_exit() -> InstanceLock {
+    lock().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    })
+}
+
+/// Take the lock or return an error if another instance holds it. Like with `lock_or_exit`, other
+/// failures only cause a warning.
+pub fn lock() -> io::Result<InstanceLock> {
+    match try_lock() {
+        Ok(file) => Ok(InstanceLock { _file: file }),
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+            let pid = holder()
+                .map(|pid| format!(" (pid {})", pid))
+                .unwrap_or_default();
+            Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                format!(
+                    "Another instance of cargo-clean-all is running{}. Wait for it to finish before starting another cleanup",
+                    pid
+                ),
+            ))
+        }
+        Err(e) => {
+            eprintln!("Warning: Failed to take the instance lock: {}", e);
+            Ok(InstanceLock { _file: None })
+        }
+    }
+}
+
+fn try_lock() -> io::Result<Option<File>> {
+    let Some(dir) = config::config_dir() else {
+        return Ok(None);
+    };
+    std::fs::create_dir_all(&dir)?;
+
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(dir.join(LOCK_FILE_NAME))?;
+    file.try_lock().map_err(|e| match e {
+        TryLockError::WouldBlock => io::ErrorKind::WouldBlock.into(),
+        TryLockError::Error(e) => e,
+    })?;
+
+    file.set_len(0)?;
+    file.rewind()?;
+    write!(file, "{}", std::process::id())?;
+    Ok(Some(file))
+}
+
+/// The process id of the instance that holds the lock, if it is known
+fn holder() -> Option<u32> {
+    let mut content = String::new();
+    File::open(config::config_dir()?.join(LOCK_FILE_NAME))
+        .ok()?
+        .read_to_string(&mut content)
+        .ok()?;
+    content.trim().parse().ok()
+}
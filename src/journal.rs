@@ -0,0 +1,310 @@
+//! The journal of a running cleanup. It lists the projects that are about to be cleaned and is
+//! updated after each project, so that an interrupted cleanup can be continued using the `resume`
+//! subcommand without scanning and confirming again. Targets that are deleted in the background
+//! are recorded when they are renamed, so that resuming finishes their deletion.
+
+use crate::{
+    cleanup, config,
+    i18n::{self, tr},
+    json,
+    output::{self, outln},
+    plan::{self, PlannedProject},
+    pretty_format_path, AppArgs, Cli, Command, ProjectTargetAnalysis,
+};
+use clap::Parser;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the journal in the config directory
+const JOURNAL_FILE_NAME: &str = "journal.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Journal {
+    /// The arguments of the run that started the cleanup without the program name. These
+    /// determine how the remaining projects are cleaned when resuming
+    pub args: Vec<String>,
+    /// The time the cleanup was started at as RFC 3339 timestamp
+    pub started: String,
+    pub projects: Vec<PlannedProject>,
+    /// The canonicalized paths of the projects that were already processed
+    pub completed: Vec<PathBuf>,
+    /// The targets that were renamed for the deletion in the background, but not deleted yet
+    #[serde(default)]
+    pub renamed: Vec<RenamedTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenamedTarget {
+    /// The canonicalized path of the project
+    pub project_path: PathBuf,
+    /// The path the target was renamed to
+    pub renamed: PathBuf,
+}
+
+fn journal_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(JOURNAL_FILE_NAME))
+}
+
+impl Journal {
+    /// Write the journal for the selected projects before the cleanup starts. Returns `None` if
+    /// the journal can't be written, which only means that the cleanup can't be resumed
+    pub fn start(args: &[String], selected: &[ProjectTargetAnalysis]) -> Option<Self> {
+        let journal = Self {
+            args: args.to_vec(),
+            started: chrono::Local::now().to_rfc3339(),
+            projects: selected.iter().map(PlannedProject::from).collect(),
+            completed: Vec::new(),
+            renamed: Vec::new(),
+        };
+        match journal.save() {
+            Ok(()) => Some(journal),
+            Err(e) => {
+                eprintln!("Warning: Failed to write the cleanup journal: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Load the journal of an interrupted cleanup, if there is one
+    pub fn load() -> io::Result<Option<Self>> {
+        let Some(path) = journal_path() else {
+            return Ok(None);
+        };
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let path = journal_path().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "The config directory could not be determined",
+            )
+        })?;
+        std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+
+        // Writing to a temporary file first makes sure that an interruption never leaves a
+        // truncated journal behind
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(tmp_path, path)
+    }
+
+    /// Record that the project was processed, regardless of whether it was cleaned successfully
+    pub fn complete(&mut self, project_path: &Path) -> io::Result<()> {
+        self.completed
+            .push(crate::canonicalize_or_not(project_path));
+        self.save()
+    }
+
+    /// Record that the target of the project was renamed and is deleted in the background
+    pub fn rename(&mut self, project_path: &Path, renamed: &Path) -> io::Result<()> {
+        self.renamed.push(RenamedTarget {
+            project_path: crate::canonicalize_or_not(project_path),
+            renamed: renamed.to_path_buf(),
+        });
+        self.save()
+    }
+
+    /// Record that the deletion of the renamed target of the project finished
+    pub fn complete_renamed(&mut self, project_path: &Path) -> io::Result<()> {
+        let project_path = crate::canonicalize_or_not(project_path);
+        self.renamed.retain(|r| r.project_path != project_path);
+        self.completed.push(project_path);
+        self.save()
+    }
+
+    /// Remove the journal after the cleanup ran to the end. It is kept if some projects were not
+    /// processed, because the time limit was reached or the cleanup was quit, so that they can be
+    /// cleaned by resuming
+    pub fn finish(self) {
+        let unprocessed = self
+            .projects
+            .iter()
+            .filter(|p| !self.completed.contains(&p.project_path))
+            .count()
+            + self.renamed.len();
+        if unprocessed > 0 {
+            outln!(
+                "{}",
+                tr!(
+                    "{} projects were not processed, the cleanup can be continued using `cargo clean-all resume`",
+                    unprocessed
+                )
+            );
+            return;
+        }
+        if let Some(path) = journal_path() {
+            if let Err(e) = std::fs::remove_file(path) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    eprintln!("Warning: Failed to remove the cleanup journal: {}", e);
+                }
+            }
+        }
+    }
+
+    /// The options of the interrupted run
+    fn app_args(&self) -> io::Result<AppArgs> {
+        let cli = Cli::try_parse_from(
+            std::iter::once("cargo-clean-all").chain(self.args.iter().map(String::as_str)),
+        )
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid arguments in the journal: {}", e),
+            )
+        })?;
+        Ok(match cli.command {
+            Some(Command::Plan(plan_args)) => plan_args.args,
+            Some(Command::Serve(serve_args)) => serve_args.args,
+            _ => cli.args,
+        })
+    }
+}
+
+/// Continue the cleanup of the projects that were not processed before the last cleanup was
+/// interrupted. Projects whose target directory was modified since then are skipped.
+pub fn resume(dry_run: bool) -> io::Result<()> {
+    let Some(mut journal) = Journal::load()? else {
+        outln!("{}", tr!("There is no interrupted cleanup to resume"));
+        return Ok(());
+    };
+    let args = journal.app_args()?;
+
+    output::set_quiet(args.oneline || args.json);
+    if let Some(locale) = args.lang {
+        i18n::set_locale(locale);
+    }
+    crate::ensure_not_root(&args);
+    let _lock = (!dry_run).then(crate::instance_lock::lock_or_exit);
+
+    outln!(
+        "{}",
+        tr!("Resuming the cleanup started at {}", journal.started)
+    );
+
+    // The projects whose renamed target was not deleted yet are finished by deleting it, their
+    // target directory may already be in use again
+    let remaining: Vec<_> = std::mem::take(&mut journal.projects)
+        .into_iter()
+        .filter(|p| {
+            !journal.completed.contains(&p.project_path)
+                && !journal
+                    .renamed
+                    .iter()
+                    .any(|r| r.project_path == p.project_path)
+        })
+        .collect();
+    let selected = plan::analyze_planned(&remaining, &args, &tr!("the cleanup was started"));
+    let json_report = args
+        .json
+        .then(|| json::Report::new(false, &selected, &[], args.group_by));
+
+    // The resumed cleanup is recorded as a run of its own
+    #[cfg(feature = "db")]
+    let mut db = args.db.as_ref().and_then(|path| {
+        crate::db::Db::record_scan(path, &journal.args, dry_run, false, &selected, &[])
+            .map_err(|e| eprintln!("Failed to record the scan in the database: {}", e))
+            .ok()
+    });
+
+    if selected.is_empty() && journal.renamed.is_empty() {
+        outln!("{}", tr!("None of the remaining projects can be cleaned"));
+        if !dry_run {
+            journal.finish();
+        }
+        if let Some(report) = json_report {
+            report.status(json::Status::Cleaned).print();
+        }
+        return Ok(());
+    }
+
+    if !journal.renamed.is_empty() {
+        outln!(
+            "\n{}",
+            tr!("Finishing the deletion of the renamed targets:")
+        );
+        for renamed in &journal.renamed {
+            outln!("{}", pretty_format_path(&renamed.renamed));
+        }
+    }
+
+    if !selected.is_empty() {
+        outln!("\n{}", tr!("Cleaning the remaining project directories:"));
+        let width = output::terminal_width();
+        selected
+            .iter()
+            .for_each(|p| outln!("{}", p.listing_line(width)));
+
+        let will_free_size: u64 = selected.iter().map(ProjectTargetAnalysis::total_size).sum();
+        outln!(
+            "\n{}",
+            tr!(
+                "Cleaning {} projects will free: {}",
+                selected.len(),
+                i18n::format_size(will_free_size).bold()
+            )
+        );
+    }
+
+    if dry_run {
+        outln!("{}", tr!("Dry run. Not doing any cleanup"));
+        if let Some(report) = json_report {
+            report.status(json::Status::DryRun).print();
+        }
+        return Ok(());
+    }
+
+    // The projects were confirmed when the cleanup was started
+    outln!("{}", tr!("Starting cleanup..."));
+    let renamed: Vec<_> = journal
+        .renamed
+        .iter()
+        .map(|r| (r.project_path.clone(), r.renamed.clone()))
+        .collect();
+    let mut leftovers = cleanup::LeftoverDeletion::default();
+    for (project_path, renamed) in renamed {
+        leftovers.delete(&renamed, args.fix_permissions);
+        if let Err(e) = journal.complete_renamed(&project_path) {
+            eprintln!("Error updating the cleanup journal: {}", e);
+        }
+    }
+
+    // The skipped projects are not resumed again
+    journal.projects = selected.iter().map(PlannedProject::from).collect();
+    let mut result = cleanup::cleanup(&args, &selected, Some(&mut journal));
+    leftovers.add_to(&mut result);
+    journal.finish();
+
+    #[cfg(feature = "db")]
+    if let Some(db) = &mut db {
+        if let Err(e) = db.record_cleanup(&result) {
+            eprintln!("Failed to record the cleanup in the database: {}", e);
+        }
+    }
+
+    outln!();
+    crate::print_cleanup_result(&result);
+
+    if args.oneline {
+        println!(
+            "cleaned {}/{} remaining projects, freed {}, {} failure{}",
+            result.cleaned,
+            selected.len(),
+            bytefmt::format(result.freed_size),
+            result.failed.len(),
+            if result.failed.len() == 1 { "" } else { "s" }
+        );
+    }
+    if let Some(report) = json_report {
+        report.with_cleanup(&result).print();
+    }
+    Ok(())
+}
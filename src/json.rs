@@ -0,0 +1,171 @@
+//! The machine readable output of `--json`, which replaces the human readable output with a single
+//! JSON document on stdout when the run is finished. The project entries are shared with the
+//! daemon API.
+
+use crate::{cleanup::CleanupResult, pretty_format_path, stats, ProjectTargetAnalysis};
+use serde::Serialize;
+use std::time::UNIX_EPOCH;
+
+#[derive(Serialize)]
+pub struct ProjectInfo {
+    pub path: String,
+    /// The size that is freed by cleaning the project in bytes
+    pub size: u64,
+    pub file_count: u64,
+    /// The last modified time of the target in seconds since the unix epoch
+    pub last_modified: u64,
+    pub toolchains: Vec<String>,
+    /// Whether the project matches the selection criteria
+    pub selected: bool,
+    pub never_clean: bool,
+    /// Whether this is a target directory that is shared by multiple projects, like the one of
+    /// `CARGO_TARGET_DIR`, whose path is given instead of a project path
+    pub shared_target: bool,
+}
+
+impl ProjectInfo {
+    pub fn new(project: &ProjectTargetAnalysis, selected: bool) -> Self {
+        Self {
+            path: pretty_format_path(&project.project_path),
+            size: project.total_size(),
+            file_count: project.file_count,
+            last_modified: project
+                .last_modified
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            toolchains: project.toolchains.iter().map(|tc| tc.to_string()).collect(),
+            selected,
+            never_clean: project.policy.never_clean,
+            shared_target: project.shared_target,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct GroupInfo {
+    group: String,
+    projects: usize,
+    reclaimable: u64,
+    selected: u64,
+}
+
+#[derive(Serialize)]
+struct FailureInfo {
+    path: String,
+    error: String,
+}
+
+#[derive(Serialize)]
+struct CleanupInfo {
+    cleaned: usize,
+    skipped: usize,
+    timed_out: usize,
+    /// The selected projects that were not cleaned, since the --max-free limit was reached
+    max_free_kept: usize,
+    freed: u64,
+    deleted_files: u64,
+    deletion_ms: u128,
+    failed: Vec<FailureInfo>,
+}
+
+/// How the run ended
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    #[default]
+    Deferred,
+    DryRun,
+    Cancelled,
+    Cleaned,
+}
+
+#[derive(Default, Serialize)]
+pub struct Report {
+    status: Status,
+    /// Why the cleanup was deferred
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    partial_scan: bool,
+    projects: Vec<ProjectInfo>,
+    /// The size that is freed by cleaning the selected projects
+    will_free: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<GroupInfo>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cleanup: Option<CleanupInfo>,
+}
+
+impl Report {
+    /// The report of a run that was deferred before scanning
+    pub fn deferred(reason: &str) -> Self {
+        Self {
+            reason: Some(reason.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// The report of the found projects, grouped if `--group-by` is used
+    pub fn new(
+        partial_scan: bool,
+        selected: &[ProjectTargetAnalysis],
+        ignored: &[ProjectTargetAnalysis],
+        group_by: Option<stats::GroupBy>,
+    ) -> Self {
+        Self {
+            partial_scan,
+            projects: selected
+                .iter()
+                .map(|p| ProjectInfo::new(p, true))
+                .chain(ignored.iter().map(|p| ProjectInfo::new(p, false)))
+                .collect(),
+            will_free: selected.iter().map(ProjectTargetAnalysis::total_size).sum(),
+            groups: group_by.map(|group_by| {
+                stats::group_totals(selected, ignored, group_by)
+                    .into_iter()
+                    .map(|(group, projects, reclaimable, selected)| GroupInfo {
+                        group,
+                        projects,
+                        reclaimable,
+                        selected,
+                    })
+                    .collect()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Add the result of the cleanup
+    pub fn with_cleanup(mut self, result: &CleanupResult) -> Self {
+        self.cleanup = Some(CleanupInfo {
+            cleaned: result.cleaned,
+            skipped: result.skipped,
+            timed_out: result.timed_out,
+            max_free_kept: result.max_free_kept,
+            freed: result.freed_size,
+            deleted_files: result.deleted_files,
+            deletion_ms: result.deletion_duration.as_millis(),
+            failed: result
+                .failed
+                .iter()
+                .map(|(p, e)| FailureInfo {
+                    path: pretty_format_path(&p.project_path),
+                    error: e.to_string(),
+                })
+                .collect(),
+        });
+        self.status(Status::Cleaned)
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn print(&self) {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize the JSON output: {}", e),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Deletion using the native `removefile` API of macOS. It removes the directory tree in a single
+//! call without changing the permissions of the entries first, which is a lot faster than deleting
+//! each file separately on APFS.
+
+use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path};
+
+/// `REMOVEFILE_RECURSIVE` from `removefile.h`
+const REMOVEFILE_RECURSIVE: u32 = 1 << 0;
+
+extern "C" {
+    fn removefile(path: *const libc::c_char, state: *mut libc::c_void, flags: u32) -> libc::c_int;
+}
+
+/// Recursively delete the directory using `removefile`
+pub fn remove_dir_all(path: &Path) -> io::Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    // SAFETY: The path is a valid NUL terminated string that outlives the call and removefile
+    // allows passing no state
+    let res = unsafe { removefile(c_path.as_ptr(), std::ptr::null_mut(), REMOVEFILE_RECURSIVE) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
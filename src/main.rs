@@ -1,16 +1,94 @@
-use clap::Parser;
+mod archive;
+mod audit;
+mod backup_exclusion;
+mod bench;
+mod build_lock;
+mod cargo_cache;
+mod cargo_clean;
+mod cargo_config;
+mod cleanup;
+mod config;
+mod coverage;
+#[cfg(unix)]
+mod daemon;
+#[cfg(feature = "db")]
+mod db;
+mod doctor;
+mod elevate;
+mod filter_expr;
+mod git;
+mod i18n;
+mod import;
+mod init;
+mod installed;
+mod instance_lock;
+mod journal;
+mod json;
+#[cfg(target_os = "macos")]
+mod macos;
+mod manifest;
+mod mounts;
+mod output;
+mod plan;
+mod policy;
+mod power;
+mod preserve;
+mod privileges;
+mod prune;
+mod safety;
+mod selector;
+#[cfg(feature = "self-update")]
+mod self_update;
+mod serve;
+mod sort;
+mod stats;
+mod storage;
+mod target_filter;
+mod toolchain;
+mod trim;
+#[cfg(feature = "tui")]
+mod tui;
+mod web_outputs;
+mod worker_pool;
+mod wsl;
+
+use clap::{
+    parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+};
 use colored::{Color, Colorize};
 use crossbeam_channel::{SendError, Sender};
+use dialoguer::console::measure_text_width;
+use filter_expr::{Expr, ExprFields};
+use i18n::tr;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
-use is_executable::is_executable;
+use output::outln;
+use policy::{Policy, PolicyResolver};
 use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     fmt::Display,
+    fs,
+    io::{self, Read},
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     thread,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
+use target_filter::{TargetFilter, TargetPattern};
+use toolchain::RustcVersion;
+
+/// Set while scanning for projects, so that Ctrl+C stops the scan instead of exiting
+static SCAN_RUNNING: AtomicBool = AtomicBool::new(false);
+/// Set when the scan was stopped early by Ctrl+C or the scan timeout
+static SCAN_STOPPED: AtomicBool = AtomicBool::new(false);
+/// Set by --follow-symlinks, so that the size calculations follow symlinks as well
+static FOLLOW_SYMLINKS: AtomicBool = AtomicBool::new(false);
 
-const SPINNER_TICK_STRS: &[&'static str] = &[
+const SPINNER_TICK_STRS: &[&str] = &[
     "[=---------]",
     "[-=--------]",
     "[--=-------]",
@@ -33,11 +111,197 @@ const SPINNER_TICK_STRS: &[&'static str] = &[
 ];
 
 #[derive(Debug, Parser)]
-#[clap(author, version, about, bin_name = "cargo clean-all", long_about = None)]
+#[clap(
+    author,
+    version,
+    about,
+    bin_name = "cargo clean-all",
+    long_about = None,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Write the config file from the given DIR, --keep-days and --keep-size without asking and
+    /// exit. The --ignore directories are added to the persistent ignore list
+    #[arg(long = "init-config")]
+    init_config: bool,
+
+    /// Add the ignored directories of another cleaning tool to the persistent ignore list and exit
+    #[arg(long = "import-config", value_enum, value_name = "TOOL")]
+    import_config: Option<import::ImportSource>,
+
+    /// The file to import using --import-config. For kondo, the shell config files in the home
+    /// directory are searched for kondo aliases by default
+    #[arg(long = "import-file", value_name = "FILE", requires = "import_config")]
+    import_file: Option<PathBuf>,
+
+    #[command(flatten)]
+    args: AppArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Restore the target directory of a project from an archive that was created using --archive
+    Unarchive {
+        /// The project whose target directory should be restored
+        #[arg(default_value = ".", value_name = "PROJECT")]
+        project: PathBuf,
+
+        /// The archive directory that was used with --archive
+        #[arg(long = "archive", value_name = "DIR")]
+        archive_dir: PathBuf,
+
+        /// Delete the archive and its index entry after it was restored successfully
+        #[arg(long = "remove-archive")]
+        remove_archive: bool,
+
+        /// Restore the archive even if the project already has a target directory. Existing
+        /// files with the same names are overwritten
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Replace this binary with the latest prebuilt release from GitHub. The downloaded binary is
+    /// verified using the published checksums
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long = "yes")]
+        yes: bool,
+
+        /// Also replace binaries that were installed using `cargo install`
+        #[arg(long = "force")]
+        force: bool,
+    },
+
+    /// Measure how fast the available deletion backends delete a synthetic target directory in
+    /// the given directory and recommend the settings for the fastest one. The synthetic targets
+    /// are generated in a new subdirectory that is removed afterwards
+    BenchDelete {
+        /// The directory on the storage to benchmark, for example the one that contains the projects
+        #[arg(default_value = ".", value_name = "DIR")]
+        dir: PathBuf,
+
+        /// The number of files in each synthetic target
+        #[arg(long = "files", value_name = "N", default_value_t = 20000)]
+        files: u64,
+    },
+
+    /// Show the locations that are used, like the config directory and the cargo home directory
+    /// with the registry and git caches
+    Doctor,
+
+    /// Create the config file by answering questions about the directories to scan, the keep
+    /// thresholds and the projects to ignore permanently
+    Init,
+
+    /// Select the projects to clean like a normal run, but save the selection to a plan file
+    /// instead of cleaning them. The plan can be reviewed and executed later using `apply`
+    Plan(Box<PlanArgs>),
+
+    /// Select the projects like a normal run, but show them in a local web dashboard instead of
+    /// cleaning them right away. The projects can be sorted, filtered and ticked in the browser,
+    /// which then starts the cleanup and shows its progress
+    Serve(Box<ServeArgs>),
+
+    /// Run in the background and offer scanning and cleaning as JSON-RPC API over HTTP on a unix
+    /// socket, so that editor extensions and other tools can integrate the cleanup
+    #[cfg(unix)]
+    Daemon {
+        /// The path of the socket. Defaults to daemon.sock in the config directory
+        #[arg(long = "socket", value_name = "PATH")]
+        socket: Option<PathBuf>,
+    },
+
+    /// Continue a cleanup that was interrupted, for example by Ctrl+C or a crash. The remaining
+    /// projects are cleaned without scanning and confirming them again
+    Resume {
+        /// Just list the remaining projects, but don't delete anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+
+    /// Clean the projects of a plan that was created using `plan`. Projects whose target directory
+    /// was modified after the plan was created are skipped
+    Apply {
+        /// The plan file
+        #[arg(value_name = "PLAN")]
+        plan: PathBuf,
+
+        /// Don't ask for confirmation
+        #[arg(short = 'y', long = "yes")]
+        yes: bool,
+
+        /// Just verify and list the planned projects, but don't delete anything
+        #[arg(long = "dry-run")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct PlanArgs {
+    /// The file the plan is written to
+    #[arg(short = 'o', long = "output", value_name = "FILE")]
+    output: PathBuf,
+
+    #[command(flatten)]
+    args: AppArgs,
+}
+
+#[derive(Debug, Parser)]
+struct ServeArgs {
+    /// The port on localhost the dashboard is served on
+    #[arg(long = "port", value_name = "PORT", default_value_t = 8470)]
+    port: u16,
+
+    #[command(flatten)]
+    args: AppArgs,
+}
+
+#[derive(Debug, Args)]
 struct AppArgs {
-    /// The directory in which the projects will be searched
-    #[arg(default_value_t  = String::from("."), value_name = "DIR")]
-    root_dir: String,
+    /// The directories in which the projects will be searched. Multiple directories are scanned
+    /// concurrently, so that fast disks don't wait for slow ones
+    #[arg(default_value = ".", value_name = "DIR")]
+    root_dirs: Vec<String>,
+
+    /// Don't scan for projects, but treat each DIR as a project. This is useful to use the
+    /// selective cleaning options for a single project
+    #[arg(long = "no-scan", alias = "this", conflicts_with = "all_users")]
+    no_scan: bool,
+
+    /// Don't scan for projects, but read the project directories from the specified file, or from
+    /// stdin when FILE is `-`. The directories are separated by newlines or NUL characters, paths
+    /// to Cargo.toml files are accepted as well. When reading from stdin, the confirmation is read
+    /// from the terminal
+    #[arg(
+        long = "projects-from",
+        value_name = "FILE",
+        conflicts_with_all = ["root_dirs", "no_scan", "all_users"]
+    )]
+    projects_from: Option<PathBuf>,
+
+    /// Scan the home directories of all users instead of DIR and report the reclaimable space per
+    /// user. This requires root or administrator privileges
+    #[arg(long = "all-users", conflicts_with = "root_dirs")]
+    all_users: bool,
+
+    /// Also scan the temporary directories of the system (like /tmp and /var/tmp, or %TEMP% on
+    /// Windows) for leftover projects that were created and built by tests or other tools
+    #[arg(long = "temp-dirs", conflicts_with_all = ["no_scan", "projects_from"])]
+    temp_dirs: bool,
+
+    /// Allow running as root or with administrator privileges. This is refused by default, since
+    /// wrong ignore rules can do much more damage with elevated privileges. Implied by --all-users
+    #[arg(long = "allow-root")]
+    allow_root: bool,
+
+    /// Only select the projects of the specified users for cleaning when using --all-users. The
+    /// projects of the other users are still listed, but kept
+    #[arg(long = "user", value_name = "NAME", requires = "all_users")]
+    users: Vec<String>,
 
     /// Don't ask for confirmation; Just clean all detected projects that are not excluded by other
     /// constraints
@@ -65,29 +329,182 @@ struct AppArgs {
     )]
     keep_last_modified: u32,
 
+    /// Ignore projects whose git repository got a commit in the last [DAYS] days. Unlike
+    /// --keep-days, this isn't affected by IDEs that check abandoned projects in the background.
+    /// Projects that are not in a git repository are not kept by this
+    #[arg(long = "keep-committed-days", value_name = "DAYS")]
+    keep_committed_days: Option<u32>,
+
+    /// Keep the N most recently compiled projects within each directory, so that only their older
+    /// siblings are cleaned. This is useful when projects are grouped in directories, for example
+    /// by client
+    #[arg(long = "keep-newest-per-dir", value_name = "N", default_value_t = 0)]
+    keep_newest_per_dir: usize,
+
+    /// Only select projects whose target directory was built by a toolchain matching the specified
+    /// glob pattern, for example `nightly-*`, `nightly-2023-*`, `1.68.*` or `stable-*`. The
+    /// toolchains are detected from the .rustc_info.json in the target directory
+    #[arg(long = "built-with", value_name = "TOOLCHAIN_PATTERN", value_parser = glob::Pattern::new)]
+    built_with: Vec<glob::Pattern>,
+
+    /// Only select projects whose directory name or package name matches the specified glob
+    /// pattern, for example `game-*`
+    #[arg(long = "match", value_name = "GLOB", value_parser = glob::Pattern::new)]
+    match_names: Vec<glob::Pattern>,
+
+    /// Don't select projects whose directory name or package name matches the specified glob
+    /// pattern, for example `work-*`
+    #[arg(long = "exclude-name", value_name = "GLOB", value_parser = glob::Pattern::new)]
+    exclude_names: Vec<glob::Pattern>,
+
+    /// Only select projects that contain crates of the specified kind, based on their manifest.
+    /// The targets of pure libraries are just a cache, while the artifacts of binaries might still
+    /// be used
+    #[arg(long = "only", value_enum, value_name = "KIND")]
+    only: Vec<manifest::CrateKind>,
+
+    /// Keep the projects that installed binaries were built from using `cargo install --path`,
+    /// since those are likely to be rebuilt and installed again soon
+    #[arg(long = "keep-installed")]
+    keep_installed: bool,
+
+    /// Keep the projects with uncommitted changes, including untracked files, or commits that are
+    /// not pushed yet, since those are probably still being worked on
+    #[arg(long = "keep-dirty")]
+    keep_dirty: bool,
+
+    /// Also keep the projects that are not in a git repository when using --keep-dirty. By default
+    /// they are treated like clean projects
+    #[arg(long = "keep-without-git", requires = "keep_dirty")]
+    keep_without_git: bool,
+
     /// Just collect the cleanable projects and list the freeable space, but don't delete anything
     #[arg(long = "dry-run")]
     dry_run: bool,
 
     /// The number of threads to use for directory scanning. 0 automatically selects the number of
-    /// threads
+    /// threads based on the storage the directories are on, with fewer threads for spinning disks
+    /// and network filesystems
     #[arg(
         short = 't',
-        long = "threads",
+        long = "scan-threads",
+        alias = "threads",
         value_name = "THREADS",
         default_value_t = 0
     )]
-    number_of_threads: usize,
+    scan_threads: usize,
+
+    /// Stop the scan after the specified number of seconds and continue with the projects found so
+    /// far. The scan can also be stopped early by pressing Ctrl+C
+    #[arg(long = "scan-timeout", value_name = "SECONDS")]
+    scan_timeout: Option<u64>,
+
+    /// Limit the duration of the whole run, like `45m` or `1h30m`. When the limit is reached, the
+    /// scan stops with the projects found so far and no further targets are deleted. Deletions
+    /// that are already running are finished
+    #[arg(long = "time-limit", value_name = "DURATION", value_parser = parse_duration_from_str)]
+    time_limit: Option<Duration>,
+
+    /// The end of the time limit, which is set when the run starts
+    #[arg(skip)]
+    deadline: Option<Instant>,
+
+    /// Read the metadata of the files in the selected targets on background threads ahead of the
+    /// deletion. This warms up the filesystem caches, which speeds up the deletion on cold caches,
+    /// especially on Windows
+    #[arg(long = "prefetch-metadata")]
+    prefetch_metadata: bool,
+
+    /// The number of threads to use for preserving artifacts and deleting the target directories.
+    /// 0 automatically selects the number of threads based on the storage the targets are on
+    #[arg(long = "delete-threads", value_name = "THREADS", default_value_t = 0)]
+    delete_threads: usize,
+
+    /// The number of projects whose targets are deleted at the same time. Deleting multiple
+    /// targets concurrently reduces the total time on fast SSDs, especially with many small
+    /// targets. The deletions share the --delete-threads threads
+    #[arg(
+        long = "parallel-projects",
+        value_name = "COUNT",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    parallel_projects: u16,
+
+    /// The order in which the selected projects are cleaned. Cleaning the biggest targets first
+    /// frees space the fastest, cleaning the oldest first loses the least when aborting midway.
+    /// With --max-free, the order decides which projects are cleaned
+    #[arg(
+        long = "delete-order",
+        value_enum,
+        value_name = "ORDER",
+        default_value_t = cleanup::DeleteOrder::AsListed
+    )]
+    delete_order: cleanup::DeleteOrder,
+
+    /// When deleting a target fails due to missing permissions, add the owner permissions to the
+    /// remaining directories and files (u+rwx) and try again. Some build scripts create read-only
+    /// output directories, which can't be deleted otherwise. Only supported on Unix
+    #[arg(long = "fix-permissions")]
+    fix_permissions: bool,
+
+    /// When deleting a target fails due to missing permissions, offer to delete it with elevated
+    /// privileges using sudo or the UAC prompt on Windows. Each target has to be confirmed
+    /// separately, even with --yes
+    #[arg(long = "elevate")]
+    elevate: bool,
 
     /// Show access errors that occur while scanning. By default those errors are hidden
     #[arg(short = 'v', long = "verbose")]
     verbose: bool,
 
+    /// Stop cleaning further projects once the cleaned ones freed up at least the specified amount
+    /// of space. The size can be specified like for --keep-size
+    #[arg(long = "max-free", value_name = "SIZE", value_parser = parse_bytes_from_str)]
+    max_free: Option<u64>,
+
+    /// Only clean the N largest of the selected projects. The other projects are kept and marked in
+    /// the listing
+    #[arg(long = "top", value_name = "N")]
+    top: Option<usize>,
+
+    /// Keep the combined size of all targets under the specified budget by only cleaning the least
+    /// recently used projects until the rest fits. The other selection criteria still protect
+    /// projects from being cleaned. The size can be specified like for --keep-size
+    #[arg(long = "budget", value_name = "SIZE", value_parser = parse_bytes_from_str, conflicts_with = "max_free")]
+    budget: Option<u64>,
+
+    /// Also clean projects whose target directory resolves to a path outside of the project, for
+    /// example because it is a symlink. By default those projects are refused
+    #[arg(long = "force")]
+    force: bool,
+
+    /// Also select projects that are being built right now. By default the targets whose lock is
+    /// held by a running cargo are kept, since deleting them breaks the build
+    #[arg(long = "force-locked")]
+    force_locked: bool,
+
+    /// Refuse to delete target directories with fewer path components than this, for example 2
+    /// for `/project/target`. Filesystem roots and the home directory are always refused
+    #[arg(long = "min-target-depth", value_name = "N", default_value_t = 2)]
+    min_target_depth: usize,
+
+    /// Ask for confirmation before cleaning each of the selected projects, with the options to
+    /// clean all remaining projects or to quit the cleanup
+    #[arg(long = "confirm-each", conflicts_with = "yes")]
+    confirm_each: bool,
+
     /// Use the interactive project selection. This will show a selection of all cleanable projects
     /// with the possibility to manually select or deselect
     #[arg(short = 'i', long = "interactive")]
     interactive: bool,
 
+    /// Manage the projects in a full-screen table that can be scrolled and searched, with the
+    /// details of the highlighted project. The cleanup is confirmed in the table
+    #[cfg(feature = "tui")]
+    #[arg(long = "tui", conflicts_with_all = ["interactive", "oneline", "json"])]
+    tui: bool,
+
     /// Directories that should be ignored by default, including subdirectories. This will still
     /// detect the projects in those directories, but mark them to not be cleaned. To actually skip
     /// scanning directories, use --skip instead.
@@ -95,22 +512,477 @@ struct AppArgs {
     #[arg(long = "ignore")]
     ignore: Vec<String>,
 
+    /// Append a record of everything that is deleted to the specified file. The file uses the JSON
+    /// Lines format and lists the deleted files with their sizes and modification times
+    #[arg(long = "audit-log", value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// Include the SHA-256 hashes of the executables preserved by --keep-executable in the audit
+    /// log
+    #[arg(long = "audit-hashes", requires = "audit_log")]
+    audit_hashes: bool,
+
+    /// Record the found projects and the result of the cleanup in the SQLite database at the
+    /// specified path, which is created if it doesn't exist. The database can be shared by multiple
+    /// machines and read by other tools
+    #[cfg(feature = "db")]
+    #[arg(long = "db", value_name = "PATH")]
+    db: Option<PathBuf>,
+
     /// Keeping compiled executables in release, debug and cross-compilation directories.
-    /// Moves the executable to a new folder outside of target.
+    /// Moves the executable to a new folder outside of target. Debug symbols (.pdb, .dSYM, .dwp)
+    /// of the executables are moved along with them.
     #[arg(short = 'e', long = "keep-executable")]
     executable: bool,
 
+    /// Keep the entries of the target directories that match the specified glob pattern while
+    /// deleting everything else. The patterns are relative to the target directory, for example
+    /// `release`, `doc` or `*/release` for the release builds of all cross-compilation targets
+    #[arg(long = "keep-target-subdir", value_name = "GLOB", value_parser = TargetPattern::parse)]
+    keep_target_subdirs: Vec<TargetPattern>,
+
+    /// Keep the Criterion benchmark history (target/criterion) while deleting the rest of the
+    /// target directories. This is a shorthand for `--keep-target-subdir criterion`
+    #[arg(long = "keep-criterion")]
+    keep_criterion: bool,
+
+    /// Delete the tarballs and verification builds that `cargo package` and `cargo publish` leave
+    /// in target/package. On its own, only target/package is deleted and only the projects that
+    /// have one are selected. Together with --keep-target-subdir, target/package is deleted even
+    /// if it matches one of the kept patterns
+    #[arg(long = "package-artifacts")]
+    package_artifacts: bool,
+
+    /// Delete the build outputs and release archives of cargo-dist in target/distrib, which are
+    /// rarely needed after the release was uploaded. Like --package-artifacts, on its own only
+    /// target/distrib is deleted and only the projects that have one are selected
+    #[arg(long = "dist-artifacts")]
+    dist_artifacts: bool,
+
+    /// Only delete the build outputs of the specified profiles, like `debug`, `release` or the
+    /// name of a custom profile, including the ones of cross-compilation targets. Only the
+    /// projects that have one of the profiles are selected and the size per profile is listed.
+    /// The `dev` and `test` profiles are stored in `debug`, the `bench` profile in `release`
+    #[arg(long = "profile", value_name = "PROFILE", value_delimiter = ',')]
+    profiles: Vec<String>,
+
+    /// Only delete the generated documentation in target/doc and in the doc directories of
+    /// cross-compilation targets, while keeping the compiled artifacts. The listed sizes and ages
+    /// are the ones of the documentation, so only the projects that have any are selected
+    #[arg(
+        long = "doc-only",
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "executable",
+            "prune_outdated_deps",
+            "per_project_budget",
+            "prune_older_than",
+            "prune_toolchains"
+        ]
+    )]
+    doc_only: bool,
+
+    /// Instead of deleting the whole target directories, only delete the artifacts of dependency
+    /// versions that are not in the Cargo.lock of the project anymore. Projects without a
+    /// Cargo.lock are reported as failed
+    #[arg(
+        long = "prune-outdated-deps",
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "package_artifacts",
+            "dist_artifacts",
+            "profiles"
+        ]
+    )]
+    prune_outdated_deps: bool,
+
+    /// Instead of deleting the whole target directories, only select the projects whose target is
+    /// larger than the specified size and trim them to that size by deleting their least recently
+    /// used artifacts. The size can be specified like for --keep-size
+    #[arg(
+        long = "per-project-budget",
+        value_name = "SIZE",
+        value_parser = parse_bytes_from_str,
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "package_artifacts",
+            "dist_artifacts",
+            "profiles",
+            "prune_outdated_deps"
+        ]
+    )]
+    per_project_budget: Option<u64>,
+
+    /// Instead of deleting the whole target directories, only delete the artifacts that were not
+    /// used in the specified number of days, like the files of crates that are not compiled
+    /// anymore or old incremental compilation sessions. The recently used artifacts are kept, so
+    /// they don't have to be rebuilt
+    #[arg(
+        long = "prune-older-than",
+        value_name = "DAYS",
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "package_artifacts",
+            "dist_artifacts",
+            "profiles",
+            "prune_outdated_deps",
+            "per_project_budget"
+        ]
+    )]
+    prune_older_than: Option<u32>,
+
+    /// Instead of deleting the whole target directories, only delete the artifacts that were built
+    /// by toolchains that are not installed anymore (`uninstalled`), or by any other toolchain
+    /// than the one that is active in the project (`inactive`), like `cargo sweep --installed`.
+    /// Artifacts whose toolchain can't be determined are kept
+    #[arg(
+        long = "prune-toolchains",
+        value_name = "MODE",
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "package_artifacts",
+            "dist_artifacts",
+            "profiles",
+            "prune_outdated_deps",
+            "per_project_budget",
+            "prune_older_than"
+        ]
+    )]
+    prune_toolchains: Option<toolchain::PruneToolchains>,
+
+    /// Pack the generated documentation (target/doc) of each cleaned project into a zip archive in
+    /// the specified directory before deleting it. Projects where archiving fails are not cleaned
+    #[arg(long = "archive-docs", value_name = "DIR")]
+    archive_docs: Option<PathBuf>,
+
+    /// Pack the target directory of each cleaned project into a zstd compressed tar archive in the
+    /// specified directory before deleting it. The archived targets are recorded in an index file
+    /// in that directory. Projects where archiving fails are not cleaned
+    #[arg(long = "archive", value_name = "DIR")]
+    archive: Option<PathBuf>,
+
+    /// Also delete the coverage data files (`*.profraw` and `*.gcda`) that coverage tools leave in
+    /// the project directories outside of the target directory when cleaning a project
+    #[arg(long = "coverage")]
+    coverage: bool,
+
+    /// Also delete the output directories of wasm-pack (`pkg`) and trunk (`dist`) next to the
+    /// target directory when cleaning a project
+    #[arg(long = "web-outputs")]
+    web_outputs: bool,
+
+    /// Keeping the .wasm outputs of wasm32 targets and the JS bindings generated by wasm-bindgen.
+    /// Moves them to a new folder outside of target.
+    #[arg(long = "keep-wasm")]
+    keep_wasm: bool,
+
     /// Directories that should be fully skipped during scanning, including subdirectories. This
     /// will speed up the scanning time by not doing any reads for the specified directories.
     /// The directories can be specified as absolute paths or relative to the workdir.
     #[arg(long = "skip")]
     skip: Vec<String>,
 
+    /// Don't skip the directories that are ignored by ripgrep style `.ignore` and `.rgignore`
+    /// files during the scan
+    #[arg(long = "no-ignore-files")]
+    no_ignore_files: bool,
+
+    /// Don't skip the directories that are ignored by `.gitignore` files during the scan, but
+    /// still respect `.ignore` and `.rgignore` files
+    #[arg(long = "no-gitignore")]
+    no_gitignore: bool,
+
+    /// Follow symlinks to directories when searching for projects and when calculating the size of
+    /// the targets. Directories that were already visited are detected, so looping links are only
+    /// followed once. Cleaning a target that is a symlink only removes the link
+    #[arg(long = "follow-symlinks", overrides_with = "no_follow_symlinks")]
+    follow_symlinks: bool,
+
+    /// Don't follow symlinks, which is the default. This overrides --follow-symlinks given earlier
+    #[arg(long = "no-follow-symlinks", overrides_with = "follow_symlinks")]
+    no_follow_symlinks: bool,
+
+    /// Names of directories that should be fully skipped during scanning wherever they are, in
+    /// addition to the built-in list of build and cache directories of other languages, like
+    /// `node_modules` and `.venv`
+    #[arg(long = "skip-name", value_name = "NAME")]
+    skip_names: Vec<String>,
+
+    /// Don't skip the built-in list of build and cache directories of other languages during the
+    /// scan. The --skip-name directories are still skipped
+    #[arg(long = "no-default-skips")]
+    no_default_skips: bool,
+
+    /// Mount points that the scan should not descend into, even if they are inside of DIR. The
+    /// skipped mounts are listed in the summary
+    #[arg(long = "exclude-mount", value_name = "PATH")]
+    exclude_mounts: Vec<PathBuf>,
+
     /// Maximum depth of subdirectories that should be scanned looking for the **`target/`**. This will speed up the scanning
     /// The option is for target/ dir, NOT for the project dir
     /// 0 means no limit
     #[arg(long = "depth", default_value_t = 0)]
     depth: usize,
+
+    /// Exclude the target directories of the projects that are kept from OS backups. On macOS
+    /// this sets the Time Machine exclusion, on Windows the directories are marked as not content
+    /// indexed, which also excludes them from File History
+    #[arg(long = "exclude-from-backup")]
+    exclude_from_backup: bool,
+
+    /// When running in WSL, delete the targets on Windows drives (like `/mnt/c`) using the Windows
+    /// `rmdir` command instead of going through the slow WSL filesystem bridge
+    #[arg(long = "windows-native-delete")]
+    windows_native_delete: bool,
+
+    /// Rename each target directory first and delete the renamed directories in the background,
+    /// so that the projects can be built again right away. The run still waits for the deletions
+    /// before exiting. This helps especially on Windows, where deleting large targets is slow
+    #[arg(long = "background-delete")]
+    background_delete: bool,
+
+    /// Move the target directories to the trash of the system instead of deleting them, so that
+    /// accidental cleanups can be restored. The space is only freed when the trash is emptied.
+    /// Only whole target directories can be moved to the trash
+    #[cfg(feature = "trash")]
+    #[arg(
+        long = "trash",
+        conflicts_with_all = [
+            "prune_outdated_deps",
+            "per_project_budget",
+            "prune_older_than",
+            "prune_toolchains",
+            "background_delete",
+            "use_cargo_clean"
+        ]
+    )]
+    trash: bool,
+
+    /// Run `cargo clean` in each selected project instead of deleting the target directory
+    /// directly, so that the cleanup follows the semantics of cargo. The --profile profiles are
+    /// passed on to cargo
+    #[arg(
+        long = "use-cargo-clean",
+        conflicts_with_all = [
+            "keep_target_subdirs",
+            "keep_criterion",
+            "package_artifacts",
+            "dist_artifacts",
+            "doc_only",
+            "prune_outdated_deps",
+            "per_project_budget",
+            "prune_older_than",
+            "prune_toolchains",
+            "background_delete",
+            "windows_native_delete"
+        ]
+    )]
+    use_cargo_clean: bool,
+
+    /// Only clean the artifacts of the specified packages with --use-cargo-clean, which is passed
+    /// on to `cargo clean` as `-p`
+    #[arg(
+        long = "cargo-clean-package",
+        value_name = "SPEC",
+        value_delimiter = ',',
+        requires = "use_cargo_clean"
+    )]
+    cargo_clean_packages: Vec<String>,
+
+    /// When running in a container, also select the projects whose target directory is on the
+    /// overlay filesystem of the container. By default only the targets on mounted volumes are
+    /// cleaned, since the container filesystem is discarded anyway
+    #[arg(long = "clean-overlay")]
+    clean_overlay: bool,
+
+    /// Only select projects that match the given expression. Supported fields are `size`,
+    /// `age_days`, `name` and `path`, which can be compared with `<`, `<=`, `>`, `>=`, `==`, `!=`
+    /// and matched against regular expressions with `~` and `!~`. Conditions can be combined with
+    /// `&&`, `||`, `!` and parentheses.
+    /// Example: `size > 2GiB && age_days > 60 && path ~ "experiments"`
+    #[arg(long = "where", value_name = "EXPR", value_parser = Expr::parse)]
+    filter: Option<Expr>,
+
+    /// Show the sizes of the registry and git caches in the cargo home directory together with the
+    /// projects, so that the summary reflects the total Rust related disk usage. The caches are
+    /// not cleaned
+    #[arg(long = "cache-stats")]
+    cache_stats: bool,
+
+    /// Also run the garbage collection of the registry and git caches that is built into cargo
+    /// (`cargo clean gc`) and include what it removed in the summary. This is skipped if the
+    /// installed cargo doesn't support it. The targets are still cleaned by cargo-clean-all
+    #[arg(long = "cargo-gc")]
+    cargo_gc: bool,
+
+    /// Show the distribution of the target ages and sizes of all found projects, including
+    /// percentiles and histograms. This helps to pick sensible values for --keep-days and
+    /// --keep-size
+    #[arg(long = "stats")]
+    stats: bool,
+
+    /// Show subtotals of the reclaimable and selected space with the projects grouped by their
+    /// parent directory, the month their target was last used or the toolchain it was built with
+    #[arg(long = "group-by", value_enum, value_name = "GROUP")]
+    group_by: Option<stats::GroupBy>,
+
+    /// Sort the listing and the interactive selection by the size of the targets, the name or path
+    /// of the projects or the time the targets were last modified
+    #[arg(
+        long = "sort",
+        value_enum,
+        value_name = "KEY",
+        default_value_t = sort::SortKey::Size
+    )]
+    sort: sort::SortKey,
+
+    /// Sort the listing in ascending or descending order
+    #[arg(
+        long = "sort-order",
+        value_enum,
+        value_name = "ORDER",
+        default_value_t = sort::SortOrder::Asc
+    )]
+    sort_order: sort::SortOrder,
+
+    /// List the N largest individual files across the targets of all found projects, since a
+    /// single huge binary or LTO artifact is easily hidden by the total target sizes
+    #[arg(long = "largest-files", value_name = "N")]
+    largest_files: Option<usize>,
+
+    /// Skip the cleanup if the system is running on battery. This is intended for scheduled
+    /// cleanups, which should rather be deferred to the next run than cause a lot of I/O on battery
+    #[arg(long = "only-on-ac")]
+    only_on_ac: bool,
+
+    /// Skip the cleanup if there was user input in the last [MINUTES] minutes. This is intended
+    /// for scheduled cleanups. The idle time is detected using `xprintidle` on Linux and is not
+    /// supported on Windows
+    #[arg(long = "min-idle", value_name = "MINUTES")]
+    min_idle: Option<u64>,
+
+    /// Only print a single summary line like "cleaned 14/62 projects, freed 38.2 GB, 1 failure"
+    /// and suppress all other output. This is intended for cronjobs and logs
+    #[arg(long = "oneline", conflicts_with = "interactive")]
+    oneline: bool,
+
+    /// Print the found projects and the result of the cleanup as a JSON document instead of the
+    /// human readable output, including the errors of failed cleanups. This is intended for
+    /// scripts, usually together with --yes or --dry-run
+    #[arg(long = "json", conflicts_with_all = ["interactive", "oneline"])]
+    json: bool,
+
+    /// The language of the output, like `de` or `fr`. By default the language is detected from
+    /// the LANG environment variable. Currently English, German, French and Spanish are supported
+    #[arg(long = "lang", value_name = "LANG", value_parser = i18n::parse_locale)]
+    lang: Option<i18n::Locale>,
+}
+
+impl AppArgs {
+    /// Check if the projects are given directly instead of being searched
+    fn skip_scan(&self) -> bool {
+        self.no_scan || self.projects_from.is_some()
+    }
+
+    /// The entries of the target directories that should be kept or deleted
+    fn target_filter(&self) -> TargetFilter {
+        let mut filter = TargetFilter {
+            keep: self.keep_target_subdirs.clone(),
+            delete: Vec::new(),
+        };
+        if self.keep_criterion {
+            filter
+                .keep
+                .push(TargetPattern::parse("criterion").expect("Invalid pattern"));
+        }
+        filter.delete = self
+            .artifact_dirs()
+            .into_iter()
+            .map(|dir| TargetPattern::parse(dir).expect("Invalid pattern"))
+            .collect();
+        if self.doc_only {
+            for pattern in ["doc", "*/doc"] {
+                filter
+                    .delete
+                    .push(TargetPattern::parse(pattern).expect("Invalid pattern"));
+            }
+        }
+        // The profile directories of the host and of the cross-compilation targets
+        for dir in self.profile_dirs() {
+            let dir = glob::Pattern::escape(dir);
+            for pattern in [dir.clone(), format!("*/{}", dir)] {
+                filter
+                    .delete
+                    .push(TargetPattern::parse(&pattern).expect("Invalid pattern"));
+            }
+        }
+        filter
+    }
+
+    /// Whether the time limit of the run has been reached
+    fn deadline_passed(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Whether only some artifacts of the targets are deleted, instead of using the target filter
+    fn deletes_artifacts(&self) -> bool {
+        self.prune_outdated_deps
+            || self.per_project_budget.is_some()
+            || self.prune_older_than.is_some()
+            || self.prune_toolchains.is_some()
+    }
+
+    /// Whether the projects are selected in the full-screen table of --tui
+    fn uses_tui(&self) -> bool {
+        #[cfg(feature = "tui")]
+        return self.tui;
+        #[cfg(not(feature = "tui"))]
+        false
+    }
+
+    /// Whether the targets are moved to the trash instead of being deleted
+    fn moves_to_trash(&self) -> bool {
+        #[cfg(feature = "trash")]
+        return self.trash;
+        #[cfg(not(feature = "trash"))]
+        false
+    }
+
+    /// The directories in the target directories that contain the requested categories of
+    /// release artifacts
+    fn artifact_dirs(&self) -> Vec<&'static str> {
+        let mut dirs = Vec::new();
+        if self.package_artifacts {
+            dirs.push("package");
+        }
+        if self.dist_artifacts {
+            dirs.push("distrib");
+        }
+        dirs
+    }
+
+    /// The names of the directories in the targets that contain the outputs of the --profile
+    /// profiles
+    fn profile_dirs(&self) -> Vec<&str> {
+        let mut dirs: Vec<&str> = self
+            .profiles
+            .iter()
+            .map(|profile| match profile.as_str() {
+                "dev" | "test" => "debug",
+                "bench" => "release",
+                profile => profile,
+            })
+            .collect();
+        dirs.sort_unstable();
+        dirs.dedup();
+        dirs
+    }
 }
 
 /// Wrap the bytefmt::parse function to return the error as an owned String
@@ -118,6 +990,44 @@ fn parse_bytes_from_str(byte_str: &str) -> Result<u64, String> {
     bytefmt::parse(byte_str).map_err(|e| e.to_string())
 }
 
+/// Parse a duration like `90s`, `45m`, `2h` or `1h30m`. Plain numbers are seconds
+fn parse_duration_from_str(duration_str: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration '{}', use values like 90s, 45m or 1h30m",
+            duration_str
+        )
+    };
+    if let Ok(secs) = duration_str.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut secs: u64 = 0;
+    let mut rest = duration_str;
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit_end = rest[digits..]
+            .find(|c: char| c.is_ascii_digit())
+            .map_or(rest.len(), |len| digits + len);
+        let unit_secs = match &rest[digits..unit_end] {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "d" => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        secs = secs.saturating_add(value.saturating_mul(unit_secs));
+        rest = &rest[unit_end..];
+    }
+    Ok(Duration::from_secs(secs))
+}
+
 /// Try to get the canonicalized path and return the non canonicalized path if it doesn't work
 fn canonicalize_or_not(p: impl AsRef<Path>) -> PathBuf {
     std::fs::canonicalize(p.as_ref()).unwrap_or_else(|_| p.as_ref().to_path_buf())
@@ -129,8 +1039,12 @@ fn starts_with_canonicalized(a: impl AsRef<Path>, b: impl AsRef<Path>) -> bool {
 
 fn main() {
     // If the program is interrupted while in a dialog the cursor stays hidden. This makes sure
-    // that the cursor is shown when interrupting the program
+    // that the cursor is shown when interrupting the program. While scanning, the first Ctrl+C
+    // only stops the scan
     ctrlc::set_handler(|| {
+        if SCAN_RUNNING.load(Ordering::Relaxed) && !SCAN_STOPPED.swap(true, Ordering::Relaxed) {
+            return;
+        }
         let _ = dialoguer::console::Term::stdout().show_cursor();
         std::process::exit(1);
     })
@@ -140,240 +1054,1571 @@ fn main() {
     #[cfg(windows)]
     colored::control::set_virtual_terminal(true).unwrap();
 
-    let mut args = std::env::args();
+    let mut args: Vec<_> = std::env::args().collect();
 
     // When called using `cargo clean-all`, the argument `clean-all` is inserted. To fix the arg
     // alignment, one argument is dropped.
-    if let Some("clean-all") = std::env::args().nth(1).as_deref() {
-        args.next();
+    if args.get(1).map(String::as_str) == Some("clean-all") {
+        args.remove(0);
+    }
+
+    let matches = Cli::command().get_matches_from(&args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    if cli.init_config {
+        let roots_given = matches.value_source("root_dirs") == Some(ValueSource::CommandLine);
+        if let Err(e) = init::init_from_args(&cli.args, roots_given) {
+            eprintln!("Failed to write the config file: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if let Some(source) = cli.import_config {
+        if let Err(e) = import::import_config(source, cli.import_file.as_deref()) {
+            eprintln!("Failed to import the ignored directories: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    apply_config(&mut cli, &matches);
+
+    match cli.command {
+        None => clean(cli.args, &args[1..], RunMode::Clean),
+        Some(Command::Plan(plan_args)) => {
+            let PlanArgs {
+                output,
+                args: app_args,
+            } = *plan_args;
+            clean(app_args, &args[1..], RunMode::Plan(&output))
+        }
+        Some(Command::Serve(serve_args)) => {
+            let ServeArgs {
+                port,
+                args: mut app_args,
+            } = *serve_args;
+            // The selection in the dashboard is the confirmation
+            app_args.confirm_each = false;
+            clean(app_args, &args[1..], RunMode::Serve(port))
+        }
+        #[cfg(feature = "self-update")]
+        Some(Command::SelfUpdate { yes, force }) => {
+            if let Err(e) = self_update::self_update(yes, force) {
+                eprintln!("Self-update failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        #[cfg(unix)]
+        Some(Command::Daemon { socket }) => {
+            if let Err(e) = daemon::run(socket) {
+                eprintln!("The daemon failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Doctor) => doctor::doctor(),
+        Some(Command::Init) => {
+            if let Err(e) = init::init() {
+                eprintln!("Failed to create the config file: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::BenchDelete { dir, files }) => {
+            if let Err(e) = bench::bench_delete(&dir, files) {
+                eprintln!("The deletion benchmark failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Apply { plan, yes, dry_run }) => {
+            if let Err(e) = plan::apply(&plan, yes, dry_run) {
+                eprintln!("Failed to apply the plan: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Resume { dry_run }) => {
+            if let Err(e) = journal::resume(dry_run) {
+                eprintln!("Failed to resume the cleanup: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Some(Command::Unarchive {
+            project,
+            archive_dir,
+            remove_archive,
+            force,
+        }) => {
+            if let Err(e) = archive::unarchive(&project, &archive_dir, remove_archive, force) {
+                eprintln!("Failed to restore the archived target: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// Use the values of the config file for the options of a run that were not given on the command
+/// line
+fn apply_config(cli: &mut Cli, matches: &ArgMatches) {
+    let (args, matches) = match &mut cli.command {
+        None => (&mut cli.args, matches),
+        Some(Command::Plan(plan_args)) => (
+            &mut plan_args.args,
+            matches.subcommand_matches("plan").unwrap_or(matches),
+        ),
+        Some(Command::Serve(serve_args)) => (
+            &mut serve_args.args,
+            matches.subcommand_matches("serve").unwrap_or(matches),
+        ),
+        _ => return,
+    };
+    match config::read_config() {
+        Ok(Some(config)) => config.apply(args, matches),
+        Ok(None) => (),
+        Err(e) => {
+            eprintln!("Failed to read the config file: {}", e);
+            std::process::exit(1);
+        }
     }
+}
+
+const ROOT_REFUSAL: &str = "Refusing to run as root or administrator, since mistakes in the \
+    ignore rules could delete files of other users or the system. Use --allow-root to run anyway";
+
+/// Whether running with the args is refused, because the process has elevated privileges
+fn refuses_root(args: &AppArgs) -> bool {
+    !args.allow_root && !args.all_users && privileges::is_elevated()
+}
 
-    let args = AppArgs::parse_from(args);
+/// Check the combinations of options that clap can't express
+fn check_args(args: &AppArgs) -> Result<(), &'static str> {
+    if args.moves_to_trash() && !args.target_filter().deletes_all() {
+        return Err("--trash can only be used for deleting whole target directories");
+    }
+    Ok(())
+}
 
-    let scan_path = Path::new(&args.root_dir);
+/// Abort if running with elevated privileges without explicitly allowing it
+fn ensure_not_root(args: &AppArgs) {
+    if refuses_root(args) {
+        eprintln!("{}", ROOT_REFUSAL);
+        std::process::exit(1);
+    }
+}
 
-    let multi_progress = if args.verbose {
-        println!("Scanning for projects in {}", args.root_dir);
+/// Check the power and idle conditions of the args and return the reason if the cleanup should
+/// be deferred
+fn defer_reason(args: &AppArgs) -> Option<String> {
+    if args.only_on_ac {
+        match power::on_ac_power() {
+            Some(false) => return Some("the system is running on battery".to_string()),
+            Some(true) => (),
+            None => eprintln!("Could not determine the power source, ignoring --only-on-ac"),
+        }
+    }
+
+    if let Some(min_idle) = args.min_idle {
+        let min_idle = Duration::from_secs(min_idle * 60);
+        match power::idle_time() {
+            Some(idle) if idle < min_idle => {
+                return Some(format!(
+                    "the system was only idle for {} minutes",
+                    idle.as_secs() / 60
+                ))
+            }
+            Some(_) => (),
+            None => eprintln!("Could not determine the idle time, ignoring --min-idle"),
+        }
+    }
+
+    None
+}
+
+/// What happens with the selected projects
+enum RunMode<'a> {
+    /// Confirm and clean them
+    Clean,
+    /// Save them as plan to the given file
+    Plan(&'a Path),
+    /// Show them in the web dashboard on the given port
+    Serve(u16),
+    /// Return the found projects together with whether they are selected, without any output
+    Collect(&'a mut Vec<(ProjectTargetAnalysis, bool)>),
+}
+
+/// Find the projects, select the ones that should be cleaned and clean them, save them as plan or
+/// serve or return them depending on the mode. The raw arguments without the program name
+/// are saved in the plan or the cleanup journal, so that applying the plan or resuming the
+/// cleanup uses the same cleanup options.
+fn clean(mut args: AppArgs, raw_args: &[String], mode: RunMode) {
+    let run_start = Instant::now();
+    let collect = matches!(mode, RunMode::Collect(_));
+    output::set_quiet(args.oneline || args.json || collect);
+    FOLLOW_SYMLINKS.store(args.follow_symlinks, Ordering::Relaxed);
+    if let Some(locale) = args.lang {
+        i18n::set_locale(locale);
+    }
+    ensure_not_root(&args);
+
+    // The dashboard and the daemon clean on request, so only their scan is limited
+    if matches!(mode, RunMode::Clean) {
+        args.deadline = args.time_limit.map(|limit| run_start + limit);
+    }
+
+    // Runs that don't delete anything can run alongside another instance
+    // The dashboard only takes the lock while it cleans
+    let lock = (!args.dry_run && !collect && !matches!(mode, RunMode::Plan(_) | RunMode::Serve(_)))
+        .then(instance_lock::lock_or_exit);
+
+    if let (Some(_), Ok(Some(journal))) = (&lock, journal::Journal::load()) {
+        outln!(
+            "The cleanup started at {} was interrupted. It can be continued using `cargo clean-all resume`, starting another cleanup discards it",
+            journal.started
+        );
+    }
+
+    if let Some(reason) = defer_reason(&args) {
+        outln!("Deferring the cleanup, since {}", reason);
+        if args.oneline {
+            println!("cleanup deferred, {}", reason);
+        }
+        if args.json {
+            json::Report::deferred(&reason).print();
+        }
+        return;
+    }
+
+    if let Err(e) = check_args(&args) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
+    let mut scan_roots = match scan_roots(&args) {
+        Ok(roots) => roots,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    if args.temp_dirs {
+        let temp_roots = temp_scan_roots(&scan_roots);
+        scan_roots.extend(temp_roots);
+    }
+    let scan_paths: Vec<_> = scan_roots.iter().map(|root| root.path.clone()).collect();
+    let scan_paths_str = scan_paths
+        .iter()
+        .map(|p| pretty_format_path(p))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mounts = mounts::mounts();
+    let in_container = mounts::in_container();
+    let is_wsl = wsl::is_wsl();
+
+    // The mounts can only be verified where the mount table is known
+    if !mounts.is_empty() {
+        for path in args.exclude_mounts.iter().filter(|path| {
+            let path = canonicalize_or_not(path);
+            !mounts.iter().any(|m| m.mount_point == path)
+        }) {
+            eprintln!(
+                "Warning: {} is not a mount point, excluding it anyway",
+                pretty_format_path(path)
+            );
+        }
+    }
+
+    // Each root is scanned by its own threads, so that a root on slow storage doesn't occupy the
+    // threads that the other roots could use
+    let root_threads: Vec<usize> = scan_paths
+        .iter()
+        .map(|path| match args.scan_threads {
+            0 => storage::default_threads(&mounts, std::slice::from_ref(path)),
+            threads => threads,
+        })
+        .collect();
+
+    if is_wsl {
+        for path in scan_paths
+            .iter()
+            .filter(|p| mounts::mount_of(&mounts, p).is_some_and(wsl::is_windows_drive))
+        {
+            outln!(
+                "Warning: {} is on a Windows drive, scanning it through the WSL filesystem bridge \
+                 is slow",
+                pretty_format_path(path)
+            );
+        }
+    }
+
+    let multi_progress = if args.verbose && !args.skip_scan() {
+        outln!("{}", tr!("Scanning for projects in {}", scan_paths_str));
         MultiProgress::with_draw_target(ProgressDrawTarget::stderr_with_hz(10))
     } else {
         MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
     };
 
-    let spinner = ProgressBar::new_spinner()
-        .with_message(format!("Scanning for projects in {}", args.root_dir))
-        .with_style(ProgressStyle::default_spinner().tick_strings(SPINNER_TICK_STRS));
+    let spinner_message = if args.projects_from.is_some() {
+        format!("Analyzing {} listed projects", scan_paths.len())
+    } else {
+        tr!("Scanning for projects in {}", scan_paths_str)
+    };
+    let root_progress = MultiProgress::with_draw_target(output::progress_target());
+    let spinner = root_progress.add(
+        ProgressBar::new_spinner()
+            .with_message(spinner_message)
+            .with_style(ProgressStyle::default_spinner().tick_strings(SPINNER_TICK_STRS)),
+    );
+
+    // With multiple roots, each of them gets its own progress line below the spinner
+    let root_bars: Vec<ProgressBar> = if scan_paths.len() > 1 && !args.verbose && !args.skip_scan()
+    {
+        let style = ProgressStyle::with_template("  {wide_msg}").expect("Invalid template syntax");
+        scan_paths
+            .iter()
+            .map(|_| root_progress.add(ProgressBar::new_spinner().with_style(style.clone())))
+            .collect()
+    } else {
+        Vec::new()
+    };
 
     if !args.verbose {
         spinner.enable_steady_tick(Duration::from_millis(100));
     }
 
-    // Find project dirs and analyze them
-    let cargo_projects: Vec<_> =
-        find_cargo_projects(scan_path, &multi_progress, args.number_of_threads, &args)
-            .filter(|d| d.1)
-            .collect();
+    let scan_start = Instant::now();
+
+    // Find project dirs and analyze their targets. Without scanning, the given directories are the
+    // projects
+    let root_stats: Vec<ScanStats> = scan_paths.iter().map(|_| ScanStats::default()).collect();
+    let scan_done = AtomicBool::new(false);
+    let mut cargo_projects: Vec<_> = if args.skip_scan() {
+        scan_paths
+            .iter()
+            .map(|p| ProjectDir(p.clone(), Some(ProjectTargetAnalysis::analyze(p))))
+            .filter(|proj| proj.1.as_ref().is_some_and(|a| a.target_path.is_dir()))
+            .collect()
+    } else {
+        thread::scope(|scope| {
+            // The spinner message is refreshed periodically with the live counters, since the
+            // scan threads are too busy to do so themselves
+            let refresher = scope.spawn(|| {
+                while !scan_done.load(Ordering::Relaxed) {
+                    if args
+                        .scan_timeout
+                        .is_some_and(|secs| scan_start.elapsed().as_secs() >= secs)
+                        || args
+                            .time_limit
+                            .is_some_and(|limit| run_start.elapsed() >= limit)
+                    {
+                        SCAN_STOPPED.store(true, Ordering::Relaxed);
+                    }
+                    spinner.set_message(tr!(
+                        "Scanning for projects in {} ({}), press Ctrl+C to stop early",
+                        scan_paths_str,
+                        ScanStats::sum(&root_stats).summary()
+                    ));
+                    for ((bar, path), stats) in root_bars.iter().zip(&scan_paths).zip(&root_stats) {
+                        let state = match *stats.duration.lock().unwrap() {
+                            Some(duration) => format!("done after {:.2?}", duration),
+                            None => "scanning".to_string(),
+                        };
+                        bar.set_message(format!(
+                            "{}: {}, {}",
+                            pretty_format_path(path),
+                            state,
+                            stats.summary()
+                        ));
+                    }
+                    thread::park_timeout(Duration::from_millis(100));
+                }
+            });
+
+            SCAN_STOPPED.store(false, Ordering::Relaxed);
+            SCAN_RUNNING.store(true, Ordering::Relaxed);
+
+            let projects = thread::scope(|roots_scope| {
+                let scans: Vec<_> = scan_paths
+                    .iter()
+                    .zip(&root_threads)
+                    .zip(&root_stats)
+                    .map(|((path, &threads), stats)| {
+                        let (multi_progress, args) = (&multi_progress, &args);
+                        roots_scope.spawn(move || {
+                            let projects: Vec<_> = find_cargo_projects(
+                                std::slice::from_ref(path),
+                                multi_progress,
+                                threads,
+                                args,
+                                stats,
+                            )
+                            .filter(|d| d.1.is_some())
+                            .collect();
+                            *stats.duration.lock().unwrap() = Some(scan_start.elapsed());
+                            projects
+                        })
+                    })
+                    .collect();
+                scans
+                    .into_iter()
+                    .flat_map(|scan| scan.join().expect("A scan thread panicked"))
+                    .collect()
+            });
+            SCAN_RUNNING.store(false, Ordering::Relaxed);
+            scan_done.store(true, Ordering::Relaxed);
+            refresher.thread().unpark();
+            projects
+        })
+    };
+
+    // Overlapping scan roots, like roots inside of the temp dirs, find the same projects twice
+    if scan_paths.len() > 1 {
+        let mut seen = HashSet::new();
+        cargo_projects.retain(|proj| seen.insert(canonicalize_or_not(&proj.0)));
+    }
+
+    let mut cargo_projects = merge_shared_targets(cargo_projects);
+
+    // The projects don't have their own target directories when CARGO_TARGET_DIR is set, so the
+    // shared one is analyzed on its own, unless it is the target directory of a found project
+    if let Some(dir) = shared_target_dir(&scan_paths).filter(|_| !args.skip_scan()) {
+        if !cargo_projects.iter().any(|proj| {
+            proj.1
+                .as_ref()
+                .is_some_and(|a| canonicalize_or_not(&a.target_path) == dir)
+        }) {
+            let analysis = ProjectTargetAnalysis::analyze_shared(&dir);
+            cargo_projects.push(ProjectDir(dir, Some(analysis)));
+        }
+    }
 
     multi_progress.clear().unwrap();
     spinner.finish_and_clear();
+    root_bars.iter().for_each(ProgressBar::finish_and_clear);
+    let scan_stats = ScanStats::sum(&root_stats);
+    let scan_duration = scan_start.elapsed();
+    let partial_scan = SCAN_STOPPED.load(Ordering::Relaxed);
+    if partial_scan {
+        outln!(
+            "{}",
+            tr!(
+                "Scan stopped early after {}, the results are partial",
+                scan_stats.summary()
+            )
+        );
+    } else if args.verbose && !args.skip_scan() {
+        outln!("Scanned {}", scan_stats.summary());
+    }
 
-    println!("Computing size of target/ for project");
-    let pb = ProgressBar::new(cargo_projects.len() as u64).with_style(
-        ProgressStyle::with_template("[{elapsed}] [{bar:.cyan/blue}] {pos}/{len}: {msg}")
-            .expect("Invalid template syntax")
-            .progress_chars("#>-"),
-    );
+    // The target directories are already analyzed during the scan, but searching the projects for
+    // coverage data can still take a while, so the rest of the analysis gets its own progress bar
+    let pb =
+        ProgressBar::with_draw_target(Some(cargo_projects.len() as u64), output::progress_target())
+            .with_style(
+                ProgressStyle::with_template(&format!(
+                    "[{{elapsed}}] {} ({{pos}}/{{len}}) [{{bar:.cyan/blue}}] {{msg}}",
+                    tr!("Analyzing targets")
+                ))
+                .expect("Invalid template syntax")
+                .progress_chars("#>-"),
+            );
+    pb.enable_steady_tick(Duration::from_millis(100));
+
+    let analysis_start = Instant::now();
+    let mut policy_resolver = PolicyResolver::new(&scan_paths, args.verbose);
 
     let mut projects: Vec<_> = cargo_projects
         .into_iter()
         .filter_map(|proj| {
-            proj.1.then(|| {
+            proj.1.map(|mut analysis| {
                 pb.set_message(format!("{}", proj.0.display()));
-                let analysis = ProjectTargetAnalysis::analyze(&proj.0);
+                analysis.policy = policy_resolver.resolve(&proj.0);
+                analysis.find_extra_files(&args);
+                analysis.owner = scan_roots
+                    .iter()
+                    .find(|root| starts_with_canonicalized(&proj.0, &root.path))
+                    .and_then(|root| root.user.clone());
                 pb.inc(1);
                 analysis
             })
         })
         .collect();
 
-    pb.finish_and_clear();
+    pb.finish_and_clear();
+    let analysis_duration = analysis_start.elapsed();
+
+    sort::sort_projects(&mut projects, args.sort, args.sort_order);
+
+    let ignore_list = config::read_ignore_list().unwrap_or_else(|e| {
+        eprintln!("Failed to read the persistent ignore list: {}", e);
+        Vec::new()
+    });
+
+    let installed_projects = if args.keep_installed {
+        installed::installed_project_paths()
+    } else {
+        Vec::new()
+    };
+
+    // When only release artifacts are deleted, the projects without any are left alone
+    let artifact_dirs = args.artifact_dirs();
+    let profile_dirs = args.profile_dirs();
+    let only_artifacts = (!artifact_dirs.is_empty() || !profile_dirs.is_empty())
+        && args.target_filter().keep.is_empty();
+
+    // Determin what projects are selected by the restrictions
+    let mut preselected_projects = projects
+        .iter_mut()
+        .map(|tgt| {
+            let secs_elapsed = tgt
+                .last_modified
+                .elapsed()
+                .unwrap_or_default()
+                .as_secs_f32();
+            let days_elapsed = secs_elapsed / (60.0 * 60.0 * 24.0);
+            let ignored = tgt.policy.ignored
+                || tgt.policy.never_clean
+                || args
+                    .ignore
+                    .iter()
+                    .any(|p| starts_with_canonicalized(&tgt.project_path, p))
+                || ignore_list
+                    .iter()
+                    .any(|p| starts_with_canonicalized(&tgt.project_path, p));
+            let keep_days = tgt.policy.keep_days.unwrap_or(args.keep_last_modified);
+            let keep_size = tgt.policy.keep_size.unwrap_or(args.keep_size);
+
+            let matches_filter = args.filter.as_ref().is_none_or(|filter| {
+                filter.matches(&ExprFields {
+                    size: tgt.size,
+                    age_days: days_elapsed as f64,
+                    name: &tgt
+                        .project_path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                    path: &pretty_format_path(&canonicalize_or_not(&tgt.project_path)),
+                })
+            });
+
+            let on_overlay = in_container
+                && mounts::mount_of(&mounts, &tgt.target_path)
+                    .is_some_and(mounts::Mount::is_overlay);
+
+            let matches_user = args.users.is_empty()
+                || tgt
+                    .owner
+                    .as_ref()
+                    .is_some_and(|owner| args.users.contains(owner));
+
+            let matches_toolchain = args.built_with.is_empty()
+                || tgt
+                    .toolchains
+                    .iter()
+                    .any(|tc| args.built_with.iter().any(|pattern| tc.matches(pattern)));
+
+            // The installed crate can also be a member of the workspace that contains the target
+            let is_installed = !installed_projects.is_empty() && {
+                let project_path = canonicalize_or_not(&tgt.project_path);
+                installed_projects
+                    .iter()
+                    .any(|p| p.starts_with(&project_path))
+            };
+
+            let has_artifacts = !only_artifacts
+                || artifact_dirs
+                    .iter()
+                    .any(|dir| tgt.target_path.join(dir).is_dir())
+                || (!profile_dirs.is_empty()
+                    && prune::profile_dirs(&tgt.target_path)
+                        .iter()
+                        .any(|dir| profile_dirs.contains(&prune::file_name(dir))));
+
+            let matches_name = (args.match_names.is_empty() && args.exclude_names.is_empty()) || {
+                let names = project_names(tgt);
+                let matches = |pattern: &glob::Pattern| names.iter().any(|n| pattern.matches(n));
+                (args.match_names.is_empty() || args.match_names.iter().any(matches))
+                    && !args.exclude_names.iter().any(matches)
+            };
+
+            let matches_kind = args.only.is_empty()
+                || manifest::Manifest::read(&tgt.project_path).is_ok_and(|manifest| {
+                    manifest
+                        .crate_kinds(&tgt.project_path)
+                        .iter()
+                        .any(|kind| args.only.contains(kind))
+                });
+
+            days_elapsed >= keep_days as f32
+                && tgt.size > keep_size
+                && args
+                    .per_project_budget
+                    .is_none_or(|budget| tgt.size > budget)
+                && !ignored
+                && matches_filter
+                && matches_toolchain
+                && matches_name
+                && matches_kind
+                && !is_installed
+                && has_artifacts
+                && matches_user
+                && (!on_overlay || args.clean_overlay)
+                // These are checked last, since they are slow or report the kept projects
+                && args
+                    .keep_committed_days
+                    .is_none_or(|days| !is_recently_committed(tgt, days))
+                && (!args.keep_dirty || !is_dirty(tgt, &args))
+                && (args.force_locked || !is_being_built(tgt))
+        })
+        .collect::<Vec<_>>();
+
+    // Keep the most recently built projects within each parent directory
+    if args.keep_newest_per_dir > 0 {
+        let mut by_parent: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for (idx, proj) in projects.iter().enumerate() {
+            let parent = canonicalize_or_not(&proj.project_path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            by_parent.entry(parent).or_default().push(idx);
+        }
+
+        for siblings in by_parent.values_mut() {
+            siblings.sort_by_key(|&idx| std::cmp::Reverse(projects[idx].last_modified));
+            for &idx in siblings.iter().take(args.keep_newest_per_dir) {
+                preselected_projects[idx] = false;
+            }
+        }
+    }
+
+    // With a budget, the selection criteria only determine which projects may be cleaned. Of those,
+    // the least recently used ones are cleaned until the remaining targets fit into the budget
+    if let Some(budget) = args.budget {
+        let mut total: u64 = projects.iter().map(ProjectTargetAnalysis::total_size).sum();
+        let mut candidates: Vec<_> = (0..projects.len())
+            .filter(|&idx| preselected_projects[idx])
+            .collect();
+        candidates.sort_by_key(|&idx| projects[idx].last_modified);
+        preselected_projects.iter_mut().for_each(|p| *p = false);
+
+        let before = total;
+        for idx in candidates {
+            if total <= budget {
+                break;
+            }
+            preselected_projects[idx] = true;
+            total -= projects[idx].total_size();
+        }
+
+        let evicted = preselected_projects.iter().filter(|&&p| p).count();
+        if before <= budget {
+            outln!(
+                "{}",
+                tr!(
+                    "The targets take up {}, which is within the budget of {}",
+                    i18n::format_size(before),
+                    i18n::format_size(budget)
+                )
+            );
+        } else {
+            outln!(
+                "{}",
+                tr!(
+                    "The targets take up {}, cleaning the {} least recently used projects to get to {} within the budget of {}",
+                    i18n::format_size(before),
+                    evicted,
+                    i18n::format_size(total),
+                    i18n::format_size(budget)
+                )
+            );
+            if total > budget {
+                outln!(
+                    "{}",
+                    tr!("The budget can't be met, since the remaining projects are protected by the selection criteria")
+                );
+            }
+        }
+    }
+
+    // Only the largest projects are cleaned, the others are marked in the listing
+    let mut cut_off_by_top = HashSet::new();
+    if let Some(top) = args.top {
+        let mut candidates: Vec<_> = (0..projects.len())
+            .filter(|&idx| preselected_projects[idx])
+            .collect();
+        candidates.sort_by_key(|&idx| std::cmp::Reverse(projects[idx].total_size()));
+        for &idx in candidates.iter().skip(top) {
+            preselected_projects[idx] = false;
+            cut_off_by_top.insert(projects[idx].project_path.clone());
+        }
+    }
+
+    if args.interactive || args.uses_tui() {
+        let prompt = if partial_scan {
+            tr!("Select projects to clean (partial scan)")
+        } else {
+            tr!("Select projects to clean")
+        };
+        let selection = match select_interactively(&args, &prompt, &projects, &preselected_projects)
+        {
+            Ok(Some(selection)) => selection,
+            Ok(None) => {
+                outln!("{}", tr!("Nothing selected"));
+                return;
+            }
+            Err(e) => {
+                eprintln!("Interactive selection failed: {}", e);
+                return;
+            }
+        };
+
+        if !selection.ignored_permanently.is_empty() {
+            let ignored_paths: Vec<_> = selection
+                .ignored_permanently
+                .iter()
+                .map(|&idx| projects[idx].project_path.clone())
+                .collect();
+            match config::append_ignore_list(&ignored_paths) {
+                Ok(()) => ignored_paths
+                    .iter()
+                    .for_each(|p| outln!("Permanently ignoring {}", pretty_format_path(p))),
+                Err(e) => eprintln!("Failed to update the persistent ignore list: {}", e),
+            }
+        }
+
+        for idx in selection.selected {
+            if projects[idx].policy.never_clean {
+                outln!(
+                    "Not cleaning {}, since it is configured to never be cleaned",
+                    pretty_format_path(&projects[idx].project_path)
+                );
+                continue;
+            }
+            projects[idx].selected_for_cleanup = true;
+        }
+    } else {
+        for i in 0..preselected_projects.len() {
+            projects[i].selected_for_cleanup = preselected_projects[i];
+        }
+    }
+
+    let (selected, ignored): (Vec<_>, Vec<_>) = projects
+        .into_iter()
+        .partition(|proj| proj.selected_for_cleanup);
+
+    let will_free_size: u64 = selected.iter().map(ProjectTargetAnalysis::total_size).sum();
+    let ignored_free_size: u64 = ignored.iter().map(ProjectTargetAnalysis::total_size).sum();
+
+    outln!("{}", tr!("Ignoring the following project directories:"));
+    let width = output::terminal_width();
+    let cut_off_note = format!(" (cut off by --top {})", args.top.unwrap_or_default());
+    ignored.iter().for_each(|p| {
+        if cut_off_by_top.contains(&p.project_path) {
+            let width = width.map(|w| w.saturating_sub(cut_off_note.len()));
+            outln!("{}{}", p.listing_line(width), cut_off_note.dimmed());
+        } else {
+            outln!("{}", p.listing_line(width));
+        }
+    });
+
+    outln!(
+        "\n{}",
+        tr!("Selected the following project directories for cleaning:")
+    );
+    selected
+        .iter()
+        .for_each(|p| outln!("{}", p.listing_line(width)));
+
+    let summary = if partial_scan {
+        tr!(
+            "Selected {}/{} projects of a partial scan, cleaning will free: {}. Keeping: {}",
+            selected.len(),
+            selected.len() + ignored.len(),
+            i18n::format_size(will_free_size).bold(),
+            i18n::format_size(ignored_free_size)
+        )
+    } else {
+        tr!(
+            "Selected {}/{} projects, cleaning will free: {}. Keeping: {}",
+            selected.len(),
+            selected.len() + ignored.len(),
+            i18n::format_size(will_free_size).bold(),
+            i18n::format_size(ignored_free_size)
+        )
+    };
+    outln!("\n{}", summary);
+
+    let mut skipped_mounts = scan_stats.skipped_mounts.into_inner().unwrap();
+    skipped_mounts.sort();
+    skipped_mounts.dedup();
+    for mount in skipped_mounts {
+        outln!("Skipped the excluded mount {}", pretty_format_path(&mount));
+    }
+
+    let mut leftover_targets = scan_stats.leftover_targets.into_inner().unwrap();
+    leftover_targets.sort();
+    leftover_targets.dedup();
+    if !leftover_targets.is_empty() {
+        outln!("\nFound the leftovers of an interrupted deletion in the background:");
+        for leftover in &leftover_targets {
+            let (size, _, _) = ProjectTargetAnalysis::recursive_scan_target(leftover);
+            outln!(
+                "{}: {}",
+                pretty_format_path(leftover),
+                i18n::format_size(size)
+            );
+        }
+    }
+
+    if args.stats {
+        stats::print_stats(&selected.iter().chain(ignored.iter()).collect::<Vec<_>>());
+    }
+
+    if let Some(group_by) = args.group_by {
+        stats::print_groups(&selected, &ignored, group_by);
+    }
+
+    if !profile_dirs.is_empty() {
+        stats::print_profile_sizes(&selected, &profile_dirs);
+    }
+
+    if let Some(n) = args.largest_files {
+        stats::print_largest_files(
+            &selected.iter().chain(ignored.iter()).collect::<Vec<_>>(),
+            n,
+        );
+    }
+
+    if args.cache_stats {
+        cargo_cache::print_cache_stats(will_free_size + ignored_free_size);
+    }
+
+    if args.all_users {
+        print_user_totals(&selected, &ignored);
+    } else if scan_paths.len() > 1 && !args.skip_scan() {
+        print_root_totals(&selected, &ignored, &scan_paths, &root_stats);
+    }
+
+    print_disk_totals(
+        &selected,
+        &ignored,
+        &mounts,
+        in_container,
+        args.clean_overlay,
+    );
+
+    if is_wsl && !args.windows_native_delete {
+        let on_windows_drives = selected
+            .iter()
+            .filter(|p| {
+                mounts::mount_of(&mounts, &p.target_path).is_some_and(wsl::is_windows_drive)
+            })
+            .count();
+        if on_windows_drives > 0 {
+            outln!(
+                "{} of the selected projects are on Windows drives, which are slow to delete \
+                 through the WSL filesystem bridge. Use --windows-native-delete to delete them \
+                 using Windows instead",
+                on_windows_drives
+            );
+        }
+    }
+
+    if let Some(max_free) = args.max_free.filter(|&max_free| will_free_size > max_free) {
+        outln!(
+            "{}",
+            tr!(
+                "The cleanup stops once {} are freed (--max-free), so not all selected projects may be cleaned",
+                i18n::format_size(max_free)
+            )
+        );
+    }
+
+    let total_projects = selected.len() + ignored.len();
+    let json_report = args
+        .json
+        .then(|| json::Report::new(partial_scan, &selected, &ignored, args.group_by));
+
+    // Every scan is recorded, the result of the cleanup is added to it afterward
+    #[cfg(feature = "db")]
+    let mut db = args.db.as_ref().and_then(|path| {
+        db::Db::record_scan(
+            path,
+            raw_args,
+            args.dry_run,
+            partial_scan,
+            &selected,
+            &ignored,
+        )
+        .map_err(|e| eprintln!("Failed to record the scan in the database: {}", e))
+        .ok()
+    });
+
+    if let RunMode::Collect(found) = mode {
+        found.extend(selected.into_iter().map(|p| (p, true)));
+        found.extend(ignored.into_iter().map(|p| (p, false)));
+        return;
+    }
+
+    if let RunMode::Serve(port) = mode {
+        if let Err(e) = serve::serve(&args, raw_args, selected, ignored, port) {
+            eprintln!("Failed to serve the dashboard: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let RunMode::Plan(plan_path) = mode {
+        // Only the arguments after the subcommand are saved in the plan
+        if let Err(e) = plan::write_plan(plan_path, &raw_args[1..], &selected) {
+            eprintln!("Failed to write the plan: {}", e);
+            std::process::exit(1);
+        }
+        outln!(
+            "Saved the plan for {} projects to {}",
+            selected.len(),
+            plan_path.display()
+        );
+        return;
+    }
+
+    if args.dry_run {
+        outln!(
+            "{}",
+            tr!(
+                "Scanning took {}, analysis took {}",
+                format!("{:.2?}", scan_duration),
+                format!("{:.2?}", analysis_duration)
+            )
+        );
+        if args.cargo_gc {
+            cargo_cache::report_cargo_gc(true);
+        }
+        outln!("{}", tr!("Dry run. Not doing any cleanup"));
+        if args.oneline {
+            println!(
+                "dry run, selected {}/{} projects, cleaning would free {}",
+                selected.len(),
+                total_projects,
+                bytefmt::format(will_free_size)
+            );
+        }
+        if let Some(report) = json_report {
+            report.status(json::Status::DryRun).print();
+        }
+        return;
+    }
+
+    // Confirm cleanup if --yes is not present in the args. When confirming each project, the
+    // confirmation happens during the cleanup instead, and the TUI already confirmed the cleanup
+    if !args.yes
+        && !args.confirm_each
+        && !args.uses_tui()
+        && !dialoguer::Confirm::new()
+            .with_prompt(tr!("Clean the project directories shown above?"))
+            .wait_for_newline(true)
+            .interact()
+            .unwrap_or(false)
+    {
+        outln!("{}", tr!("Cleanup cancelled"));
+        if args.oneline {
+            println!(
+                "cleanup cancelled, selected {}/{} projects",
+                selected.len(),
+                total_projects
+            );
+        }
+        if let Some(report) = json_report {
+            report.status(json::Status::Cancelled).print();
+        }
+        return;
+    }
+
+    outln!("{}", tr!("Starting cleanup..."));
+
+    let mut journal = journal::Journal::start(raw_args, &selected);
+    let mut result = cleanup::cleanup(&args, &selected, journal.as_mut());
+    if let Some(journal) = journal {
+        journal.finish();
+    }
+    outln!();
+
+    // The leftovers are not part of the listed projects, so they are confirmed separately
+    if !leftover_targets.is_empty()
+        && !args.deadline_passed()
+        && (args.yes
+            || dialoguer::Confirm::new()
+                .with_prompt("Delete the leftovers of the interrupted deletion in the background?")
+                .wait_for_newline(true)
+                .interact()
+                .unwrap_or(false))
+    {
+        let mut leftovers = cleanup::LeftoverDeletion::default();
+        for leftover in &leftover_targets {
+            leftovers.delete(leftover, args.fix_permissions);
+        }
+        leftovers.add_to(&mut result);
+    }
+
+    if args.cargo_gc && !args.deadline_passed() {
+        let gc_start = Instant::now();
+        if let Some(summary) = cargo_cache::report_cargo_gc(false) {
+            result.freed_size += summary.size;
+            result.deleted_files += summary.file_count;
+            result.deletion_duration += gc_start.elapsed();
+        }
+    }
+
+    #[cfg(feature = "db")]
+    if let Some(db) = &mut db {
+        if let Err(e) = db.record_cleanup(&result) {
+            eprintln!("Failed to record the cleanup in the database: {}", e);
+        }
+    }
+
+    print_cleanup_result(&result);
+    print_disk_reclaimed(&result, &mounts);
+    if args.moves_to_trash() && result.cleaned > 0 {
+        outln!(
+            "{}",
+            tr!(
+                "The targets were moved to the trash, the space is freed when the trash is emptied"
+            )
+        );
+    }
+
+    let deletion_secs = result.deletion_duration.as_secs_f64().max(f64::EPSILON);
+    outln!(
+        "{}",
+        tr!(
+            "Scanning took {}, analysis took {}, deletion took {} ({} files/s, {}/s)",
+            format!("{:.2?}", scan_duration),
+            format!("{:.2?}", analysis_duration),
+            format!("{:.2?}", result.deletion_duration),
+            format!("{:.0}", result.deleted_files as f64 / deletion_secs),
+            i18n::format_size((result.freed_size as f64 / deletion_secs) as u64)
+        )
+    );
+
+    if args.oneline {
+        println!(
+            "cleaned {}/{} projects, freed {}, {} failure{}{}",
+            result.cleaned,
+            total_projects,
+            bytefmt::format(result.freed_size),
+            result.failed.len(),
+            if result.failed.len() == 1 { "" } else { "s" },
+            if result.timed_out > 0 {
+                ", time limit reached"
+            } else {
+                ""
+            }
+        );
+    }
+    if let Some(report) = json_report {
+        report.with_cleanup(&result).print();
+    }
+
+    if args.exclude_from_backup {
+        for tgt in ignored.iter() {
+            let target_dir = &tgt.target_path;
+            if !target_dir.is_dir() {
+                continue;
+            }
+            match backup_exclusion::exclude_from_backup(target_dir) {
+                Ok(()) => (),
+                Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+                    eprintln!("Not excluding kept targets from backups: {}", e);
+                    break;
+                }
+                Err(e) => eprintln!(
+                    "Failed to exclude {} from backups: {}",
+                    pretty_format_path(target_dir),
+                    e
+                ),
+            }
+        }
+    }
+}
+
+/// Let the user select the projects in the TUI or the interactive selection. Returns `None` if the
+/// selection was cancelled
+#[cfg_attr(not(feature = "tui"), allow(unused_variables))]
+fn select_interactively(
+    args: &AppArgs,
+    prompt: &str,
+    projects: &[ProjectTargetAnalysis],
+    preselected: &[bool],
+) -> io::Result<Option<selector::Selection>> {
+    #[cfg(feature = "tui")]
+    if args.tui {
+        return tui::Tui::new(prompt, projects, preselected, args).interact();
+    }
+    selector::Selector::new(prompt, projects, preselected).interact()
+}
+
+/// Print the failures and the reclaimed space of the cleanup
+fn print_cleanup_result(result: &cleanup::CleanupResult) {
+    for (tgt, e) in result.failed.iter() {
+        outln!(
+            "{}",
+            tr!("Failed to clean {}", pretty_format_path(&tgt.project_path))
+        );
+        outln!("{}", tr!("Error: {}", e));
+    }
+
+    if result.skipped > 0 {
+        outln!(
+            "{}",
+            tr!("Skipped {} of the selected projects", result.skipped)
+        );
+    }
 
-    projects.sort_by_key(|proj| proj.size);
+    if result.max_free_kept > 0 {
+        outln!(
+            "{}",
+            tr!(
+                "Not cleaning {} more projects, since the cleaned ones already freed up the --max-free limit",
+                result.max_free_kept
+            )
+        );
+    }
 
-    // Determin what projects are selected by the restrictions
-    let preselected_projects = projects
-        .iter_mut()
-        .map(|tgt| {
-            let secs_elapsed = tgt
-                .last_modified
-                .elapsed()
-                .unwrap_or_default()
-                .as_secs_f32();
-            let days_elapsed = secs_elapsed / (60.0 * 60.0 * 24.0);
-            let ignored = args
-                .ignore
-                .iter()
-                .any(|p| starts_with_canonicalized(&tgt.project_path, p));
+    if result.timed_out > 0 {
+        outln!(
+            "{}",
+            tr!(
+                "The time limit was reached, {} of the selected projects were not cleaned",
+                result.timed_out
+            )
+        );
+    }
 
-            days_elapsed >= args.keep_last_modified as f32 && tgt.size > args.keep_size && !ignored
-        })
-        .collect::<Vec<_>>();
+    outln!(
+        "\n{}",
+        tr!(
+            "Projects cleaned. Reclaimed {} of disk space",
+            i18n::format_size(result.freed_size).bold()
+        )
+    );
+}
 
-    if args.interactive {
-        let Ok(Some(prompt)) = dialoguer::MultiSelect::new()
-            .items(&projects)
-            .with_prompt("Select projects to clean")
-            .report(false)
-            .defaults(&preselected_projects)
-            .interact_opt()
-        else {
-            println!("Nothing selected");
-            return;
-        };
+/// The mount the target of the project is on. Mounts are grouped into disks by their device, so
+/// that bind mounts of the same disk are counted together
+fn disk_of<'a>(mounts: &'a [mounts::Mount], target_path: &Path) -> Option<&'a mounts::Mount> {
+    mounts::mount_of(mounts, target_path)
+}
 
-        for idx in prompt {
-            projects[idx].selected_for_cleanup = true;
+/// Print the reclaimable and selected size of the projects grouped by the disk their target
+/// directory is on, together with the free space of the disk. This is shown if the targets are on
+/// multiple disks, since freeing space only helps on the disk that is full, and inside of
+/// containers to distinguish mounted volumes from the container filesystem
+fn print_disk_totals(
+    selected: &[ProjectTargetAnalysis],
+    ignored: &[ProjectTargetAnalysis],
+    mounts: &[mounts::Mount],
+    in_container: bool,
+    clean_overlay: bool,
+) {
+    // (mount, number of projects, reclaimable size, selected size) in order of appearance
+    let mut totals: Vec<(Option<&mounts::Mount>, usize, u64, u64)> = Vec::new();
+    for (proj, is_selected) in selected
+        .iter()
+        .map(|p| (p, true))
+        .chain(ignored.iter().map(|p| (p, false)))
+    {
+        let mount = disk_of(mounts, &proj.target_path);
+        let idx = match totals
+            .iter()
+            .position(|t| t.0.map(|m| m.disk_id()) == mount.map(|m| m.disk_id()))
+        {
+            Some(idx) => idx,
+            None => {
+                totals.push((mount, 0, 0, 0));
+                totals.len() - 1
+            }
+        };
+        totals[idx].1 += 1;
+        totals[idx].2 += proj.total_size();
+        if is_selected {
+            totals[idx].3 += proj.total_size();
         }
+    }
+
+    if !in_container && totals.len() < 2 {
+        return;
+    }
+
+    if in_container {
+        outln!(
+            "\n{}",
+            tr!("Running in a container, target directories per mount:")
+        );
     } else {
-        for i in 0..preselected_projects.len() {
-            projects[i].selected_for_cleanup = preselected_projects[i];
+        outln!("\n{}", tr!("Target directories per disk:"));
+    }
+    for (mount, count, reclaimable, selected_size) in totals {
+        let name = mount.map_or_else(|| tr!("unknown mount"), |m| m.describe());
+        let free = mount
+            .and_then(|m| mounts::free_space(&m.mount_point))
+            .map(|free| tr!(", free: {}", i18n::format_size(free)))
+            .unwrap_or_default();
+        let note = if mount.is_some_and(mounts::Mount::is_overlay) && !clean_overlay {
+            tr!(" (container filesystem, kept unless --clean-overlay is used)")
+        } else {
+            String::new()
+        };
+        outln!(
+            "{}",
+            tr!(
+                "{}: {} in {} projects, selected: {}{}{}",
+                name.bold(),
+                i18n::format_size(reclaimable),
+                count,
+                i18n::format_size(selected_size),
+                free,
+                note.dimmed()
+            )
+        );
+    }
+}
+
+/// Print the reclaimed space per disk after the cleanup, together with the free space the disk
+/// has now, if the cleaned targets are on multiple disks
+fn print_disk_reclaimed(result: &cleanup::CleanupResult, mounts: &[mounts::Mount]) {
+    // (mount, reclaimed size) in order of appearance
+    let mut totals: Vec<(Option<&mounts::Mount>, u64)> = Vec::new();
+    for (_, target_path, freed) in &result.freed_per_project {
+        let mount = disk_of(mounts, target_path);
+        match totals
+            .iter_mut()
+            .find(|t| t.0.map(|m| m.disk_id()) == mount.map(|m| m.disk_id()))
+        {
+            Some(total) => total.1 += freed,
+            None => totals.push((mount, *freed)),
         }
     }
+    if totals.len() < 2 {
+        return;
+    }
 
-    let (selected, ignored): (Vec<_>, Vec<_>) = projects
-        .into_iter()
-        .partition(|proj| proj.selected_for_cleanup);
+    outln!("{}", tr!("Reclaimed per disk:"));
+    for (mount, freed) in totals {
+        let name = mount.map_or_else(|| tr!("unknown mount"), |m| m.describe());
+        let free = mount
+            .and_then(|m| mounts::free_space(&m.mount_point))
+            .map(|free| tr!(", free now: {}", i18n::format_size(free)))
+            .unwrap_or_default();
+        outln!("{}: {}{}", name.bold(), i18n::format_size(freed), free);
+    }
+}
 
-    let will_free_size: u64 = selected.iter().map(|it| it.size).sum();
-    let ignored_free_size: u64 = ignored.iter().map(|it| it.size).sum();
+/// A directory that is scanned for projects, together with the user that owns it when scanning the
+/// home directories of all users
+struct ScanRoot {
+    path: PathBuf,
+    user: Option<String>,
+}
 
-    println!("Ignoring the following project directories:");
-    ignored.iter().for_each(|p| println!("{}", p));
+/// Determine the directories to scan, which are either the specified directory, the home
+/// directories of all users or the listed projects
+fn scan_roots(args: &AppArgs) -> Result<Vec<ScanRoot>, String> {
+    if let Some(list) = &args.projects_from {
+        return read_project_list(list)
+            .map_err(|e| format!("Failed to read the project list: {}", e))
+            .map(|projects| {
+                projects
+                    .into_iter()
+                    .map(|path| ScanRoot { path, user: None })
+                    .collect()
+            });
+    }
 
-    println!("\nSelected the following project directories for cleaning:");
-    selected.iter().for_each(|p| println!("{}", p));
+    if let Some(dir) = args
+        .root_dirs
+        .iter()
+        .find(|dir| args.no_scan && !Path::new(dir).join("Cargo.toml").is_file())
+    {
+        return Err(format!(
+            "{} is not a cargo project, since it doesn't contain a Cargo.toml",
+            dir
+        ));
+    }
 
-    println!(
-        "\nSelected {}/{} projects, cleaning will free: {}. Keeping: {}",
-        selected.len(),
-        selected.len() + ignored.len(),
-        bytefmt::format(will_free_size).bold(),
-        bytefmt::format(ignored_free_size)
-    );
+    if !args.all_users {
+        return Ok(args
+            .root_dirs
+            .iter()
+            .map(|dir| ScanRoot {
+                path: PathBuf::from(dir),
+                user: None,
+            })
+            .collect());
+    }
 
-    if args.dry_run {
-        println!("Dry run. Not doing any cleanup");
-        return;
+    if !privileges::is_elevated() {
+        return Err(
+            "Scanning the projects of all users requires root or administrator privileges"
+                .to_string(),
+        );
     }
 
-    // Confirm cleanup if --yes is not present in the args
-    if !args.yes {
-        if !dialoguer::Confirm::new()
-            .with_prompt("Clean the project directories shown above?")
-            .wait_for_newline(true)
-            .interact()
-            .unwrap_or(false)
-        {
-            println!("Cleanup cancelled");
-            return;
-        }
+    let homes = privileges::user_home_dirs()
+        .map_err(|e| format!("Failed to list the home directories: {}", e))?;
+
+    if let Some(unknown) = args
+        .users
+        .iter()
+        .find(|user| !homes.iter().any(|(name, _)| name == *user))
+    {
+        return Err(format!("No home directory found for user '{}'", unknown));
     }
 
-    println!("Starting cleanup...");
+    Ok(homes
+        .into_iter()
+        .map(|(user, path)| ScanRoot {
+            path,
+            user: Some(user),
+        })
+        .collect())
+}
 
-    // Saves the executables in another folder before cleaning the target folder
-    if args.executable {
-        for project in selected.iter() {
-            let project_target_path = &project.project_path.join("target");
-            let project_executables_path = project.project_path.join("executables");
+/// Whether the project has work that is not committed or pushed yet, which is reported when verbose
+fn is_dirty(tgt: &ProjectTargetAnalysis, args: &AppArgs) -> bool {
+    if tgt.shared_target {
+        return false;
+    }
+    let (dirty, reason) = match git::has_unsaved_work(&tgt.project_path) {
+        Some(dirty) => (dirty, "it has uncommitted or unpushed changes"),
+        None => (args.keep_without_git, "it is not in a git repository"),
+    };
+    if dirty && args.verbose {
+        outln!(
+            "Keeping {}, since {}",
+            pretty_format_path(&tgt.project_path),
+            reason
+        );
+    }
+    dirty
+}
 
-            let target_rd = match project_target_path.read_dir() {
-                Ok(it) => it,
-                Err(e) => {
-                    args.verbose
-                        .then(|| eprintln!("Error reading target dir of: '{}'  {}", project, e));
-                    continue;
-                }
-            };
+/// The names a project can be matched by, which are the directory name and the package name
+fn project_names(tgt: &ProjectTargetAnalysis) -> Vec<String> {
+    let mut names = vec![tgt
+        .project_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()];
+    if let Ok(manifest) = manifest::Manifest::read(&tgt.project_path) {
+        names.extend(manifest.package_name_version().0.map(str::to_string));
+    }
+    names
+}
 
-            let target_rd = target_rd
-                .filter_map(|it| it.ok())
-                .filter_map(|it| it.file_type().is_ok_and(|t| t.is_dir()).then(|| it.path()));
+/// Whether the git repository of the project got a commit in the last number of days
+fn is_recently_committed(tgt: &ProjectTargetAnalysis, days: u32) -> bool {
+    let since = SystemTime::now() - Duration::from_secs(u64::from(days) * 60 * 60 * 24);
+    !tgt.shared_target
+        && git::last_commit_time(&tgt.project_path).is_some_and(|committed| committed >= since)
+}
 
-            for target_subdir in target_rd {
-                let files = match target_subdir.read_dir() {
-                    Ok(it) => it,
-                    Err(e) => {
-                        args.verbose.then(|| {
-                            eprintln!("Error reading target dir of: '{}'  {}", project, e)
-                        });
-                        continue;
-                    }
-                };
+/// Whether cargo is building the project right now, in which case it is kept and reported
+fn is_being_built(tgt: &ProjectTargetAnalysis) -> bool {
+    let locked = build_lock::is_locked(&tgt.target_path);
+    if locked {
+        outln!(
+            "Keeping {}, since it is being built right now. Use --force-locked to clean it anyway",
+            pretty_format_path(&tgt.project_path)
+        );
+    }
+    locked
+}
 
-                let files = files
-                    .filter_map(|it| it.ok())
-                    .filter_map(|it| it.file_type().is_ok_and(|t| t.is_file()).then(|| it.path()));
+/// List the projects whose cargo config redirects them to the same target directory only once,
+/// like the members of a workspace. The target belongs to the project that contains all of the
+/// others, otherwise it is listed as shared target directory
+fn merge_shared_targets(mut projects: Vec<ProjectDir>) -> Vec<ProjectDir> {
+    // The parent directories come first, which makes them the owners of the targets
+    projects.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut merged: Vec<ProjectDir> = Vec::new();
+    let mut owners: HashMap<PathBuf, usize> = HashMap::new();
+    for proj in projects {
+        let Some(target) = proj.1.as_ref().map(|a| canonicalize_or_not(&a.target_path)) else {
+            continue;
+        };
+        let Some(&owner) = owners.get(&target) else {
+            owners.insert(target, merged.len());
+            merged.push(proj);
+            continue;
+        };
+        let owner = &mut merged[owner];
+        if !proj.0.starts_with(&owner.0) {
+            if let Some(analysis) = &mut owner.1 {
+                analysis.project_path = target.clone();
+                analysis.shared_target = true;
+            }
+            owner.0 = target;
+        }
+    }
+    merged
+}
 
-                for exe_file_path in files.filter(|file| is_executable(file)) {
-                    let new_exe_file_path = project_executables_path
-                        .join(target_subdir.file_name().expect("Path Error"))
-                        .join(exe_file_path.file_name().expect("Path Error"));
+/// The shared target directory that `CARGO_TARGET_DIR` points all projects to, if it exists and
+/// is inside of one of the scan roots. A relative path is resolved against the working directory,
+/// like cargo does
+fn shared_target_dir(scan_paths: &[PathBuf]) -> Option<PathBuf> {
+    // CARGO_BUILD_TARGET_DIR is the environment variable of `build.target-dir` in the config
+    let dir = ["CARGO_TARGET_DIR", "CARGO_BUILD_TARGET_DIR"]
+        .iter()
+        .find_map(|var| std::env::var_os(var).filter(|dir| !dir.is_empty()))?;
+    let dir = canonicalize_or_not(std::path::absolute(dir).ok()?);
+    (dir.is_dir()
+        && scan_paths
+            .iter()
+            .any(|root| starts_with_canonicalized(&dir, root)))
+    .then_some(dir)
+}
 
-                    if let Err(e) =
-                        std::fs::create_dir_all(new_exe_file_path.parent().expect("Path Error"))
-                    {
-                        eprintln!(
-                            "Error createing executable dir: '{}'  {}",
-                            new_exe_file_path.parent().expect("Path Error").display(),
-                            e
-                        );
-                        continue;
-                    }
+/// The temporary directories of the system that are not already inside of one of the scan roots
+fn temp_scan_roots(roots: &[ScanRoot]) -> Vec<ScanRoot> {
+    let mut temp_dirs = vec![std::env::temp_dir()];
+    if cfg!(unix) {
+        temp_dirs.push(PathBuf::from("/var/tmp"));
+    }
 
-                    if let Err(e) = std::fs::rename(exe_file_path, &new_exe_file_path) {
-                        eprintln!(
-                            "Error moving executable: '{}'  {}",
-                            new_exe_file_path.display(),
-                            e
-                        );
-                        continue;
-                    }
-                }
-            }
+    let mut temp_roots: Vec<ScanRoot> = Vec::new();
+    for dir in temp_dirs.into_iter().filter(|dir| dir.is_dir()) {
+        let already_scanned = roots
+            .iter()
+            .chain(temp_roots.iter())
+            .any(|root| starts_with_canonicalized(&dir, &root.path));
+        if !already_scanned {
+            temp_roots.push(ScanRoot {
+                path: dir,
+                user: None,
+            });
         }
     }
+    temp_roots
+}
 
-    let clean_progress = ProgressBar::new(selected.len() as u64).with_style(
-        ProgressStyle::with_template("[{elapsed}] [{bar:}] {pos}/{len}: {msg}")
-            .expect("Invalid template syntax")
-            .progress_chars("#>-"),
-    );
+/// Read the project directories from the file or stdin for `-`. Entries that are not cargo projects
+/// are skipped with a warning.
+fn read_project_list(list: &Path) -> io::Result<Vec<PathBuf>> {
+    let content = if list == Path::new("-") {
+        let mut content = String::new();
+        io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(list)?
+    };
 
-    let failed_cleanups = selected.iter().filter_map(|tgt| {
-        clean_progress.set_message(format!("{}", tgt.project_path.display()));
-        let res = remove_dir_all::remove_dir_all(&tgt.project_path.join("target"))
-            .err()
-            .map(|e| (tgt.clone(), e));
-        clean_progress.inc(1);
-        res
-    });
+    let separator = if content.contains('\0') { '\0' } else { '\n' };
+    let mut projects: Vec<PathBuf> = Vec::new();
+    for entry in content
+        .split(separator)
+        .map(|e| e.trim_end_matches('\r'))
+        .filter(|e| !e.is_empty())
+    {
+        let mut path = PathBuf::from(entry);
+        if path.file_name().is_some_and(|name| name == "Cargo.toml") {
+            path.pop();
+        }
+        if !path.join("Cargo.toml").is_file() {
+            eprintln!("Skipping {}, it is not a cargo project", entry);
+            continue;
+        }
+        if !projects.contains(&path) {
+            projects.push(path);
+        }
+    }
+    Ok(projects)
+}
 
-    clean_progress.finish_and_clear();
-    println!("");
+/// Print the reclaimable and selected size of the projects of each user
+fn print_user_totals(selected: &[ProjectTargetAnalysis], ignored: &[ProjectTargetAnalysis]) {
+    // (number of projects, reclaimable size, selected size) per user
+    let mut totals: HashMap<&str, (usize, u64, u64)> = HashMap::new();
+    for (proj, is_selected) in selected
+        .iter()
+        .map(|p| (p, true))
+        .chain(ignored.iter().map(|p| (p, false)))
+    {
+        let total = totals
+            .entry(proj.owner.as_deref().unwrap_or("unknown"))
+            .or_default();
+        total.0 += 1;
+        total.1 += proj.total_size();
+        if is_selected {
+            total.2 += proj.total_size();
+        }
+    }
 
-    // The current leftover size calculation assumes that a failed deletion didn't delete anything.
-    // This will not be true in most cases as a recursive deletion might delet stuff before failing.
-    let mut leftover_size = 0;
-    for (tgt, e) in failed_cleanups {
-        leftover_size += tgt.size;
-        println!("Failed to clean {}", pretty_format_path(&tgt.project_path));
-        println!("Error: {}", e);
+    let mut totals: Vec<_> = totals.into_iter().collect();
+    totals.sort_by(|a, b| b.1 .1.cmp(&a.1 .1).then(a.0.cmp(b.0)));
+
+    outln!("\n{}", tr!("Reclaimable space per user:"));
+    for (user, (count, reclaimable, selected_size)) in totals {
+        outln!(
+            "{}",
+            tr!(
+                "{}: {} in {} projects, selected: {}",
+                user.bold(),
+                i18n::format_size(reclaimable),
+                count,
+                i18n::format_size(selected_size)
+            )
+        );
     }
+}
 
-    println!(
-        "\nProjects cleaned. Reclaimed {} of disk space",
-        bytefmt::format(will_free_size - leftover_size).bold()
-    );
+/// Print the reclaimable and selected size of the projects per scanned directory, together with
+/// the time it took to scan the directory
+fn print_root_totals(
+    selected: &[ProjectTargetAnalysis],
+    ignored: &[ProjectTargetAnalysis],
+    roots: &[PathBuf],
+    stats: &[ScanStats],
+) {
+    // (number of projects, reclaimable size, selected size) per root
+    let mut totals = vec![(0, 0, 0); roots.len()];
+    for (proj, is_selected) in selected
+        .iter()
+        .map(|p| (p, true))
+        .chain(ignored.iter().map(|p| (p, false)))
+    {
+        let Some(idx) = roots
+            .iter()
+            .position(|root| starts_with_canonicalized(&proj.project_path, root))
+        else {
+            continue;
+        };
+        totals[idx].0 += 1;
+        totals[idx].1 += proj.total_size();
+        if is_selected {
+            totals[idx].2 += proj.total_size();
+        }
+    }
+
+    outln!("\n{}", tr!("Reclaimable space per scanned directory:"));
+    for ((root, (count, reclaimable, selected_size)), stats) in roots.iter().zip(totals).zip(stats)
+    {
+        let duration = stats
+            .duration
+            .lock()
+            .unwrap()
+            .map(|duration| tr!(", scanned in {}", format!("{:.2?}", duration)))
+            .unwrap_or_default();
+        outln!(
+            "{}",
+            tr!(
+                "{}: {} in {} projects, selected: {}{}",
+                pretty_format_path(root).bold(),
+                i18n::format_size(reclaimable),
+                count,
+                i18n::format_size(selected_size),
+                duration
+            )
+        );
+    }
 }
 
 /// Job for the threaded project finder. First the path to be searched, second the sender to create
@@ -382,28 +2627,218 @@ struct Job {
     path: PathBuf,
     sender: Sender<Job>,
     depth: Option<usize>,
+    /// The ignore files of the parent directories, the innermost one last
+    ignore_files: Arc<Vec<Gitignore>>,
+    /// Whether the path is inside of a git repository, since `.gitignore` files are only
+    /// respected there
+    in_git_repo: bool,
 }
 
 impl Job {
     pub fn new(path: PathBuf, sender: Sender<Job>, depth: Option<usize>) -> Self {
+        let in_git_repo = canonicalize_or_not(&path)
+            .ancestors()
+            .any(|dir| dir.join(".git").exists());
         Self {
             path,
             sender,
             depth,
+            ignore_files: Arc::default(),
+            in_git_repo,
         }
     }
 
-    pub fn explore_recursive(&self, path: PathBuf) -> Result<(), SendError<Self>> {
+    pub fn explore_recursive(
+        &self,
+        path: PathBuf,
+        ignore_files: &Arc<Vec<Gitignore>>,
+        in_git_repo: bool,
+    ) -> Result<(), SendError<Self>> {
         self.sender.send(Job {
             path,
             sender: self.sender.clone(),
             depth: self.depth.map(|d| d - 1),
+            ignore_files: ignore_files.clone(),
+            in_git_repo,
         })
     }
 }
 
-/// Directory of the project and bool that is true if the target directory exists
-struct ProjectDir(PathBuf, bool);
+/// The ignore files in the order of their precedence like in ripgrep, where `.rgignore` takes
+/// precedence over `.ignore`, which takes precedence over `.gitignore`
+const IGNORE_FILE_NAMES: &[&str] = &[".gitignore", ".ignore", ".rgignore"];
+
+/// Add the ignore files in the directory to the ones of the parent directories. `.gitignore`
+/// files are only added if `use_gitignore` is set
+fn with_ignore_files(
+    dir: &Path,
+    files: &[fs::DirEntry],
+    parents: &Arc<Vec<Gitignore>>,
+    use_gitignore: bool,
+    pb: &ProgressBar,
+    verbose: bool,
+) -> Arc<Vec<Gitignore>> {
+    let mut builder = GitignoreBuilder::new(dir);
+    let mut found = false;
+    for name in IGNORE_FILE_NAMES
+        .iter()
+        .filter(|name| use_gitignore || **name != ".gitignore")
+    {
+        if files.iter().any(|it| it.file_name() == *name) {
+            if let Some(e) = builder.add(dir.join(name)) {
+                pb.suspend(|| verbose.then(|| eprintln!("Error reading ignore file: {}", e)));
+            }
+            found = true;
+        }
+    }
+    if !found {
+        return parents.clone();
+    }
+    match builder.build() {
+        Ok(ignore_file) => {
+            let mut ignore_files = parents.as_ref().clone();
+            ignore_files.push(ignore_file);
+            Arc::new(ignore_files)
+        }
+        Err(e) => {
+            pb.suspend(|| verbose.then(|| eprintln!("Error reading ignore file: {}", e)));
+            parents.clone()
+        }
+    }
+}
+
+/// Check if the directory is ignored by the innermost ignore file that has a matching pattern
+fn is_ignored_dir(dir: &Path, ignore_files: &[Gitignore]) -> bool {
+    ignore_files
+        .iter()
+        .rev()
+        .map(|ignore_file| ignore_file.matched(dir, true))
+        .find(|m| !m.is_none())
+        .is_some_and(|m| m.is_ignore())
+}
+
+/// The build and cache directories of other languages that are skipped by default, since they
+/// are large and don't contain cargo projects worth cleaning
+const DEFAULT_SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "bower_components",
+    ".venv",
+    "__pycache__",
+    ".mypy_cache",
+    ".pytest_cache",
+    ".tox",
+    ".gradle",
+    ".m2",
+    ".next",
+    ".nuxt",
+    ".terraform",
+    ".stack-work",
+    "_build",
+    "zig-cache",
+    ".zig-cache",
+];
+
+/// Directories that are only skipped by default if they contain the marker file, since the name
+/// alone is too common, like the `build` directory of CMake
+const DEFAULT_SKIPPED_MARKED_DIRS: &[(&str, &str)] = &[
+    ("build", "CMakeCache.txt"),
+    ("cmake-build-debug", "CMakeCache.txt"),
+    ("cmake-build-release", "CMakeCache.txt"),
+];
+
+/// Check if the directory should be skipped because of its name
+fn is_skipped_name(dir: &Path, name: &str, args: &AppArgs) -> bool {
+    if args.skip_names.iter().any(|skipped| skipped == name) {
+        return true;
+    }
+    !args.no_default_skips
+        && (DEFAULT_SKIPPED_DIRS.contains(&name)
+            || DEFAULT_SKIPPED_MARKED_DIRS
+                .iter()
+                .any(|(skipped, marker)| *skipped == name && dir.join(marker).exists()))
+}
+
+/// Directory of the project and the analysis of its target directory, if it exists
+struct ProjectDir(PathBuf, Option<ProjectTargetAnalysis>);
+
+/// Live counters of the project finder, shown while the scan is running
+#[derive(Default)]
+struct ScanStats {
+    dirs: AtomicU64,
+    projects: AtomicU64,
+    target_size: AtomicU64,
+    /// The mounts excluded by --exclude-mount that were encountered and skipped
+    skipped_mounts: Mutex<Vec<PathBuf>>,
+    /// The renamed targets of --background-delete that were left over by an interrupted cleanup
+    leftover_targets: Mutex<Vec<PathBuf>>,
+    /// The time from the start of the scan until the root was done
+    duration: Mutex<Option<Duration>>,
+    /// The directories that were visited, which is only tracked with --follow-symlinks to detect
+    /// symlink loops
+    visited: Mutex<HashSet<DirId>>,
+    /// The analyzed target directories that were redirected by the cargo config, since the members
+    /// of a workspace all share the target of the workspace
+    redirected_targets: Mutex<HashMap<PathBuf, ProjectTargetAnalysis>>,
+}
+
+/// Identifies a directory regardless of the path it is reached by, which is the device and inode
+/// on unix and the canonical path elsewhere
+#[derive(PartialEq, Eq, Hash)]
+enum DirId {
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Inode(u64, u64),
+    #[cfg_attr(unix, allow(dead_code))]
+    Path(PathBuf),
+}
+
+fn dir_id(path: &Path) -> Option<DirId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let md = fs::metadata(path).ok()?;
+        Some(DirId::Inode(md.dev(), md.ino()))
+    }
+    #[cfg(not(unix))]
+    {
+        fs::canonicalize(path).ok().map(DirId::Path)
+    }
+}
+
+impl ScanStats {
+    /// The combined counters of the scans of multiple roots
+    fn sum(stats: &[ScanStats]) -> ScanStats {
+        let total = ScanStats::default();
+        for s in stats {
+            for (sum, counter) in [
+                (&total.dirs, &s.dirs),
+                (&total.projects, &s.projects),
+                (&total.target_size, &s.target_size),
+            ] {
+                sum.fetch_add(counter.load(Ordering::Relaxed), Ordering::Relaxed);
+            }
+            total
+                .skipped_mounts
+                .lock()
+                .unwrap()
+                .extend_from_slice(&s.skipped_mounts.lock().unwrap());
+            total
+                .leftover_targets
+                .lock()
+                .unwrap()
+                .extend_from_slice(&s.leftover_targets.lock().unwrap());
+        }
+        total
+    }
+
+    fn summary(&self) -> String {
+        tr!(
+            "{} directories, {} projects, {} in target directories",
+            self.dirs.load(Ordering::Relaxed),
+            self.projects.load(Ordering::Relaxed),
+            i18n::format_size(self.target_size.load(Ordering::Relaxed))
+        )
+    }
+}
 
 fn progress_bar(multi_progress: &MultiProgress, spinner_style: ProgressStyle) -> ProgressBar {
     let pb = multi_progress.add(ProgressBar::new(u64::MAX)); // unbounded
@@ -411,19 +2846,28 @@ fn progress_bar(multi_progress: &MultiProgress, spinner_style: ProgressStyle) ->
     pb
 }
 
-/// Recursively scan the given path for cargo projects using the specified number of threads.
+/// Recursively scan the given paths for cargo projects using the specified number of threads.
 ///
 /// When the number of threads is 0, use as many threads as virtual CPU cores.
 fn find_cargo_projects(
-    path: &Path,
+    paths: &[PathBuf],
     multi_progress: &MultiProgress,
     mut num_threads: usize,
     args: &AppArgs,
+    stats: &ScanStats,
 ) -> impl Iterator<Item = ProjectDir> {
     if num_threads == 0 {
         num_threads = num_cpus::get();
     }
-    let depth = (args.depth > 0).then(|| args.depth);
+    let depth = (args.depth > 0).then_some(args.depth);
+    let excluded_mounts: Vec<_> = args
+        .exclude_mounts
+        .iter()
+        .map(canonicalize_or_not)
+        .collect();
+    let excluded_mounts = &excluded_mounts;
+    let cargo_home = cargo_cache::cargo_home().map(canonicalize_or_not);
+    let cargo_home = cargo_home.as_deref();
 
     thread::scope(|scope| {
         {
@@ -438,16 +2882,26 @@ fn find_cargo_projects(
                             .expect("Invalid template syntax");
                         let pb = progress_bar(multi_progress, spinner_style.clone());
                         job_rx.into_iter().for_each(|job| {
-                            find_cargo_projects_task(job, &pb, result_tx.clone(), &args)
+                            find_cargo_projects_task(
+                                job,
+                                &pb,
+                                result_tx.clone(),
+                                args,
+                                excluded_mounts,
+                                cargo_home,
+                                stats,
+                            )
                         });
                         pb.finish_with_message("waiting...");
                     });
                 });
 
-            job_tx
-                .clone()
-                .send(Job::new(path.to_path_buf(), job_tx, depth))
-                .unwrap();
+            for path in paths {
+                job_tx
+                    .send(Job::new(path.clone(), job_tx.clone(), depth))
+                    .unwrap();
+            }
+            drop(job_tx);
 
             result_rx
         }
@@ -457,6 +2911,8 @@ fn find_cargo_projects(
 
 /// Scan the given directory and report to the results Sender if the directory contains a
 /// Cargo.toml . Detected subdirectories should be queued as a new job in with the job_sender.
+/// The target directories of found projects are analyzed right away, so that their size can be
+/// shown while the scan is still running.
 ///
 /// This function is supposed to be called by the threadpool in find_cargo_projects
 fn find_cargo_projects_task(
@@ -464,8 +2920,11 @@ fn find_cargo_projects_task(
     pb: &ProgressBar,
     results: Sender<ProjectDir>,
     args: &AppArgs,
+    excluded_mounts: &[PathBuf],
+    cargo_home: Option<&Path>,
+    stats: &ScanStats,
 ) {
-    if let Some(0) = job.depth {
+    if job.depth == Some(0) || SCAN_STOPPED.load(Ordering::Relaxed) {
         return;
     }
     let mut has_target = false;
@@ -484,18 +2943,63 @@ fn find_cargo_projects_task(
             return;
         }
     };
-    let (dirs, files): (Vec<_>, Vec<_>) = read_dir
-        .filter_map(|it| it.ok())
-        .partition(|it| it.file_type().is_ok_and(|t| t.is_dir()));
+    if args.follow_symlinks {
+        // A directory is reached again through a symlink loop or multiple links to it
+        if dir_id(&job.path).is_some_and(|id| !stats.visited.lock().unwrap().insert(id)) {
+            return;
+        }
+    }
+    stats.dirs.fetch_add(1, Ordering::Relaxed);
+    let (dirs, files): (Vec<_>, Vec<_>) = read_dir.filter_map(|it| it.ok()).partition(|it| {
+        it.file_type().is_ok_and(|t| {
+            t.is_dir() || (t.is_symlink() && args.follow_symlinks && it.path().is_dir())
+        })
+    });
     let dirs = dirs.iter().map(|it| it.path());
     let has_cargo_toml = files
         .iter()
         .any(|it| it.file_name().to_string_lossy() == "Cargo.toml");
+    // The target directory can be redirected by `build.target-dir` in the cargo config
+    let configured_target = has_cargo_toml
+        .then(|| cargo_config::target_dir(&job.path))
+        .flatten();
+    // Worktrees and submodules have a .git file instead of a directory
+    let in_git_repo = job.in_git_repo
+        || files.iter().any(|it| it.file_name() == ".git")
+        || dirs.clone().any(|it| it.ends_with(".git"));
+    let ignore_files = if args.no_ignore_files {
+        job.ignore_files.clone()
+    } else {
+        let use_gitignore = in_git_repo && !args.no_gitignore;
+        with_ignore_files(
+            &job.path,
+            &files,
+            &job.ignore_files,
+            use_gitignore,
+            pb,
+            args.verbose,
+        )
+    };
     // Iterate through the subdirectories of path, ignoring entries that caused errors
     for it in dirs {
         if args.skip.iter().any(|p| starts_with_canonicalized(&it, p)) {
             continue;
         }
+        if !excluded_mounts.is_empty() {
+            let canonical = canonicalize_or_not(&it);
+            if excluded_mounts.contains(&canonical) {
+                stats.skipped_mounts.lock().unwrap().push(canonical);
+                continue;
+            }
+        }
+
+        // The cargo home doesn't need to be named .cargo, for example when it is relocated using
+        // CARGO_HOME. It is only canonicalized if the name matches to keep the scan fast
+        if cargo_home.is_some_and(|home| {
+            home.file_name() == it.file_name() && canonicalize_or_not(&it) == home
+        }) {
+            continue;
+        }
 
         let filename = it.file_name().unwrap_or_default().to_string_lossy();
         match filename.as_ref() {
@@ -504,66 +3008,247 @@ fn find_cargo_projects_task(
             // they should probably not be deleted. See issue #2 (https://github.com/dnlmlr/cargo-clean-all/issues/2)
             ".git" | ".cargo" => (),
             "target" if has_cargo_toml => has_target = true,
+            // Redirected target directories inside of the project are not searched either
+            _ if configured_target.as_ref().is_some_and(|target| {
+                target.file_name() == it.file_name() && canonicalize_or_not(&it) == *target
+            }) => {}
+            _ if filename.starts_with(cleanup::RENAMED_TARGET_PREFIX) => stats
+                .leftover_targets
+                .lock()
+                .unwrap()
+                .push(it.to_path_buf()),
+            _ if is_skipped_name(&it, &filename, args) => (),
+            // The ignore files only prevent descending into directories, since many of them
+            // ignore the target directories as well
+            _ if is_ignored_dir(&it, &ignore_files) => (),
             // For directories queue a new job to search it with the threadpool
-            _ => job.explore_recursive(it.to_path_buf()).unwrap(),
+            _ => job
+                .explore_recursive(it.to_path_buf(), &ignore_files, in_git_repo)
+                .unwrap(),
         }
     }
 
     // If path contains a Cargo.toml, it is a project directory
     if has_cargo_toml {
-        results.send(ProjectDir(job.path, has_target)).unwrap();
+        // A leftover `target` directory is not used by cargo when the target is redirected
+        let analysis = match configured_target {
+            Some(target) if target.is_dir() => {
+                Some(redirected_target_analysis(&job.path, target, stats))
+            }
+            Some(_) => None,
+            None => has_target.then(|| {
+                let analysis =
+                    ProjectTargetAnalysis::analyze_target(&job.path, job.path.join("target"));
+                stats
+                    .target_size
+                    .fetch_add(analysis.size, Ordering::Relaxed);
+                analysis
+            }),
+        };
+        stats.projects.fetch_add(1, Ordering::Relaxed);
+        results.send(ProjectDir(job.path, analysis)).unwrap();
     }
     if args.verbose {
         pb.set_message("waiting...");
     }
 }
 
+/// Analyze the redirected target directory of the project, unless it was already analyzed for
+/// another project
+fn redirected_target_analysis(
+    project_path: &Path,
+    target_path: PathBuf,
+    stats: &ScanStats,
+) -> ProjectTargetAnalysis {
+    if let Some(analysis) = stats.redirected_targets.lock().unwrap().get(&target_path) {
+        return ProjectTargetAnalysis {
+            project_path: project_path.to_owned(),
+            ..analysis.clone()
+        };
+    }
+    let analysis = ProjectTargetAnalysis::analyze_target(project_path, target_path.clone());
+    stats
+        .target_size
+        .fetch_add(analysis.size, Ordering::Relaxed);
+    stats
+        .redirected_targets
+        .lock()
+        .unwrap()
+        .insert(target_path, analysis.clone());
+    analysis
+}
+
 #[derive(Clone, Debug)]
 struct ProjectTargetAnalysis {
-    /// The path of the project without the `target` directory suffix
+    /// The path of the project without the `target` directory suffix. For the shared target
+    /// directory, this is the target directory itself
     project_path: PathBuf,
+    /// The target directory, which is `target` in the project unless it is redirected by
+    /// `build.target-dir` in the cargo config
+    target_path: PathBuf,
+    /// Whether this target directory is shared by multiple projects, like the one of
+    /// `CARGO_TARGET_DIR`, instead of belonging to a single project
+    shared_target: bool,
     /// The size in bytes that the target directory takes up
     size: u64,
     /// The timestamp of the last recently modified file in the target directory
     last_modified: SystemTime,
+    /// The number of files in the target directory
+    file_count: u64,
+    /// The rustc versions that were used to build the target directory
+    toolchains: Vec<RustcVersion>,
     /// Indicate that this target directory should be cleaned
     selected_for_cleanup: bool,
+    /// The cleanup policy configured for this project
+    policy: Policy,
+    /// The user whose home directory contains the project when using --all-users
+    owner: Option<String>,
+    /// The coverage data files outside of the target directory and their sizes, if enabled
+    coverage_files: Vec<(PathBuf, u64)>,
+    /// The wasm-pack and trunk output directories with their sizes and file counts, if enabled
+    web_outputs: Vec<(PathBuf, u64, u64)>,
 }
 
 impl ProjectTargetAnalysis {
     /// Analyze a given project directories target directory
     pub fn analyze(path: &Path) -> Self {
-        let (size, last_modified) = Self::recursive_scan_target(&path.join("target"));
+        let target_path = cargo_config::target_dir(path).unwrap_or_else(|| path.join("target"));
+        Self::analyze_target(path, target_path)
+    }
+
+    /// Analyze a target directory that is shared by multiple projects, like the one that
+    /// `CARGO_TARGET_DIR` points to
+    pub fn analyze_shared(target_path: &Path) -> Self {
+        Self {
+            shared_target: true,
+            ..Self::analyze_target(target_path, target_path.to_owned())
+        }
+    }
+
+    /// Analyze the target directory of the project, which is not `target` in the project if it is
+    /// redirected by the cargo config
+    fn analyze_target(path: &Path, target_path: PathBuf) -> Self {
+        let (size, last_modified, file_count) = Self::recursive_scan_target(&target_path);
         Self {
+            toolchains: toolchain::target_toolchains(&target_path),
             project_path: path.to_owned(),
+            target_path,
+            shared_target: false,
             size,
             last_modified,
+            file_count,
             selected_for_cleanup: false,
+            policy: Policy::default(),
+            owner: None,
+            coverage_files: Vec::new(),
+            web_outputs: Vec::new(),
+        }
+    }
+
+    /// Find the files outside of the target directory that are cleaned as well, as requested by
+    /// the args
+    pub fn find_extra_files(&mut self, args: &AppArgs) {
+        // The shared target directory has no project directory around it
+        if args.coverage && !self.shared_target {
+            self.coverage_files = coverage::find_coverage_files(&self.project_path);
+        }
+        if args.web_outputs && !self.shared_target {
+            self.web_outputs = web_outputs::find_web_outputs(&self.project_path);
+        }
+        if args.doc_only {
+            let (size, last_modified, file_count) = doc_dirs(&self.target_path)
+                .iter()
+                .map(Self::recursive_scan_target)
+                .fold((0, SystemTime::UNIX_EPOCH, 0), |a, b| {
+                    (a.0 + b.0, a.1.max(b.1), a.2 + b.2)
+                });
+            self.size = size;
+            self.last_modified = last_modified;
+            self.file_count = file_count;
         }
     }
 
-    // Recursively sum up the file sizes and find the last modified timestamp
-    fn recursive_scan_target<T: AsRef<Path>>(path: T) -> (u64, SystemTime) {
+    /// The size of the coverage data files outside of the target directory
+    pub fn coverage_size(&self) -> u64 {
+        self.coverage_files.iter().map(|(_, size)| size).sum()
+    }
+
+    /// The size of the web toolchain output directories
+    pub fn web_outputs_size(&self) -> u64 {
+        self.web_outputs.iter().map(|(_, size, _)| size).sum()
+    }
+
+    /// The space that is freed by cleaning the project
+    pub fn total_size(&self) -> u64 {
+        self.size + self.coverage_size() + self.web_outputs_size()
+    }
+
+    // Recursively sum up the file sizes, find the last modified timestamp and count the files
+    fn recursive_scan_target<T: AsRef<Path>>(path: T) -> (u64, SystemTime, u64) {
+        if FOLLOW_SYMLINKS.load(Ordering::Relaxed) {
+            return Self::recursive_scan_following(path.as_ref(), &mut HashSet::new());
+        }
         let path = path.as_ref();
 
-        let default = (0, SystemTime::UNIX_EPOCH);
+        let default = (0, SystemTime::UNIX_EPOCH, 0);
 
         if !path.exists() || path.is_symlink() {
             return default;
         }
 
         match (path.is_file(), path.metadata()) {
-            (true, Ok(md)) => (md.len(), md.modified().unwrap_or(default.1)),
+            (true, Ok(md)) => (md.len(), md.modified().unwrap_or(default.1), 1),
             _ => path
                 .read_dir()
                 .map(|rd| {
                     rd.filter_map(|it| it.ok().map(|it| it.path()))
                         .map(Self::recursive_scan_target)
-                        .fold(default, |a, b| (a.0 + b.0, a.1.max(b.1)))
+                        .fold(default, |a, b| (a.0 + b.0, a.1.max(b.1), a.2 + b.2))
                 })
                 .unwrap_or(default),
         }
     }
+
+    /// Like `recursive_scan_target`, but following symlinks. Directories that were already
+    /// visited are skipped, so that symlink loops end
+    fn recursive_scan_following(
+        path: &Path,
+        visited: &mut HashSet<DirId>,
+    ) -> (u64, SystemTime, u64) {
+        let default = (0, SystemTime::UNIX_EPOCH, 0);
+
+        match path.metadata() {
+            Ok(md) if md.is_file() => (md.len(), md.modified().unwrap_or(default.1), 1),
+            Ok(md) if md.is_dir() => {
+                if !dir_id(path).is_some_and(|id| visited.insert(id)) {
+                    return default;
+                }
+                path.read_dir()
+                    .map(|rd| {
+                        rd.filter_map(|it| it.ok().map(|it| it.path()))
+                            .map(|it| Self::recursive_scan_following(&it, visited))
+                            .fold(default, |a, b| (a.0 + b.0, a.1.max(b.1), a.2 + b.2))
+                    })
+                    .unwrap_or(default)
+            }
+            _ => default,
+        }
+    }
+}
+
+/// The generated documentation in the target directory, `target/doc` and the doc directories of
+/// the cross-compilation targets like `target/wasm32-unknown-unknown/doc`
+fn doc_dirs(target_path: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = prune::dir_entries(target_path)
+        .into_iter()
+        .map(|dir| dir.join("doc"))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    let doc = target_path.join("doc");
+    if doc.is_dir() {
+        dirs.push(doc);
+    }
+    dirs
 }
 
 /// Remove the `\\?\` prefix from canonicalized windows paths and replace all `\` path separators
@@ -576,23 +3261,131 @@ fn pretty_format_path(p: &Path) -> String {
         .replace('\\', "/")
 }
 
-impl Display for ProjectTargetAnalysis {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let project_name = self
-            .project_path
-            .file_name()
-            .unwrap_or_default()
-            .to_string_lossy();
+/// How much a project stands out in the listing, based on the age and size of its target
+#[derive(Clone, Copy, Debug)]
+enum Severity {
+    /// Targets that are smaller than 10 MiB or were built in the last day
+    Low,
+    Normal,
+    /// Other targets that are larger than 1 GiB or older than 30 days
+    Elevated,
+    /// Targets that are larger than 5 GiB and older than 30 days
+    High,
+}
+
+impl Severity {
+    fn style(self, project_name: &str) -> colored::ColoredString {
+        let name = project_name.bold();
+        match self {
+            Severity::Low => name.dimmed(),
+            Severity::Normal => name.color(Color::Green),
+            Severity::Elevated => name.color(Color::Yellow),
+            Severity::High => name.color(Color::Red),
+        }
+    }
+}
+
+impl ProjectTargetAnalysis {
+    fn severity(&self) -> Severity {
+        const MIB: u64 = 1024 * 1024;
+        let age_days = self.last_modified.elapsed().unwrap_or_default().as_secs() / (60 * 60 * 24);
+        let size = self.total_size();
+
+        if age_days > 30 && size > 5 * 1024 * MIB {
+            Severity::High
+        } else if age_days < 1 || size < 10 * MIB {
+            Severity::Low
+        } else if age_days > 30 || size > 1024 * MIB {
+            Severity::Elevated
+        } else {
+            Severity::Normal
+        }
+    }
+}
+
+/// The path is never shortened below this number of characters, a line with less room wraps instead
+const MIN_TRUNCATED_PATH_WIDTH: usize = 20;
+
+impl ProjectTargetAnalysis {
+    /// The name that the project is listed with
+    fn name(&self) -> Cow<'_, str> {
+        if self.shared_target {
+            "shared target".into()
+        } else {
+            self.project_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+        }
+    }
+
+    /// The line that describes the project in the listing. With a width, the path is shortened in
+    /// the middle so that the line fits into that many columns.
+    fn listing_line(&self, width: Option<usize>) -> String {
+        let project_name = self.name();
         let path = pretty_format_path(&canonicalize_or_not(&self.project_path));
 
         let last_modified: chrono::DateTime<chrono::Local> = self.last_modified.into();
-        write!(
-            f,
-            "{}: {} ({}), {}",
-            project_name.bold().color(Color::Green),
-            bytefmt::format(self.size),
-            last_modified.format("%Y-%m-%d %H:%M"),
-            path,
+        let toolchains = if self.toolchains.is_empty() {
+            String::new()
+        } else {
+            let versions: Vec<_> = self.toolchains.iter().map(|tc| tc.to_string()).collect();
+            format!(", rustc {}", versions.join(", "))
+        };
+        // The files outside of the target directory that are cleaned as well
+        let mut extras = String::new();
+        if !self.coverage_files.is_empty() {
+            extras += &format!(
+                " + {} coverage data",
+                i18n::format_size(self.coverage_size())
+            );
+        }
+        if !self.web_outputs.is_empty() {
+            extras += &format!(
+                " + {} web outputs",
+                i18n::format_size(self.web_outputs_size())
+            );
+        }
+        let description = format!(
+            "{}: {}{} ({}{}), ",
+            self.severity().style(&project_name),
+            i18n::format_size(self.size),
+            extras,
+            i18n::format_datetime(&last_modified),
+            toolchains.dimmed(),
+        );
+
+        let path = match width {
+            Some(width) => {
+                let path_width = width.saturating_sub(measure_text_width(&description));
+                output::truncate_middle(&path, path_width.max(MIN_TRUNCATED_PATH_WIDTH))
+            }
+            None => path,
+        };
+        description + &path
+    }
+}
+
+impl Display for ProjectTargetAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.listing_line(None))
+    }
+}
+
+impl selector::Item for ProjectTargetAnalysis {
+    fn truncated(&self, width: usize) -> String {
+        self.listing_line(Some(width))
+    }
+
+    fn size(&self) -> u64 {
+        self.total_size()
+    }
+
+    fn search_text(&self) -> String {
+        format!(
+            "{} {}",
+            self.name(),
+            pretty_format_path(&canonicalize_or_not(&self.project_path))
         )
     }
 }
@@ -0,0 +1,91 @@
+//! Minimal representation of the parts of a `Cargo.toml` manifest that are relevant for cleaning.
+
+use crate::policy::PolicyConfig;
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{io, path::Path};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Manifest {
+    pub package: Option<ManifestTable>,
+    pub workspace: Option<ManifestTable>,
+    /// The `[lib]` target, only its presence is relevant
+    pub lib: Option<toml::Value>,
+    /// The `[[bin]]` targets, only their presence is relevant
+    #[serde(default)]
+    pub bin: Vec<toml::Value>,
+}
+
+/// The kinds of crates a project can contain. A package can be a library and a binary at the same
+/// time and the root package of a workspace is a workspace as well
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CrateKind {
+    /// Packages with a library target
+    Lib,
+    /// Packages with at least one binary target
+    Bin,
+    /// Workspace roots
+    Workspace,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ManifestTable {
+    pub name: Option<String>,
+    /// The version is kept as a raw value, since it can also be inherited from the workspace
+    pub version: Option<toml::Value>,
+    pub metadata: Option<ManifestMetadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ManifestMetadata {
+    /// The `[package.metadata.clean-all]` or `[workspace.metadata.clean-all]` table
+    #[serde(rename = "clean-all")]
+    pub clean_all: Option<PolicyConfig>,
+}
+
+impl Manifest {
+    /// Read and parse the `Cargo.toml` in the given project directory
+    pub fn read(project_path: &Path) -> io::Result<Self> {
+        let content = std::fs::read_to_string(project_path.join("Cargo.toml"))?;
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// The package name and version, if they are specified in the manifest
+    pub fn package_name_version(&self) -> (Option<&str>, Option<&str>) {
+        let package = self.package.as_ref();
+        (
+            package.and_then(|p| p.name.as_deref()),
+            package.and_then(|p| p.version.as_ref()?.as_str()),
+        )
+    }
+
+    /// The cleanup policy configured in the package metadata, or the workspace metadata if the
+    /// package doesn't specify one
+    pub fn clean_all_policy(&self) -> Option<&PolicyConfig> {
+        [&self.package, &self.workspace]
+            .into_iter()
+            .flatten()
+            .find_map(|t| t.metadata.as_ref()?.clean_all.as_ref())
+    }
+
+    /// The kinds of crates in the project, based on the manifest and the default target locations
+    /// `src/lib.rs`, `src/main.rs` and `src/bin`
+    pub fn crate_kinds(&self, project_path: &Path) -> Vec<CrateKind> {
+        let mut kinds = Vec::new();
+        if self.package.is_some() {
+            if self.lib.is_some() || project_path.join("src/lib.rs").is_file() {
+                kinds.push(CrateKind::Lib);
+            }
+            if !self.bin.is_empty()
+                || project_path.join("src/main.rs").is_file()
+                || project_path.join("src/bin").is_dir()
+            {
+                kinds.push(CrateKind::Bin);
+            }
+        }
+        if self.workspace.is_some() {
+            kinds.push(CrateKind::Workspace);
+        }
+        kinds
+    }
+}
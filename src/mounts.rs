@@ -0,0 +1,135 @@
+//! Detection of the mount points and filesystems that the target directories are located on.
+
+use std::path::{Path, PathBuf};
+
+/// A mounted filesystem
+#[derive(Clone, Debug)]
+pub struct Mount {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    /// The path inside the source filesystem that is mounted. This is `/` unless a subdirectory is
+    /// bind mounted
+    pub root: String,
+    /// The device number of the filesystem as `major:minor`
+    pub device: String,
+}
+
+impl Mount {
+    /// Check if this is the overlay filesystem that container images are usually running on
+    pub fn is_overlay(&self) -> bool {
+        self.fs_type == "overlay" || self.fs_type == "fuse-overlayfs"
+    }
+
+    /// Identifies the filesystem, so that multiple mounts of the same disk are grouped together
+    pub fn disk_id(&self) -> &str {
+        if self.device.is_empty() {
+            self.mount_point.to_str().unwrap_or_default()
+        } else {
+            &self.device
+        }
+    }
+
+    /// A short description like `/workspace (ext4, bind mount)` that is used in the reports
+    pub fn describe(&self) -> String {
+        if self.root == "/" {
+            format!("{} ({})", self.mount_point.display(), self.fs_type)
+        } else {
+            format!(
+                "{} ({}, bind mount)",
+                self.mount_point.display(),
+                self.fs_type
+            )
+        }
+    }
+}
+
+/// List the mounted filesystems. This is only supported on Linux, on other platforms the list is
+/// empty.
+pub fn mounts() -> Vec<Mount> {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .map(|content| content.lines().filter_map(parse_mountinfo_line).collect())
+        .unwrap_or_default()
+}
+
+/// Parse a line of `/proc/self/mountinfo`, which looks like this:
+/// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+fn parse_mountinfo_line(line: &str) -> Option<Mount> {
+    let (mount_fields, fs_fields) = line.split_once(" - ")?;
+    let mut mount_fields = mount_fields.split(' ');
+    let device = mount_fields.nth(2)?.to_string();
+    let root = unescape(mount_fields.next()?);
+    let mount_point = PathBuf::from(unescape(mount_fields.next()?));
+
+    let fs_type = fs_fields.split(' ').next()?.to_string();
+
+    Some(Mount {
+        mount_point,
+        fs_type,
+        root,
+        device,
+    })
+}
+
+/// Decode the octal escapes like `\040` for spaces that are used in the mountinfo paths
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(idx) = rest.find('\\') {
+        result.push_str(&rest[..idx]);
+        let escape = rest.get(idx + 1..idx + 4);
+        match escape.and_then(|oct| u8::from_str_radix(oct, 8).ok()) {
+            Some(byte) => {
+                result.push(byte as char);
+                rest = &rest[idx + 4..];
+            }
+            None => {
+                result.push('\\');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Find the mount that contains the given path, which is the one with the longest matching mount
+/// point
+pub fn mount_of<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a Mount> {
+    let path = crate::canonicalize_or_not(path);
+    mounts
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.components().count())
+}
+
+/// Check if the program is running inside a container like docker, podman or a dev container
+pub fn in_container() -> bool {
+    Path::new("/.dockerenv").exists()
+        || Path::new("/run/.containerenv").exists()
+        || std::env::var_os("REMOTE_CONTAINERS").is_some()
+        || std::env::var_os("CODESPACES").is_some()
+}
+
+/// The space that is available to unprivileged users on the filesystem that contains the path.
+/// This is only supported on Unix
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: The path is a valid NUL terminated string and statvfs is a plain C struct, which is
+    // only read after the call succeeded
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+            return None;
+        }
+        stat
+    };
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
@@ -0,0 +1,69 @@
+//! Control over the human readable output, which is suppressed when a compact or machine
+//! readable output mode is selected.
+
+use dialoguer::console::Term;
+use indicatif::ProgressDrawTarget;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress all human readable output, including the progress bars
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// The draw target for progress bars, which is hidden when the output is suppressed
+pub fn progress_target() -> ProgressDrawTarget {
+    if is_quiet() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/// The width of the terminal that stdout is connected to. Returns `None` if stdout is not a
+/// terminal, in which case nothing should be shortened
+pub fn terminal_width() -> Option<usize> {
+    let term = Term::stdout();
+    term.is_term().then(|| term.size().1 as usize)
+}
+
+/// Shorten the text to the given number of characters by replacing the middle with `…`, which
+/// keeps both the start and the end of paths recognizable
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+
+    let kept = width.saturating_sub(1);
+    let head: String = text.chars().take(kept / 2).collect();
+    let tail: String = text.chars().skip(len - (kept - kept / 2)).collect();
+    format!("{}…{}", head, tail)
+}
+
+/// Like `println!`, but only prints if the human readable output is not suppressed
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use outln;
+
+/// Like `eprintln!`, but only prints if the human readable output is not suppressed
+macro_rules! errln {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub(crate) use errln;
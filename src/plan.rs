@@ -0,0 +1,278 @@
+//! Cleanup plans, which save the selection of a run so that it can be reviewed and executed later
+//! using the `apply` subcommand.
+
+use crate::{
+    cleanup,
+    i18n::{self, tr},
+    journal::Journal,
+    json,
+    output::{self, outln},
+    pretty_format_path, AppArgs, PlanArgs, ProjectTargetAnalysis,
+};
+use clap::Parser;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    /// The arguments of the `plan` subcommand. These also determine how the cleanup is done when
+    /// the plan is applied, for example whether executables are preserved
+    pub args: Vec<String>,
+    /// The time the plan was created at as RFC 3339 timestamp
+    pub created: String,
+    pub projects: Vec<PlannedProject>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedProject {
+    /// The canonicalized path of the project
+    pub project_path: PathBuf,
+    /// The size of the target directory in bytes
+    pub target_size: u64,
+    pub file_count: u64,
+    /// The last modified time of the target directory in seconds since the unix epoch
+    pub last_modified: u64,
+    /// Whether the project is a target directory that is shared by multiple projects
+    #[serde(default)]
+    pub shared_target: bool,
+}
+
+impl From<&ProjectTargetAnalysis> for PlannedProject {
+    fn from(p: &ProjectTargetAnalysis) -> Self {
+        Self {
+            project_path: crate::canonicalize_or_not(&p.project_path),
+            target_size: p.size,
+            file_count: p.file_count,
+            last_modified: unix_secs(p.last_modified),
+            shared_target: p.shared_target,
+        }
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Save the selected projects as plan to the given file
+pub fn write_plan(
+    path: &Path,
+    args: &[String],
+    selected: &[ProjectTargetAnalysis],
+) -> io::Result<()> {
+    let plan = Plan {
+        args: args.to_vec(),
+        created: chrono::Local::now().to_rfc3339(),
+        projects: selected.iter().map(PlannedProject::from).collect(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&plan)?)
+}
+
+/// Analyze the planned projects again. Projects whose target directory is gone or was modified
+/// after the given point in time are skipped.
+pub fn analyze_planned(
+    projects: &[PlannedProject],
+    args: &AppArgs,
+    since: &str,
+) -> Vec<ProjectTargetAnalysis> {
+    let mut selected = Vec::new();
+    for planned in projects {
+        let path = pretty_format_path(&planned.project_path);
+        let mut analysis = if planned.shared_target {
+            ProjectTargetAnalysis::analyze_shared(&planned.project_path)
+        } else {
+            ProjectTargetAnalysis::analyze(&planned.project_path)
+        };
+        if !analysis.target_path.is_dir() {
+            outln!(
+                "{}",
+                tr!(
+                    "Skipping {}, since its target directory doesn't exist",
+                    path
+                )
+            );
+            continue;
+        }
+        // With --doc-only, the plan records the last modification of the doc directories
+        analysis.find_extra_files(args);
+        if unix_secs(analysis.last_modified) > planned.last_modified {
+            outln!(
+                "{}",
+                tr!("Skipping {}, since it was built after {}", path, since)
+            );
+            continue;
+        }
+        selected.push(analysis);
+    }
+
+    selected
+}
+
+/// Execute the cleanup that is described by the plan file. Each project is analyzed again and
+/// skipped if its target directory is gone or was modified after the plan was created.
+pub fn apply(plan_path: &Path, yes: bool, dry_run: bool) -> io::Result<()> {
+    let plan: Plan = serde_json::from_str(&std::fs::read_to_string(plan_path)?)?;
+
+    let plan_args = PlanArgs::try_parse_from(
+        std::iter::once("plan".to_string()).chain(plan.args.iter().cloned()),
+    )
+    .map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid arguments in the plan: {}", e),
+        )
+    })?;
+    let args = plan_args.args;
+
+    crate::output::set_quiet(args.oneline || args.json);
+    if let Some(locale) = args.lang {
+        i18n::set_locale(locale);
+    }
+    crate::ensure_not_root(&args);
+    let _lock = (!dry_run).then(crate::instance_lock::lock_or_exit);
+
+    outln!("{}", tr!("Applying the plan created at {}", plan.created));
+
+    let selected = analyze_planned(&plan.projects, &args, &tr!("the plan was created"));
+    let json_report = args
+        .json
+        .then(|| json::Report::new(false, &selected, &[], args.group_by));
+
+    // The plan is journaled and recorded like the selection of a normal run, so an interrupted
+    // apply can be resumed as well
+    let mut journal_args = vec!["plan".to_string()];
+    journal_args.extend(plan.args);
+
+    #[cfg(feature = "db")]
+    let mut db = args.db.as_ref().and_then(|path| {
+        crate::db::Db::record_scan(path, &journal_args, dry_run, false, &selected, &[])
+            .map_err(|e| eprintln!("Failed to record the scan in the database: {}", e))
+            .ok()
+    });
+
+    if selected.is_empty() {
+        outln!("{}", tr!("None of the planned projects can be cleaned"));
+        if let Some(report) = json_report {
+            report.status(json::Status::Cleaned).print();
+        }
+        return Ok(());
+    }
+
+    outln!("\n{}", tr!("Cleaning the following project directories:"));
+    let width = output::terminal_width();
+    selected
+        .iter()
+        .for_each(|p| outln!("{}", p.listing_line(width)));
+
+    let will_free_size: u64 = selected.iter().map(ProjectTargetAnalysis::total_size).sum();
+    outln!(
+        "\n{}",
+        tr!(
+            "Cleaning {} projects will free: {}",
+            selected.len(),
+            i18n::format_size(will_free_size).bold()
+        )
+    );
+
+    if dry_run {
+        outln!("{}", tr!("Dry run. Not doing any cleanup"));
+        if let Some(report) = json_report {
+            report.status(json::Status::DryRun).print();
+        }
+        return Ok(());
+    }
+
+    if !yes
+        && !args.confirm_each
+        && !dialoguer::Confirm::new()
+            .with_prompt(tr!("Clean the project directories shown above?"))
+            .wait_for_newline(true)
+            .interact()
+            .unwrap_or(false)
+    {
+        outln!("{}", tr!("Cleanup cancelled"));
+        if let Some(report) = json_report {
+            report.status(json::Status::Cancelled).print();
+        }
+        return Ok(());
+    }
+
+    outln!("{}", tr!("Starting cleanup..."));
+    let mut journal = Journal::start(&journal_args, &selected);
+    let result = cleanup::cleanup(&args, &selected, journal.as_mut());
+    if let Some(journal) = journal {
+        journal.finish();
+    }
+
+    #[cfg(feature = "db")]
+    if let Some(db) = &mut db {
+        if let Err(e) = db.record_cleanup(&result) {
+            eprintln!("Failed to record the cleanup in the database: {}", e);
+        }
+    }
+
+    outln!();
+    crate::print_cleanup_result(&result);
+
+    if args.oneline {
+        println!(
+            "cleaned {}/{} planned projects, freed {}, {} failure{}",
+            result.cleaned,
+            selected.len(),
+            bytefmt::format(result.freed_size),
+            result.failed.len(),
+            if result.failed.len() == 1 { "" } else { "s" }
+        );
+    }
+    if let Some(report) = json_report {
+        report.with_cleanup(&result).print();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use std::{fs::File, time::Duration};
+
+    fn write_file(path: &Path, modified: SystemTime) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = File::create(path).unwrap();
+        file.set_len(1024).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn doc_only_plan_is_applied() {
+        let project =
+            std::env::temp_dir().join(format!("cca-doc-only-plan-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&project);
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("Cargo.toml"), "[package]\nname = \"p\"\n").unwrap();
+
+        // The build outputs are newer than the docs, only the docs are cleaned
+        let doc_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        write_file(&project.join("target/doc/index.html"), doc_modified);
+        write_file(&project.join("target/debug/p"), SystemTime::now());
+
+        let args = Cli::parse_from(["cargo-clean-all", "--doc-only"]).args;
+        let mut analysis = ProjectTargetAnalysis::analyze(&project);
+        analysis.find_extra_files(&args);
+        let planned = [PlannedProject::from(&analysis)];
+        assert_eq!(planned[0].last_modified, unix_secs(doc_modified));
+
+        let selected = analyze_planned(&planned, &args, "the plan was created");
+        std::fs::remove_dir_all(&project).unwrap();
+
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].size, 1024);
+        assert_eq!(selected[0].file_count, 1);
+    }
+}
@@ -0,0 +1,156 @@
+//! Cleanup policies that are defined alongside the projects, either by `.cargo-clean-all.toml`
+//! files in the scanned directories or by the `[package.metadata.clean-all]` table in the
+//! projects manifest.
+
+use crate::manifest::Manifest;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Name of the per-directory configuration files
+pub const DIR_CONFIG_FILE_NAME: &str = ".cargo-clean-all.toml";
+
+/// The policy settings that can be specified for a directory (and its subtree) or a project
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PolicyConfig {
+    /// Directories relative to the config file that should be ignored by default
+    #[serde(default)]
+    pub ignore: Vec<PathBuf>,
+    /// Override for `--keep-days`
+    pub keep_days: Option<u32>,
+    /// Override for `--keep-size`
+    #[serde(default, deserialize_with = "deserialize_opt_size")]
+    pub keep_size: Option<u64>,
+    /// Never clean the projects, not even when selected interactively
+    #[serde(default, alias = "never")]
+    pub never_clean: bool,
+}
+
+/// The merged policy that applies to a single project
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    pub ignored: bool,
+    pub keep_days: Option<u32>,
+    pub keep_size: Option<u64>,
+    pub never_clean: bool,
+}
+
+impl Policy {
+    /// Apply the given config on top of this policy. Values set in the config override the
+    /// current ones, while `never-clean` and `ignore` can only be enabled, not disabled again.
+    fn merge(&mut self, config: &PolicyConfig, config_dir: &Path, project_path: &Path) {
+        self.never_clean |= config.never_clean;
+        self.ignored |= config
+            .ignore
+            .iter()
+            .any(|p| crate::starts_with_canonicalized(project_path, config_dir.join(p)));
+        self.keep_days = config.keep_days.or(self.keep_days);
+        self.keep_size = config.keep_size.or(self.keep_size);
+    }
+}
+
+pub fn deserialize_opt_size<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<Option<u64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Str(String),
+    }
+
+    match Option::<Size>::deserialize(d)? {
+        None => Ok(None),
+        Some(Size::Bytes(b)) => Ok(Some(b)),
+        Some(Size::Str(s)) => bytefmt::parse(s)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Resolves the policies for projects by looking up the per-directory config files between the
+/// scan roots and the project, as well as the projects manifest. Parsed directory config files are
+/// cached, since sibling projects share most of their parent directories.
+pub struct PolicyResolver {
+    roots: Vec<PathBuf>,
+    cache: HashMap<PathBuf, Option<PolicyConfig>>,
+    verbose: bool,
+}
+
+impl PolicyResolver {
+    pub fn new(roots: &[PathBuf], verbose: bool) -> Self {
+        Self {
+            roots: roots.iter().map(crate::canonicalize_or_not).collect(),
+            cache: HashMap::new(),
+            verbose,
+        }
+    }
+
+    /// Resolve the policy for the given project. The config files closer to the project take
+    /// precedence over the ones closer to the scan root and the projects manifest takes precedence
+    /// over all directory config files.
+    pub fn resolve(&mut self, project_path: &Path) -> Policy {
+        let project_path = crate::canonicalize_or_not(project_path);
+
+        // The innermost scan root that contains the project, in case the roots are nested
+        let root = self
+            .roots
+            .iter()
+            .filter(|root| project_path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .cloned()
+            .unwrap_or_else(|| project_path.clone());
+
+        let mut dirs: Vec<_> = project_path
+            .ancestors()
+            .take_while(|p| p.starts_with(&root))
+            .map(Path::to_path_buf)
+            .collect();
+        dirs.reverse();
+
+        let mut policy = Policy::default();
+        for dir in dirs {
+            if let Some(config) = self.dir_config(&dir) {
+                policy.merge(config, &dir, &project_path);
+            }
+        }
+
+        match Manifest::read(&project_path) {
+            Ok(manifest) => {
+                if let Some(config) = manifest.clean_all_policy() {
+                    policy.merge(config, &project_path, &project_path);
+                }
+            }
+            Err(e) => {
+                if self.verbose {
+                    eprintln!(
+                        "Error reading manifest of '{}': {}",
+                        project_path.display(),
+                        e
+                    );
+                }
+            }
+        }
+
+        policy
+    }
+
+    fn dir_config(&mut self, dir: &Path) -> Option<&PolicyConfig> {
+        let verbose = self.verbose;
+        self.cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let path = dir.join(DIR_CONFIG_FILE_NAME);
+                let content = std::fs::read_to_string(&path).ok()?;
+                toml::from_str(&content)
+                    .map_err(|e| {
+                        verbose.then(|| eprintln!("Invalid config '{}': {}", path.display(), e))
+                    })
+                    .ok()
+            })
+            .as_ref()
+    }
+}
@@ -0,0 +1,65 @@
+//! Detection of the power source and the user idle time, used to defer scheduled cleanups that
+//! would otherwise cause a lot of I/O at a bad time.
+
+use std::{process::Command, time::Duration};
+
+/// Run the command and return its stdout if it succeeded
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Check if the system is running on AC power. Systems without a battery are always on AC power.
+/// Returns `None` if the power source can't be determined.
+pub fn on_ac_power() -> Option<bool> {
+    if cfg!(target_os = "linux") {
+        let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+        let mut has_battery = false;
+        for supply in supplies.filter_map(|it| it.ok()).map(|it| it.path()) {
+            let read = |name| std::fs::read_to_string(supply.join(name)).unwrap_or_default();
+            match read("type").trim() {
+                "Mains" if read("online").trim() == "1" => return Some(true),
+                "Battery" => has_battery = true,
+                _ => (),
+            }
+        }
+        Some(!has_battery)
+    } else if cfg!(target_os = "macos") {
+        command_output("pmset", &["-g", "batt"]).map(|out| out.contains("'AC Power'"))
+    } else if cfg!(windows) {
+        // BatteryStatus 2 means that the system is connected to AC power. Without a battery the
+        // output is empty
+        let out = command_output(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "(Get-CimInstance Win32_Battery).BatteryStatus",
+            ],
+        )?;
+        let status = out.trim();
+        Some(status.is_empty() || status == "2")
+    } else {
+        None
+    }
+}
+
+/// The time since the last user input. Returns `None` if the idle time can't be determined,
+/// which is the case on Linux without `xprintidle` and on Windows.
+pub fn idle_time() -> Option<Duration> {
+    if cfg!(target_os = "macos") {
+        // The HIDIdleTime is reported in nanoseconds
+        let out = command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+        let line = out.lines().find(|l| l.contains("\"HIDIdleTime\""))?;
+        let nanos = line.rsplit('=').next()?.trim().parse().ok()?;
+        Some(Duration::from_nanos(nanos))
+    } else if cfg!(unix) {
+        let millis = command_output("xprintidle", &[])?.trim().parse().ok()?;
+        Some(Duration::from_millis(millis))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,176 @@
+//! Preservation of build artifacts that should survive the cleanup, by moving them out of the
+//! target directory before it is deleted.
+
+use crate::pretty_format_path;
+use indicatif::ProgressBar;
+use is_executable::is_executable;
+use std::path::{Path, PathBuf};
+
+/// Move the executables from the profile and cross-compilation directories in the projects target
+/// directory to the `executables` directory in the project. The debug symbols of the executables
+/// are moved along with them. Errors are printed using the progress bar so that the output
+/// doesn't interfere with it. Returns the new paths of the preserved executables.
+pub fn preserve_executables(
+    project_path: &Path,
+    target_path: &Path,
+    pb: &ProgressBar,
+    verbose: bool,
+) -> Vec<PathBuf> {
+    let project_executables_path = project_path.join("executables");
+    let mut preserved = Vec::new();
+
+    for target_subdir in read_subdirs(target_path, project_path, pb, verbose) {
+        let files = match target_subdir.read_dir() {
+            Ok(it) => it,
+            Err(e) => {
+                report_read_error(project_path, e, pb, verbose);
+                continue;
+            }
+        };
+
+        let files = files
+            .filter_map(|it| it.ok())
+            .filter_map(|it| it.file_type().is_ok_and(|t| t.is_file()).then(|| it.path()));
+
+        for exe_file_path in files.filter(|file| is_executable(file)) {
+            let new_exe_dir =
+                project_executables_path.join(target_subdir.file_name().expect("Path Error"));
+
+            for symbols_path in debug_symbol_paths(&exe_file_path) {
+                move_into(&symbols_path, &new_exe_dir, "debug symbols", pb);
+            }
+            preserved.extend(move_into(&exe_file_path, &new_exe_dir, "executable", pb));
+        }
+    }
+    preserved
+}
+
+/// Move the `.wasm` outputs from the `wasm32-*` cross-compilation directories and the JS bindings
+/// generated by `wasm-bindgen` (for example by trunk) to the `wasm` directory in the project.
+pub fn preserve_wasm(project_path: &Path, target_path: &Path, pb: &ProgressBar, verbose: bool) {
+    let project_wasm_path = project_path.join("wasm");
+
+    for target_subdir in read_subdirs(target_path, project_path, pb, verbose) {
+        let subdir_name = target_subdir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        let is_wasm_triple = subdir_name.starts_with("wasm32-");
+        if !is_wasm_triple && subdir_name != "wasm-bindgen" {
+            continue;
+        }
+
+        for profile_dir in read_subdirs(&target_subdir, project_path, pb, verbose) {
+            let new_dir = project_wasm_path
+                .join(&subdir_name)
+                .join(profile_dir.file_name().expect("Path Error"));
+
+            let entries = match profile_dir.read_dir() {
+                Ok(it) => it,
+                Err(e) => {
+                    report_read_error(project_path, e, pb, verbose);
+                    continue;
+                }
+            };
+
+            for entry in entries.filter_map(|it| it.ok()).map(|it| it.path()) {
+                let is_wasm = entry.is_file() && entry.extension().is_some_and(|e| e == "wasm");
+                // The wasm-bindgen output directories only contain the generated bindings, so
+                // everything is preserved there, including the `snippets` directory
+                if is_wasm || !is_wasm_triple {
+                    move_into(&entry, &new_dir, "wasm artifact", pb);
+                }
+            }
+        }
+    }
+}
+
+/// List the subdirectories of the given directory, reporting read errors if verbose is enabled
+fn read_subdirs(
+    dir: &Path,
+    project_path: &Path,
+    pb: &ProgressBar,
+    verbose: bool,
+) -> impl Iterator<Item = PathBuf> {
+    let entries = match dir.read_dir() {
+        Ok(it) => Some(it),
+        Err(e) => {
+            report_read_error(project_path, e, pb, verbose);
+            None
+        }
+    };
+
+    entries
+        .into_iter()
+        .flatten()
+        .filter_map(|it| it.ok())
+        .filter_map(|it| it.file_type().is_ok_and(|t| t.is_dir()).then(|| it.path()))
+}
+
+fn report_read_error(project_path: &Path, e: std::io::Error, pb: &ProgressBar, verbose: bool) {
+    if verbose {
+        pb.suspend(|| {
+            eprintln!(
+                "Error reading target dir of: '{}'  {}",
+                pretty_format_path(project_path),
+                e
+            )
+        });
+    }
+}
+
+/// Move the file or directory into the destination directory, creating the destination if
+/// necessary. Returns the new path, errors are printed using the progress bar.
+fn move_into(path: &Path, dest_dir: &Path, what: &str, pb: &ProgressBar) -> Option<PathBuf> {
+    if let Err(e) = std::fs::create_dir_all(dest_dir) {
+        pb.suspend(|| {
+            eprintln!(
+                "Error creating {} dir: '{}'  {}",
+                what,
+                dest_dir.display(),
+                e
+            )
+        });
+        return None;
+    }
+
+    let new_path = dest_dir.join(path.file_name().expect("Path Error"));
+    match std::fs::rename(path, &new_path) {
+        Ok(()) => Some(new_path),
+        Err(e) => {
+            pb.suspend(|| eprintln!("Error moving {}: '{}'  {}", what, new_path.display(), e));
+            None
+        }
+    }
+}
+
+/// Find the existing debug symbol files that belong to the given executable. These are `.pdb`
+/// files on Windows (where hyphens in the crate name are replaced by underscores), `.dSYM`
+/// bundles on macOS and `.dwp` packages for split debuginfo on Linux.
+fn debug_symbol_paths(exe_file_path: &Path) -> Vec<PathBuf> {
+    let (Some(dir), Some(file_name), Some(stem)) = (
+        exe_file_path.parent(),
+        exe_file_path.file_name(),
+        exe_file_path.file_stem(),
+    ) else {
+        return Vec::new();
+    };
+    let file_name = file_name.to_string_lossy();
+    let stem = stem.to_string_lossy();
+
+    let mut candidates = vec![
+        format!("{}.pdb", stem),
+        format!("{}.pdb", stem.replace('-', "_")),
+        format!("{}.dSYM", file_name),
+        format!("{}.dwp", file_name),
+    ];
+    candidates.dedup();
+
+    candidates
+        .into_iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.exists())
+        .collect()
+}
@@ -0,0 +1,56 @@
+//! Detection of elevated privileges and the home directories of all users on the system.
+
+use std::path::PathBuf;
+
+/// Check if the program runs as root on unix or with administrator privileges on Windows
+#[cfg(unix)]
+pub fn is_elevated() -> bool {
+    // SAFETY: geteuid has no preconditions and can't fail
+    unsafe { libc::geteuid() == 0 }
+}
+
+/// Check if the program runs as root on unix or with administrator privileges on Windows
+#[cfg(windows)]
+pub fn is_elevated() -> bool {
+    // `net session` is only allowed for administrators, which makes it a simple and reliable check
+    // without needing to query the process token
+    std::process::Command::new("net")
+        .arg("session")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn is_elevated() -> bool {
+    false
+}
+
+/// The directory that contains the home directories of the users
+fn users_base_dir() -> PathBuf {
+    if cfg!(windows) {
+        let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+        PathBuf::from(format!("{}\\Users", drive))
+    } else if cfg!(target_os = "macos") {
+        PathBuf::from("/Users")
+    } else {
+        PathBuf::from("/home")
+    }
+}
+
+/// List the home directories of all users together with the user names. The user name is derived
+/// from the directory name. Shared and system directories like `Public` are skipped.
+pub fn user_home_dirs() -> std::io::Result<Vec<(String, PathBuf)>> {
+    const NON_USER_DIRS: &[&str] = &["Public", "Shared", "Default", "Default User", "All Users"];
+
+    let mut homes: Vec<_> = users_base_dir()
+        .read_dir()?
+        .filter_map(|it| it.ok())
+        .filter(|it| it.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|it| (it.file_name().to_string_lossy().to_string(), it.path()))
+        .filter(|(name, _)| !name.starts_with('.') && !NON_USER_DIRS.contains(&name.as_str()))
+        .collect();
+    homes.sort();
+    Ok(homes)
+}
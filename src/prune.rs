@@ -0,0 +1,269 @@
+//! Pruning of the artifacts of dependency versions that are no longer used by a project. After
+//! many dependency updates, most of a long-lived target directory consists of the artifacts of old
+//! versions that cargo never cleans up by itself.
+//!
+//! The dep-info files of registry dependencies list their sources in the extracted registry
+//! sources, like `registry/src/<index>/serde-1.0.188/src/lib.rs`, so the outdated units are found
+//! by comparing those package versions to the ones in the Cargo.lock. The artifacts that belong to
+//! a unit share its metadata hash, like `.fingerprint/serde-<hash>`, `build/serde-<hash>` and
+//! `deps/libserde-<hash>.rlib`.
+
+use crate::{toolchain, ProjectTargetAnalysis};
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// The locked packages as names with version like `serde-1.0.188`, which is how the registry
+/// sources are named, and the names of the packages
+struct LockedPackages {
+    dir_names: HashSet<String>,
+    names: HashSet<String>,
+}
+
+/// Read the packages from the Cargo.lock of the project
+fn locked_packages(project_path: &Path) -> io::Result<LockedPackages> {
+    let content = std::fs::read_to_string(project_path.join("Cargo.lock"))
+        .map_err(|e| io::Error::new(e.kind(), format!("Reading the Cargo.lock failed: {}", e)))?;
+    let lockfile: Lockfile =
+        toml::from_str(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(LockedPackages {
+        dir_names: lockfile
+            .package
+            .iter()
+            .map(|p| format!("{}-{}", p.name, p.version))
+            .collect(),
+        names: lockfile.package.into_iter().map(|p| p.name).collect(),
+    })
+}
+
+/// The registry source directory name like `serde-1.0.188` of the first source in the dep-info
+/// file. Returns `None` for the units of local and git packages
+fn registry_source(dep_info: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(dep_info).ok()?;
+    content.split_whitespace().find_map(|token| {
+        let components: Vec<&str> = token.split(['/', '\\']).collect();
+        let idx = components
+            .windows(2)
+            .position(|w| w == ["registry", "src"])?;
+        components.get(idx + 3).map(|c| c.to_string())
+    })
+}
+
+/// The metadata hash of an artifact name like `libserde-0123abcd.rlib` or `serde-0123abcd`
+pub fn artifact_hash(file_name: &str) -> Option<&str> {
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+    Some(stem.rsplit_once('-')?.1)
+}
+
+pub fn dir_entries(dir: &Path) -> Vec<PathBuf> {
+    dir.read_dir()
+        .map(|rd| rd.filter_map(|it| it.ok()).map(|it| it.path()).collect())
+        .unwrap_or_default()
+}
+
+pub fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+/// The directories of the target that contain build outputs, like `target/debug` or
+/// `target/x86_64-unknown-linux-gnu/release`
+pub fn profile_dirs(target_path: &Path) -> Vec<PathBuf> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        dir.read_dir()
+            .map(|rd| {
+                rd.filter_map(|it| it.ok())
+                    .filter(|it| it.file_type().is_ok_and(|t| t.is_dir()))
+                    .map(|it| it.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut dirs = Vec::new();
+    for dir in subdirs(target_path) {
+        if dir.join(".fingerprint").is_dir() {
+            dirs.push(dir);
+        } else {
+            dirs.extend(
+                subdirs(&dir)
+                    .into_iter()
+                    .filter(|d| d.join(".fingerprint").is_dir()),
+            );
+        }
+    }
+    dirs
+}
+
+/// Find the artifacts in the target directory that belong to dependency versions which are not
+/// in the Cargo.lock anymore
+pub fn outdated_artifacts(project_path: &Path, target_path: &Path) -> io::Result<Vec<PathBuf>> {
+    let locked = locked_packages(project_path)?;
+    let is_outdated = |dep_info: &Path| {
+        registry_source(dep_info).is_some_and(|source| !locked.dir_names.contains(&source))
+    };
+
+    let mut outdated = Vec::new();
+    for profile_dir in profile_dirs(target_path) {
+        let fingerprints = dir_entries(&profile_dir.join(".fingerprint"));
+        let build_dirs = dir_entries(&profile_dir.join("build"));
+        let deps = dir_entries(&profile_dir.join("deps"));
+
+        // The dep-info files of the compiled crates are in deps, the ones of the compiled build
+        // scripts in their build directory
+        let mut outdated_hashes: HashSet<&str> = deps
+            .iter()
+            .filter(|p| p.extension().is_some_and(|ext| ext == "d") && is_outdated(p))
+            .filter_map(|p| artifact_hash(file_name(p)))
+            .collect();
+        for build_dir in &build_dirs {
+            if dir_entries(build_dir)
+                .iter()
+                .any(|p| p.extension().is_some_and(|ext| ext == "d") && is_outdated(p))
+            {
+                outdated_hashes.extend(artifact_hash(file_name(build_dir)));
+            }
+        }
+
+        // The outputs of running build scripts can't be attributed to a version, so they are only
+        // pruned when the package isn't used at all anymore
+        for unit in fingerprints.iter().chain(&build_dirs) {
+            if let Some((name, hash)) = file_name(unit).rsplit_once('-') {
+                if !locked.names.contains(name) {
+                    outdated_hashes.insert(hash);
+                }
+            }
+        }
+
+        outdated.extend(
+            fingerprints
+                .iter()
+                .chain(&build_dirs)
+                .chain(&deps)
+                .filter(|p| {
+                    artifact_hash(file_name(p)).is_some_and(|h| outdated_hashes.contains(h))
+                })
+                .cloned(),
+        );
+    }
+    Ok(outdated)
+}
+
+#[derive(Deserialize)]
+struct Fingerprint {
+    /// The hash of the version of the rustc that built the unit
+    rustc: u64,
+}
+
+/// The hash of the rustc version that is stored in the fingerprints of the unit
+fn fingerprint_rustc(unit: &Path) -> Option<u64> {
+    dir_entries(unit)
+        .iter()
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .find_map(|p| {
+            let content = std::fs::read_to_string(p).ok()?;
+            serde_json::from_str::<Fingerprint>(&content).ok()
+        })
+        .map(|fingerprint| fingerprint.rustc)
+}
+
+/// Find the artifacts in the target directory that were built by other toolchains than the given
+/// ones. The fingerprints only contain a hash of the rustc version, so the version itself is read
+/// from the metadata of a crate that was compiled by the same rustc. Units of rustc versions that
+/// can't be determined that way are kept
+pub fn foreign_toolchain_artifacts(target_path: &Path, kept_versions: &[String]) -> Vec<PathBuf> {
+    let mut foreign = Vec::new();
+    for profile_dir in profile_dirs(target_path) {
+        let fingerprints = dir_entries(&profile_dir.join(".fingerprint"));
+        let build_dirs = dir_entries(&profile_dir.join("build"));
+        let deps = dir_entries(&profile_dir.join("deps"));
+
+        // The metadata hashes of the units by the rustc hash of their fingerprints
+        let mut units_by_rustc: HashMap<u64, Vec<&str>> = HashMap::new();
+        for unit in &fingerprints {
+            if let (Some(hash), Some(rustc)) =
+                (artifact_hash(file_name(unit)), fingerprint_rustc(unit))
+            {
+                units_by_rustc.entry(rustc).or_default().push(hash);
+            }
+        }
+
+        let mut foreign_hashes: HashSet<&str> = HashSet::new();
+        for hashes in units_by_rustc.values() {
+            let version = deps
+                .iter()
+                .filter(|p| {
+                    p.extension()
+                        .is_some_and(|ext| ext == "rmeta" || ext == "rlib")
+                        && artifact_hash(file_name(p)).is_some_and(|h| hashes.contains(&h))
+                })
+                .find_map(|p| toolchain::embedded_version(p));
+            if version.is_some_and(|version| !kept_versions.contains(&version)) {
+                foreign_hashes.extend(hashes);
+            }
+        }
+
+        foreign.extend(
+            fingerprints
+                .iter()
+                .chain(&build_dirs)
+                .chain(&deps)
+                .filter(|p| artifact_hash(file_name(p)).is_some_and(|h| foreign_hashes.contains(h)))
+                .cloned(),
+        );
+    }
+    foreign
+}
+
+/// List the files in the artifacts together with their sizes and modification times
+pub fn artifact_files(artifacts: &[PathBuf]) -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut files = Vec::new();
+    for path in artifacts {
+        match path.symlink_metadata() {
+            Ok(md) if md.is_dir() => files.extend(crate::target_filter::deleted_files(
+                path,
+                &Default::default(),
+            )),
+            Ok(md) => files.push((
+                path.clone(),
+                md.len(),
+                md.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            )),
+            Err(_) => (),
+        }
+    }
+    files
+}
+
+/// Delete the artifacts. Returns the size and the number of the deleted files.
+pub fn delete_artifacts(artifacts: &[PathBuf]) -> io::Result<(u64, u64)> {
+    let mut deleted = (0, 0);
+    for path in artifacts {
+        let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(path);
+        if path.is_dir() {
+            remove_dir_all::remove_dir_all(path)?;
+        } else {
+            std::fs::remove_file(path)?;
+        }
+        deleted.0 += size;
+        deleted.1 += file_count;
+    }
+    Ok(deleted)
+}
@@ -0,0 +1,78 @@
+//! Sanity checks that are done before touching the target directories, to avoid deleting data
+//! outside of the projects.
+
+use crate::{pretty_format_path, AppArgs};
+use std::{
+    io,
+    path::{Component, Path},
+};
+
+/// Check that the target directory of the project resolves to a path inside the project. This is
+/// not the case if the target directory is a symlink that points somewhere else, for example to
+/// shared data.
+pub fn check_target_inside_project(project_path: &Path) -> io::Result<()> {
+    let project = std::fs::canonicalize(project_path)?;
+    let target = std::fs::canonicalize(project_path.join("target"))?;
+
+    if target.starts_with(&project) {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "The target directory resolves to '{}', which is outside of the project. Use --force \
+             to clean it anyway",
+            pretty_format_path(&target)
+        )))
+    }
+}
+
+/// Check that the cleanup of a target directory that is shared by multiple projects doesn't need a
+/// project directory, which is where the preserved artifacts and the Cargo.lock would be and where
+/// `cargo clean` would run
+pub fn check_shared_target(args: &AppArgs, needs_preservation: bool) -> io::Result<()> {
+    if needs_preservation || args.prune_outdated_deps {
+        return Err(io::Error::other(
+            "The shared target directory doesn't belong to a single project, so its \
+             artifacts can't be preserved or pruned by the Cargo.lock",
+        ));
+    }
+    if args.use_cargo_clean {
+        return Err(io::Error::other(
+            "The shared target directory doesn't belong to a single project, so it \
+             can't be cleaned using cargo clean",
+        ));
+    }
+    Ok(())
+}
+
+/// Hard sanity checks for a directory that is about to be deleted, which can't be overridden by any
+/// flags. Filesystem roots, the home directory of the user and directories with fewer than
+/// `min_depth` path components are refused.
+pub fn check_deletion_path(path: &Path, min_depth: usize) -> io::Result<()> {
+    let path = crate::canonicalize_or_not(path);
+    let refuse = |reason: &str| {
+        Err(io::Error::other(format!(
+            "Refusing to delete '{}', since it is {}",
+            pretty_format_path(&path),
+            reason
+        )))
+    };
+
+    let depth = path
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .count();
+
+    if depth == 0 {
+        return refuse("a filesystem root");
+    }
+    if std::env::home_dir().is_some_and(|home| crate::canonicalize_or_not(home) == path) {
+        return refuse("the home directory");
+    }
+    if depth < min_depth {
+        return refuse(&format!(
+            "less than {} directories deep (see --min-target-depth)",
+            min_depth
+        ));
+    }
+    Ok(())
+}
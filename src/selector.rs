@@ -0,0 +1,337 @@
+//! The interactive project selection. In contrast to the dialoguer multi select, it allows marking
+//! projects as permanently ignored directly from the list. The list can be narrowed down with a
+//! fuzzy search, which keeps the selection of the hidden items.
+
+use crate::i18n::{self, tr};
+use colored::Colorize;
+use dialoguer::console::{measure_text_width, Key, Term};
+use std::{fmt::Display, io};
+
+const HELP: &str = "[↑↓] move, [space] toggle, [a] toggle all shown, [p] ignore permanently, \
+                    [d] details, [/] search, [enter] confirm, [esc] cancel";
+
+const SEARCH_HELP: &str = "[↑↓] move, [enter] keep the search, [esc] clear the search";
+
+const IGNORED_PERMANENTLY: &str = "(ignore permanently)";
+
+/// An item that can be shown in the selector. The `Display` implementation is used for the
+/// details of the highlighted item, which may wrap over multiple lines.
+pub trait Item: Display {
+    /// The item shortened to fit into the given number of columns
+    fn truncated(&self, width: usize) -> String;
+
+    /// The number of bytes that are freed by selecting the item, which is summed up below the list
+    fn size(&self) -> u64;
+
+    /// The text that the search query is matched against
+    fn search_text(&self) -> String;
+}
+
+/// Whether the characters of the query appear in the text in the same order, ignoring the case.
+/// This matches abbreviations like `ccall` for `cargo-clean-all`
+pub fn fuzzy_matches(text: &str, query: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|q| text.any(|c| c == q))
+}
+
+/// The state of a single item in the selector and in the table of `--tui`
+#[derive(Clone, Copy, PartialEq)]
+pub enum Mark {
+    Selected,
+    Unselected,
+    /// Not selected and added to the persistent ignore list when confirming
+    IgnoredPermanently,
+}
+
+/// The result of a confirmed selection, containing the indices of the items
+pub struct Selection {
+    pub selected: Vec<usize>,
+    pub ignored_permanently: Vec<usize>,
+}
+
+pub struct Selector<'a, T> {
+    prompt: &'a str,
+    items: &'a [T],
+    marks: Vec<Mark>,
+    /// Only the items that match the query are shown
+    query: String,
+    /// Whether the query is being typed
+    searching: bool,
+    /// The indices of the shown items
+    shown: Vec<usize>,
+    /// Position of the highlighted item in the shown items
+    cursor: usize,
+    /// Position of the first item that is shown on the screen, when not all items fit on it
+    offset: usize,
+    /// Show the highlighted item in full instead of shortened
+    details: bool,
+}
+
+impl<'a, T: Item> Selector<'a, T> {
+    pub fn new(prompt: &'a str, items: &'a [T], defaults: &[bool]) -> Self {
+        let marks = (0..items.len())
+            .map(|i| match defaults.get(i) {
+                Some(true) => Mark::Selected,
+                _ => Mark::Unselected,
+            })
+            .collect();
+        Self {
+            prompt,
+            items,
+            marks,
+            query: String::new(),
+            searching: false,
+            shown: (0..items.len()).collect(),
+            cursor: 0,
+            offset: 0,
+            details: false,
+        }
+    }
+
+    /// Show the selector until the selection is confirmed or cancelled. Returns `None` if the
+    /// selection was cancelled
+    pub fn interact(mut self) -> io::Result<Option<Selection>> {
+        let term = Term::stderr();
+        if !term.is_term() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "The interactive selection requires a terminal",
+            ));
+        }
+
+        term.hide_cursor()?;
+        let result = self.run(&term);
+        term.show_cursor()?;
+        result
+    }
+
+    fn run(&mut self, term: &Term) -> io::Result<Option<Selection>> {
+        let mut rendered_lines = 0;
+        loop {
+            term.clear_last_lines(rendered_lines)?;
+            rendered_lines = self.render(term)?;
+
+            let key = term.read_key()?;
+            match key {
+                Key::ArrowUp => self.move_cursor(-1),
+                Key::ArrowDown => self.move_cursor(1),
+                Key::PageUp => self.move_cursor(-(self.page_size(term) as isize)),
+                Key::PageDown => self.move_cursor(self.page_size(term) as isize),
+                Key::Home => self.cursor = 0,
+                Key::End => self.cursor = self.shown.len().saturating_sub(1),
+                _ if self.searching => match key {
+                    Key::Char(c) => {
+                        self.query.push(c);
+                        self.update_shown();
+                    }
+                    Key::Backspace => {
+                        self.query.pop();
+                        self.update_shown();
+                    }
+                    Key::Enter => self.searching = false,
+                    Key::Escape => {
+                        self.query.clear();
+                        self.searching = false;
+                        self.update_shown();
+                    }
+                    _ => (),
+                },
+                Key::Char('k') => self.move_cursor(-1),
+                Key::Char('j') => self.move_cursor(1),
+                Key::Char(' ') => self.toggle(),
+                Key::Char('d') => self.details = !self.details,
+                Key::Char('/') => self.searching = true,
+                Key::Char('a') => {
+                    let select = self
+                        .shown
+                        .iter()
+                        .any(|&idx| self.marks[idx] == Mark::Unselected);
+                    for &idx in &self.shown {
+                        if self.marks[idx] != Mark::IgnoredPermanently {
+                            self.marks[idx] = if select {
+                                Mark::Selected
+                            } else {
+                                Mark::Unselected
+                            };
+                        }
+                    }
+                }
+                Key::Char('p') => {
+                    if let Some(mark) = self.highlighted().map(|idx| &mut self.marks[idx]) {
+                        *mark = match mark {
+                            Mark::IgnoredPermanently => Mark::Unselected,
+                            _ => Mark::IgnoredPermanently,
+                        };
+                    }
+                }
+                Key::Enter => {
+                    term.clear_last_lines(rendered_lines)?;
+                    return Ok(Some(self.selection()));
+                }
+                Key::Escape | Key::Char('q') => {
+                    term.clear_last_lines(rendered_lines)?;
+                    return Ok(None);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn selection(&self) -> Selection {
+        let with_mark = |mark| {
+            self.marks
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| **m == mark)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        Selection {
+            selected: with_mark(Mark::Selected),
+            ignored_permanently: with_mark(Mark::IgnoredPermanently),
+        }
+    }
+
+    /// The index of the highlighted item
+    fn highlighted(&self) -> Option<usize> {
+        self.shown.get(self.cursor).copied()
+    }
+
+    /// Show the items that match the query and keep the highlighted item if it still matches
+    fn update_shown(&mut self) {
+        let highlighted = self.highlighted();
+        self.shown = (0..self.items.len())
+            .filter(|&idx| fuzzy_matches(&self.items[idx].search_text(), &self.query))
+            .collect();
+        self.cursor = highlighted
+            .and_then(|idx| self.shown.iter().position(|&i| i == idx))
+            .unwrap_or(0);
+    }
+
+    fn toggle(&mut self) {
+        if let Some(mark) = self.highlighted().map(|idx| &mut self.marks[idx]) {
+            *mark = match mark {
+                Mark::Selected => Mark::Unselected,
+                Mark::Unselected | Mark::IgnoredPermanently => Mark::Selected,
+            };
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let last = self.shown.len().saturating_sub(1) as isize;
+        self.cursor = (self.cursor as isize + delta).clamp(0, last) as usize;
+    }
+
+    /// Whether the search line is shown below the help line
+    fn shows_search(&self) -> bool {
+        self.searching || !self.query.is_empty()
+    }
+
+    /// The number of items that fit on the screen between the prompt with the help and search
+    /// lines and the selected total
+    fn page_size(&self, term: &Term) -> usize {
+        let (rows, _) = term.size();
+        let header_lines = if self.shows_search() { 5 } else { 4 };
+        (rows as usize).saturating_sub(header_lines).max(1)
+    }
+
+    /// The number of selected items and their combined size
+    fn selected_total(&self) -> String {
+        let (count, size) = self
+            .items
+            .iter()
+            .zip(&self.marks)
+            .filter(|(_, mark)| **mark == Mark::Selected)
+            .fold((0, 0), |(count, size), (item, _)| {
+                (count + 1, size + item.size())
+            });
+        tr!(
+            "Selected {}/{} projects, cleaning will free: {}",
+            count,
+            self.items.len(),
+            i18n::format_size(size).bold()
+        )
+    }
+
+    /// Draw the prompt and the visible items. Returns the number of terminal lines that were
+    /// written, including the lines of wrapped items
+    fn render(&mut self, term: &Term) -> io::Result<usize> {
+        let (_, cols) = term.size();
+        let cols = (cols as usize).max(1);
+        let page_size = self.page_size(term);
+
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + page_size {
+            self.offset = self.cursor + 1 - page_size;
+        }
+
+        let help = if self.searching { SEARCH_HELP } else { HELP };
+        let mut lines = vec![
+            format!("{}", self.prompt.bold()),
+            format!("{}", i18n::translate(help).dimmed()),
+        ];
+        if self.shows_search() {
+            let cursor = if self.searching { "_" } else { "" };
+            lines.push(format!(
+                "{}",
+                format!(
+                    "Search: {}{} ({}/{} shown)",
+                    self.query,
+                    cursor,
+                    self.shown.len(),
+                    self.items.len()
+                )
+                .cyan()
+            ));
+        }
+
+        let visible = self
+            .shown
+            .iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(page_size)
+            .map(|(pos, &idx)| (pos, &self.items[idx], &self.marks[idx]));
+
+        for (pos, item, mark) in visible {
+            let pointer = if pos == self.cursor { ">" } else { " " };
+            // The pointer and the mark take up 6 columns
+            let width = match mark {
+                Mark::IgnoredPermanently => cols
+                    .saturating_sub(7 + measure_text_width(i18n::translate(IGNORED_PERMANENTLY))),
+                _ => cols.saturating_sub(6),
+            };
+            let item = if self.details && pos == self.cursor {
+                item.to_string()
+            } else {
+                item.truncated(width)
+            };
+            let line = match mark {
+                Mark::Selected => format!("{} [x] {}", pointer, item),
+                Mark::Unselected => format!("{} [ ] {}", pointer, item),
+                Mark::IgnoredPermanently => {
+                    format!(
+                        "{} [-] {} {}",
+                        pointer,
+                        item,
+                        i18n::translate(IGNORED_PERMANENTLY).yellow()
+                    )
+                }
+            };
+            lines.push(line);
+        }
+        lines.push(self.selected_total());
+
+        let mut rendered_lines = 0;
+        for line in lines {
+            rendered_lines += measure_text_width(&line).div_ceil(cols).max(1);
+            term.write_line(&line)?;
+        }
+        Ok(rendered_lines)
+    }
+}
@@ -0,0 +1,254 @@
+//! The `self-update` subcommand, which replaces a prebuilt binary with the latest release that is
+//! published on GitHub.
+
+use crate::output::outln;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    io::{self, Read},
+    path::Path,
+};
+
+const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/dnlmlr/cargo-clean-all/releases/latest";
+
+/// The maximum size of downloaded release assets
+const MAX_DOWNLOAD_SIZE: u64 = 200 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// Update the running binary to the latest release. Binaries installed using `cargo install` are
+/// only replaced with `force`, since they should be updated using cargo instead.
+pub fn self_update(yes: bool, force: bool) -> io::Result<()> {
+    let exe_path = std::env::current_exe()?;
+
+    if !force && is_cargo_installed(&exe_path) {
+        return Err(io::Error::other(
+            "cargo-clean-all was installed using cargo, update it using `cargo install \
+             cargo-clean-all` instead or use --force to replace it anyway",
+        ));
+    }
+
+    let release: Release = get(LATEST_RELEASE_URL)?
+        .body_mut()
+        .read_json()
+        .map_err(http_error)?;
+
+    let current_version = env!("CARGO_PKG_VERSION");
+    let latest_version = release.tag_name.trim_start_matches('v');
+    if parse_version(latest_version) <= parse_version(current_version) {
+        outln!("cargo-clean-all {} is up to date", current_version);
+        return Ok(());
+    }
+
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| is_platform_asset(&a.name))
+        .ok_or_else(|| {
+            io::Error::other(format!(
+                "Release {} has no prebuilt binary for {}-{}",
+                release.tag_name,
+                std::env::consts::ARCH,
+                std::env::consts::OS
+            ))
+        })?;
+    let expected_checksum = find_checksum(&release, asset)?;
+
+    if !yes
+        && !dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Update cargo-clean-all from {} to {}?",
+                current_version, latest_version
+            ))
+            .interact()
+            .unwrap_or(false)
+    {
+        outln!("Update cancelled");
+        return Ok(());
+    }
+
+    outln!("Downloading {}", asset.name);
+    let data = download(&asset.browser_download_url)?;
+
+    let checksum = hex_sha256(&data);
+    if checksum != expected_checksum {
+        return Err(io::Error::other(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset.name, expected_checksum, checksum
+        )));
+    }
+
+    let binary = extract_binary(&asset.name, data)?;
+    replace_exe(&exe_path, &binary)?;
+
+    outln!("Updated cargo-clean-all to {}", latest_version);
+    Ok(())
+}
+
+fn http_error(e: ureq::Error) -> io::Error {
+    io::Error::other(format!("HTTP request failed: {}", e))
+}
+
+fn get(url: &str) -> io::Result<ureq::http::Response<ureq::Body>> {
+    ureq::get(url)
+        .header(
+            "User-Agent",
+            concat!("cargo-clean-all/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+        .map_err(http_error)
+}
+
+fn download(url: &str) -> io::Result<Vec<u8>> {
+    get(url)?
+        .body_mut()
+        .with_config()
+        .limit(MAX_DOWNLOAD_SIZE)
+        .read_to_vec()
+        .map_err(http_error)
+}
+
+/// Check if the binary is located in the cargo bin directory
+fn is_cargo_installed(exe_path: &Path) -> bool {
+    crate::cargo_cache::cargo_home().is_some_and(|cargo_home| {
+        crate::starts_with_canonicalized(exe_path, cargo_home.join("bin"))
+    })
+}
+
+/// Parse a version like `0.6.2` into its numeric components for comparison
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// Check if the release asset is the binary archive for the current platform
+fn is_platform_asset(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let os_names: &[&str] = match std::env::consts::OS {
+        "macos" => &["darwin", "apple", "macos"],
+        os => &[os],
+    };
+
+    !name.contains("sha256")
+        && name.contains(std::env::consts::ARCH)
+        && os_names.iter().any(|os| name.contains(os))
+}
+
+/// Get the expected SHA-256 checksum of the asset, either from a `<asset>.sha256` file or from a
+/// checksum list in the `sha256sum` format
+fn find_checksum(release: &Release, asset: &Asset) -> io::Result<String> {
+    let checksum_asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == format!("{}.sha256", asset.name))
+        .or_else(|| {
+            release
+                .assets
+                .iter()
+                .find(|a| a.name.to_lowercase().contains("sha256sums"))
+        })
+        .ok_or_else(|| {
+            io::Error::other(format!(
+                "Release {} has no checksums, refusing to update",
+                release.tag_name
+            ))
+        })?;
+
+    let content =
+        String::from_utf8_lossy(&download(&checksum_asset.browser_download_url)?).to_string();
+
+    // Each line is either just the checksum or the checksum followed by the file name
+    content
+        .lines()
+        .find_map(|line| {
+            let mut parts = line.split_whitespace();
+            let checksum = parts.next()?;
+            match parts.next().map(|name| name.trim_start_matches('*')) {
+                None => Some(checksum),
+                Some(name) if name == asset.name => Some(checksum),
+                Some(_) => None,
+            }
+        })
+        .map(str::to_lowercase)
+        .ok_or_else(|| io::Error::other(format!("No checksum found for {}", asset.name)))
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Get the binary from the downloaded asset, which is either a `.tar.gz` or `.zip` archive or the
+/// binary itself
+fn extract_binary(asset_name: &str, data: Vec<u8>) -> io::Result<Vec<u8>> {
+    let is_binary_name = |path: &Path| {
+        path.file_stem()
+            .is_some_and(|stem| stem == env!("CARGO_PKG_NAME"))
+    };
+    let not_found = || io::Error::other(format!("No binary found in {}", asset_name));
+
+    if asset_name.ends_with(".tar.gz") || asset_name.ends_with(".tgz") {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data.as_slice()));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.header().entry_type().is_file() && is_binary_name(&entry.path()?) {
+                let mut binary = Vec::new();
+                entry.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+        Err(not_found())
+    } else if asset_name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(io::Cursor::new(data)).map_err(io::Error::other)?;
+        for idx in 0..archive.len() {
+            let mut file = archive.by_index(idx).map_err(io::Error::other)?;
+            if file.is_file() && file.enclosed_name().is_some_and(|p| is_binary_name(&p)) {
+                let mut binary = Vec::new();
+                file.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+        Err(not_found())
+    } else {
+        Ok(data)
+    }
+}
+
+/// Replace the executable with the new binary. The new binary is written next to the executable
+/// first and then moved into place, so that a failed write doesn't break the installation.
+fn replace_exe(exe_path: &Path, binary: &[u8]) -> io::Result<()> {
+    let new_path = exe_path.with_extension("new");
+    std::fs::write(&new_path, binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&new_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    // The running executable can't be overwritten on Windows, but it can be renamed
+    if cfg!(windows) {
+        let old_path = exe_path.with_extension("old");
+        let _ = std::fs::remove_file(&old_path);
+        std::fs::rename(exe_path, &old_path)?;
+    }
+
+    std::fs::rename(&new_path, exe_path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&new_path);
+    })
+}
@@ -0,0 +1,325 @@
+//! The local web dashboard of the `serve` subcommand. It lists the scanned projects, lets the user
+//! tick the ones to clean and shows the progress of the cleanup.
+//!
+//! The dashboard is only served on localhost and every request has to carry a random token that
+//! is part of the printed URL, so that neither other users nor websites opened in the browser can
+//! trigger a cleanup.
+
+use crate::{
+    cleanup, instance_lock, journal::Journal, output::outln, pretty_format_path, AppArgs,
+    ProjectTargetAnalysis,
+};
+use serde::{Deserialize, Serialize};
+use std::{io, sync::Mutex, thread, time::UNIX_EPOCH};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// The state of a project in the dashboard
+#[derive(Clone, Serialize)]
+#[serde(tag = "state", rename_all = "kebab-case")]
+enum Status {
+    Idle,
+    Queued,
+    Cleaning,
+    Cleaned { freed: u64 },
+    Failed { error: String },
+}
+
+#[derive(Serialize)]
+struct ProjectEntry {
+    id: usize,
+    path: String,
+    size: u64,
+    file_count: u64,
+    /// The last modified time of the target in seconds since the unix epoch
+    last_modified: u64,
+    toolchains: Vec<String>,
+    /// Whether the project matches the selection criteria of the args
+    preselected: bool,
+    /// Projects that are configured to never be cleaned can't be ticked
+    never_clean: bool,
+    #[serde(flatten)]
+    status: Status,
+}
+
+#[derive(Default, Serialize)]
+struct CleanupProgress {
+    running: bool,
+    total: usize,
+    done: usize,
+    freed: u64,
+    /// The chosen projects that were not cleaned, since --max-free or --time-limit stopped the
+    /// cleanup. Only set once the cleanup finished
+    not_cleaned: usize,
+}
+
+#[derive(Serialize)]
+struct State {
+    dry_run: bool,
+    projects: Vec<ProjectEntry>,
+    cleanup: CleanupProgress,
+}
+
+#[derive(Deserialize)]
+struct CleanRequest {
+    projects: Vec<usize>,
+}
+
+/// A random token for authenticating the requests, made of 32 bytes from the random number
+/// generator of the system
+fn random_token() -> io::Result<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compare the tokens in constant time, so that the response time doesn't tell how much of a
+/// guessed token is right
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Serve the dashboard for the found projects until the program is stopped using Ctrl+C
+pub fn serve(
+    args: &AppArgs,
+    raw_args: &[String],
+    selected: Vec<ProjectTargetAnalysis>,
+    ignored: Vec<ProjectTargetAnalysis>,
+    port: u16,
+) -> io::Result<()> {
+    let projects: Vec<_> = selected
+        .into_iter()
+        .map(|p| (p, true))
+        .chain(ignored.into_iter().map(|p| (p, false)))
+        .collect();
+    let state = Mutex::new(State {
+        dry_run: args.dry_run,
+        projects: projects
+            .iter()
+            .enumerate()
+            .map(|(id, (p, preselected))| ProjectEntry {
+                id,
+                path: pretty_format_path(&p.project_path),
+                size: p.total_size(),
+                file_count: p.file_count,
+                last_modified: p
+                    .last_modified
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                toolchains: p.toolchains.iter().map(|tc| tc.to_string()).collect(),
+                preselected: *preselected,
+                never_clean: p.policy.never_clean,
+                status: Status::Idle,
+            })
+            .collect(),
+        cleanup: CleanupProgress::default(),
+    });
+
+    let server = Server::http(("127.0.0.1", port)).map_err(io::Error::other)?;
+    let token = random_token()?;
+    outln!(
+        "\nServing the dashboard at http://127.0.0.1:{}/?token={}, press Ctrl+C to stop",
+        port,
+        token
+    );
+
+    thread::scope(|scope| {
+        for request in server.incoming_requests() {
+            let query_token = request
+                .url()
+                .split_once('?')
+                .and_then(|(_, query)| {
+                    query
+                        .split('&')
+                        .find_map(|param| param.strip_prefix("token="))
+                })
+                .unwrap_or_default()
+                .to_string();
+            if !tokens_match(&query_token, &token) {
+                respond(
+                    request,
+                    Response::from_string("Invalid token").with_status_code(403),
+                );
+                continue;
+            }
+
+            let path = request
+                .url()
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            match (request.method(), path.as_str()) {
+                (Method::Get, "/") => respond(
+                    request,
+                    Response::from_string(DASHBOARD_HTML).with_header(content_type("text/html")),
+                ),
+                (Method::Get, "/api/state") => {
+                    let body = serde_json::to_string(&*state.lock().unwrap())?;
+                    respond(
+                        request,
+                        Response::from_string(body).with_header(content_type("application/json")),
+                    );
+                }
+                (Method::Post, "/api/clean") => {
+                    let mut request = request;
+                    let response =
+                        start_cleanup(scope, &mut request, &state, &projects, args, raw_args);
+                    respond(request, response);
+                }
+                _ => respond(
+                    request,
+                    Response::from_string("Not found").with_status_code(404),
+                ),
+            }
+        }
+        Ok(())
+    })
+}
+
+/// The state of a processed project according to the result of the cleanup
+fn outcome(project: &ProjectTargetAnalysis, result: &cleanup::CleanupResult) -> Status {
+    if let Some((_, e)) = result
+        .failed
+        .iter()
+        .find(|(p, _)| p.project_path == project.project_path)
+    {
+        return Status::Failed {
+            error: e.to_string(),
+        };
+    }
+    match result
+        .freed_per_project
+        .iter()
+        .find(|(path, _, _)| *path == project.project_path)
+    {
+        Some(&(_, _, freed)) => Status::Cleaned { freed },
+        None => Status::Idle,
+    }
+}
+
+/// Start cleaning the requested projects in the background. The state of each project is updated
+/// as the cleanup progresses
+fn start_cleanup<'scope, 'env>(
+    scope: &'scope thread::Scope<'scope, 'env>,
+    request: &mut Request,
+    state: &'env Mutex<State>,
+    projects: &'env [(ProjectTargetAnalysis, bool)],
+    args: &'env AppArgs,
+    raw_args: &'env [String],
+) -> Response<io::Cursor<Vec<u8>>> {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        return Response::from_string(format!("Reading the request failed: {}", e))
+            .with_status_code(400);
+    }
+    let clean_request: CleanRequest = match serde_json::from_str(&body) {
+        Ok(clean_request) => clean_request,
+        Err(e) => {
+            return Response::from_string(format!("Invalid request: {}", e)).with_status_code(400)
+        }
+    };
+
+    let mut guard = state.lock().unwrap();
+    if guard.dry_run {
+        return Response::from_string("Dry run. Not doing any cleanup").with_status_code(403);
+    }
+    if guard.cleanup.running {
+        return Response::from_string("A cleanup is already running").with_status_code(409);
+    }
+    // The lock is only held while cleaning, so that other runs can use it while the dashboard is
+    // open
+    let lock = match instance_lock::lock() {
+        Ok(lock) => lock,
+        Err(e) => return Response::from_string(e.to_string()).with_status_code(409),
+    };
+
+    let mut ids: Vec<usize> = clean_request
+        .projects
+        .into_iter()
+        .filter(|&id| {
+            guard.projects.get(id).is_some_and(|p| {
+                !p.never_clean && matches!(p.status, Status::Idle | Status::Failed { .. })
+            })
+        })
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.is_empty() {
+        return Response::from_string("None of the projects can be cleaned").with_status_code(400);
+    }
+
+    for &id in &ids {
+        guard.projects[id].status = Status::Queued;
+    }
+    guard.cleanup = CleanupProgress {
+        running: true,
+        total: ids.len(),
+        ..Default::default()
+    };
+    drop(guard);
+    outln!("Cleaning {} projects selected in the dashboard", ids.len());
+
+    let queued = ids.len();
+    scope.spawn(move || {
+        let chosen: Vec<_> = ids.iter().map(|&id| projects[id].0.clone()).collect();
+        let id_of = |project: &ProjectTargetAnalysis| {
+            ids.iter()
+                .zip(&chosen)
+                .find(|(_, p)| p.project_path == project.project_path)
+                .map(|(&id, _)| id)
+        };
+        let on_progress = |project: &ProjectTargetAnalysis, event: cleanup::ProjectEvent| {
+            let Some(id) = id_of(project) else {
+                return;
+            };
+            let mut guard = state.lock().unwrap();
+            match event {
+                cleanup::ProjectEvent::Started => guard.projects[id].status = Status::Cleaning,
+                cleanup::ProjectEvent::Finished(result) => {
+                    guard.projects[id].status = outcome(project, result);
+                    guard.cleanup.done += 1;
+                    guard.cleanup.freed = result.freed_size;
+                }
+            }
+        };
+
+        let mut journal = Journal::start(raw_args, &chosen);
+        let result = cleanup::cleanup_with_progress(args, &chosen, journal.as_mut(), &on_progress);
+        if let Some(journal) = journal {
+            journal.finish();
+        }
+
+        // The elevated retries change the outcome after the projects were reported, and the
+        // projects that were never reached are back to idle
+        let mut guard = state.lock().unwrap();
+        for (&id, project) in ids.iter().zip(&chosen) {
+            guard.projects[id].status = outcome(project, &result);
+        }
+        guard.cleanup.done = guard.cleanup.total;
+        guard.cleanup.freed = result.freed_size;
+        guard.cleanup.not_cleaned = result.max_free_kept + result.timed_out;
+        guard.cleanup.running = false;
+        drop(lock);
+    });
+
+    Response::from_string(format!("{{\"queued\":{}}}", queued))
+        .with_status_code(202)
+        .with_header(content_type("application/json"))
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).expect("Invalid header")
+}
+
+fn respond<R: io::Read>(request: Request, response: Response<R>) {
+    if let Err(e) = request.respond(response) {
+        eprintln!("Failed to send the response: {}", e);
+    }
+}
@@ -0,0 +1,54 @@
+//! The order of the project listing and the interactive selection, which is also the order the
+//! projects are cleaned in with `--delete-order as-listed`.
+
+use crate::ProjectTargetAnalysis;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+/// What the projects are sorted by
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SortKey {
+    /// The size that cleaning the project frees, including coverage files and web outputs
+    Size,
+    /// The name of the project directory
+    Name,
+    /// The path of the project
+    Path,
+    /// The time the target directory was last modified
+    LastModified,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+fn name(project: &ProjectTargetAnalysis) -> String {
+    project
+        .project_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase()
+}
+
+fn compare(a: &ProjectTargetAnalysis, b: &ProjectTargetAnalysis, key: SortKey) -> Ordering {
+    match key {
+        SortKey::Size => a.total_size().cmp(&b.total_size()),
+        SortKey::Name => name(a).cmp(&name(b)),
+        SortKey::Path => a.project_path.cmp(&b.project_path),
+        SortKey::LastModified => a.last_modified.cmp(&b.last_modified),
+    }
+}
+
+/// Sort the projects, the ones that are equal by the key are sorted by their path
+pub fn sort_projects(projects: &mut [ProjectTargetAnalysis], key: SortKey, order: SortOrder) {
+    projects.sort_by(|a, b| {
+        let ordering = compare(a, b, key).then_with(|| a.project_path.cmp(&b.project_path));
+        match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    });
+}
@@ -0,0 +1,335 @@
+//! The distribution of the target ages and sizes of all found projects, which helps to pick
+//! sensible values for `--keep-days` and `--keep-size`, and subtotals of the projects grouped by
+//! different criteria.
+
+use crate::{
+    i18n::{self, tr},
+    output::outln,
+    prune, ProjectTargetAnalysis,
+};
+use clap::ValueEnum;
+use colored::Colorize;
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    path::{Path, PathBuf},
+};
+
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// The upper bounds (exclusive) and labels of the age histogram buckets in days
+const AGE_BUCKETS: &[(u64, &str)] = &[
+    (1, "< 1 day"),
+    (7, "1-7 days"),
+    (30, "7-30 days"),
+    (90, "30-90 days"),
+    (365, "90-365 days"),
+    (u64::MAX, "> 1 year"),
+];
+
+/// The upper bounds (exclusive) and labels of the size histogram buckets in bytes
+const SIZE_BUCKETS: &[(u64, &str)] = &[
+    (10_000_000, "< 10 MB"),
+    (100_000_000, "10-100 MB"),
+    (1_000_000_000, "100 MB-1 GB"),
+    (10_000_000_000, "1-10 GB"),
+    (u64::MAX, "> 10 GB"),
+];
+
+/// The width of the longest histogram bar
+const BAR_WIDTH: usize = 30;
+
+fn age_days(project: &ProjectTargetAnalysis) -> u64 {
+    project
+        .last_modified
+        .elapsed()
+        .unwrap_or_default()
+        .as_secs()
+        / SECS_PER_DAY
+}
+
+/// The value at the given percentile of the sorted values, using the nearest rank
+fn percentile(sorted: &[u64], percent: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+/// Print the percentiles and histograms of the target ages and sizes of the projects
+pub fn print_stats(projects: &[&ProjectTargetAnalysis]) {
+    if projects.is_empty() {
+        return;
+    }
+
+    let mut sizes: Vec<u64> = projects.iter().map(|p| p.total_size()).collect();
+    let mut ages: Vec<u64> = projects.iter().map(|p| age_days(p)).collect();
+    sizes.sort_unstable();
+    ages.sort_unstable();
+
+    outln!(
+        "\n{}",
+        tr!("Target statistics of {} projects:", projects.len())
+    );
+    outln!(
+        "  {}",
+        tr!(
+            "Size: p50 {}, p90 {}, max {}",
+            i18n::format_size(percentile(&sizes, 50)),
+            i18n::format_size(percentile(&sizes, 90)),
+            i18n::format_size(percentile(&sizes, 100))
+        )
+    );
+    outln!(
+        "  {}",
+        tr!(
+            "Age:  p50 {} days, p90 {} days, max {} days",
+            percentile(&ages, 50),
+            percentile(&ages, 90),
+            percentile(&ages, 100)
+        )
+    );
+
+    let untouched: Vec<_> = projects.iter().filter(|p| age_days(p) >= 90).collect();
+    outln!(
+        "  {}",
+        tr!(
+            "Untouched for 90+ days: {} projects, {}",
+            untouched.len(),
+            i18n::format_size(untouched.iter().map(|p| p.total_size()).sum())
+        )
+    );
+
+    outln!("\n{}", tr!("Age distribution:"));
+    print_histogram(projects, AGE_BUCKETS, age_days);
+    outln!("\n{}", tr!("Size distribution:"));
+    // The projects are bucketed by the size of the target like --keep-size compares it, the totals
+    // of the buckets include the other outputs
+    print_histogram(projects, SIZE_BUCKETS, |p| p.size);
+}
+
+/// Print the number of projects and their total size per bucket, with a bar for the number of
+/// projects
+fn print_histogram(
+    projects: &[&ProjectTargetAnalysis],
+    buckets: &[(u64, &'static str)],
+    value: impl Fn(&ProjectTargetAnalysis) -> u64,
+) {
+    // (number of projects, total size) per bucket
+    let mut totals = vec![(0usize, 0u64); buckets.len()];
+    for project in projects {
+        let value = value(project);
+        let idx = buckets
+            .iter()
+            .position(|(bound, _)| value < *bound)
+            .unwrap_or(buckets.len() - 1);
+        totals[idx].0 += 1;
+        totals[idx].1 += project.total_size();
+    }
+
+    let max_count = totals.iter().map(|t| t.0).max().unwrap_or(0).max(1);
+    let labels: Vec<_> = buckets
+        .iter()
+        .map(|(_, label)| i18n::translate(label))
+        .collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    for (label, (count, size)) in labels.iter().zip(totals) {
+        let bar = "#".repeat((count * BAR_WIDTH).div_ceil(max_count));
+        outln!(
+            "  {:<label_width$} {} {}",
+            label,
+            format!("{:<BAR_WIDTH$}", bar).cyan(),
+            tr!("{} projects, {}", count, i18n::format_size(size))
+        );
+    }
+}
+
+/// The ways the projects can be grouped for subtotals
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum GroupBy {
+    /// The directory that contains the project
+    ParentDir,
+    /// The month the target directory was last modified in
+    MonthLastUsed,
+    /// The toolchains that were used to build the target directory
+    Toolchain,
+}
+
+impl GroupBy {
+    /// The name that is used on the command line
+    fn name(self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    fn key(self, project: &ProjectTargetAnalysis) -> String {
+        match self {
+            GroupBy::ParentDir => {
+                let path = crate::canonicalize_or_not(&project.project_path);
+                path.parent()
+                    .map(crate::pretty_format_path)
+                    .unwrap_or_default()
+            }
+            GroupBy::MonthLastUsed => {
+                chrono::DateTime::<chrono::Local>::from(project.last_modified)
+                    .format("%Y-%m")
+                    .to_string()
+            }
+            GroupBy::Toolchain if project.toolchains.is_empty() => "unknown".to_string(),
+            GroupBy::Toolchain => project
+                .toolchains
+                .iter()
+                .map(|tc| tc.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+/// The number of projects, the reclaimable size and the selected size per group. Months are
+/// sorted chronologically, the other groups by their reclaimable size.
+pub fn group_totals(
+    selected: &[ProjectTargetAnalysis],
+    ignored: &[ProjectTargetAnalysis],
+    group_by: GroupBy,
+) -> Vec<(String, usize, u64, u64)> {
+    // (group, number of projects, reclaimable size, selected size)
+    let mut groups: Vec<(String, usize, u64, u64)> = Vec::new();
+    for (proj, is_selected) in selected
+        .iter()
+        .map(|p| (p, true))
+        .chain(ignored.iter().map(|p| (p, false)))
+    {
+        let key = group_by.key(proj);
+        let idx = match groups.iter().position(|g| g.0 == key) {
+            Some(idx) => idx,
+            None => {
+                groups.push((key, 0, 0, 0));
+                groups.len() - 1
+            }
+        };
+        groups[idx].1 += 1;
+        groups[idx].2 += proj.total_size();
+        if is_selected {
+            groups[idx].3 += proj.total_size();
+        }
+    }
+
+    match group_by {
+        GroupBy::MonthLastUsed => groups.sort_by(|a, b| a.0.cmp(&b.0)),
+        _ => groups.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0))),
+    }
+    groups
+}
+
+/// Print the number of projects, the reclaimable size and the selected size per group
+pub fn print_groups(
+    selected: &[ProjectTargetAnalysis],
+    ignored: &[ProjectTargetAnalysis],
+    group_by: GroupBy,
+) {
+    outln!("\n{}", tr!("Projects grouped by {}:", group_by.name()));
+    for (group, count, reclaimable, selected_size) in group_totals(selected, ignored, group_by) {
+        outln!(
+            "{}",
+            tr!(
+                "{}: {} in {} projects, selected: {}",
+                group.bold(),
+                i18n::format_size(reclaimable),
+                count,
+                i18n::format_size(selected_size)
+            )
+        );
+    }
+}
+
+/// Print the size of the outputs of each profile in the targets of the selected projects, which is
+/// what cleaning with --profile frees
+pub fn print_profile_sizes(selected: &[ProjectTargetAnalysis], profiles: &[&str]) {
+    // The size and the number of projects per profile
+    let mut totals = vec![(0, 0); profiles.len()];
+    for project in selected {
+        let mut found = vec![false; profiles.len()];
+        for dir in prune::profile_dirs(&project.target_path) {
+            if let Some(idx) = profiles.iter().position(|p| *p == prune::file_name(&dir)) {
+                totals[idx].0 += ProjectTargetAnalysis::recursive_scan_target(&dir).0;
+                found[idx] = true;
+            }
+        }
+        for (total, found) in totals.iter_mut().zip(found) {
+            total.1 += found as usize;
+        }
+    }
+
+    outln!("\n{}", tr!("Size per profile:"));
+    for (profile, (size, count)) in profiles.iter().zip(totals) {
+        outln!(
+            "{}",
+            tr!(
+                "{}: {} in {} projects",
+                profile.bold(),
+                i18n::format_size(size),
+                count
+            )
+        );
+    }
+}
+
+/// Print the `n` largest files in the targets of the projects, together with the project they
+/// belong to
+pub fn print_largest_files(projects: &[&ProjectTargetAnalysis], n: usize) {
+    if projects.is_empty() || n == 0 {
+        return;
+    }
+
+    // A min-heap of the largest files found so far as (size, project index, path)
+    let mut largest: BinaryHeap<Reverse<(u64, usize, PathBuf)>> = BinaryHeap::new();
+    for (idx, project) in projects.iter().enumerate() {
+        collect_largest_files(&project.target_path, idx, n, &mut largest);
+    }
+
+    let mut largest: Vec<_> = largest.into_iter().map(|Reverse(file)| file).collect();
+    largest.sort_by(|a, b| b.0.cmp(&a.0).then(a.2.cmp(&b.2)));
+
+    outln!("\n{}", tr!("Largest files in the targets:"));
+    for (size, idx, path) in largest {
+        let project_path = &projects[idx].project_path;
+        let relative = path.strip_prefix(project_path).unwrap_or(&path);
+        outln!(
+            "  {:>10}  {}  {}",
+            i18n::format_size(size),
+            crate::pretty_format_path(project_path).bold(),
+            crate::pretty_format_path(relative)
+        );
+    }
+}
+
+/// Recursively add the files in the directory to the heap, keeping only the `n` largest ones
+fn collect_largest_files(
+    dir: &Path,
+    project_idx: usize,
+    n: usize,
+    largest: &mut BinaryHeap<Reverse<(u64, usize, PathBuf)>>,
+) {
+    let Ok(rd) = dir.read_dir() else {
+        return;
+    };
+    for entry in rd.filter_map(|it| it.ok()) {
+        let Ok(ft) = entry.file_type() else {
+            continue;
+        };
+        if ft.is_dir() {
+            collect_largest_files(&entry.path(), project_idx, n, largest);
+        } else if ft.is_file() {
+            let size = entry.metadata().map(|md| md.len()).unwrap_or(0);
+            if largest.len() < n {
+                largest.push(Reverse((size, project_idx, entry.path())));
+            } else if largest.peek().is_some_and(|Reverse(min)| size > min.0) {
+                largest.pop();
+                largest.push(Reverse((size, project_idx, entry.path())));
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Detection of the storage type that the scanned directories and the targets are located on, which
+//! determines the default number of scan and deletion threads. Spinning disks are thrashed by many
+//! concurrent threads, while SSDs profit from the parallelism.
+
+use crate::mounts::{self, Mount};
+use std::path::{Path, PathBuf};
+
+/// The number of threads used on spinning disks
+const HDD_THREADS: usize = 2;
+
+/// The number of threads used on network filesystems. Those mostly wait for the network, but the
+/// server shouldn't be flooded with requests either
+const NETWORK_THREADS: usize = 4;
+
+/// Filesystem types of network filesystems
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageKind {
+    Ssd,
+    Hdd,
+    Network,
+}
+
+impl StorageKind {
+    fn threads(self) -> usize {
+        match self {
+            StorageKind::Ssd => num_cpus::get(),
+            StorageKind::Hdd => HDD_THREADS,
+            StorageKind::Network => NETWORK_THREADS,
+        }
+    }
+}
+
+/// Detect the storage type of the filesystem that contains the path. This is only supported on
+/// Linux, where the mount table and the block devices are known
+pub fn storage_kind(mounts: &[Mount], path: &Path) -> Option<StorageKind> {
+    let mount = mounts::mount_of(mounts, path)?;
+    if NETWORK_FS_TYPES.contains(&mount.fs_type.as_str()) {
+        return Some(StorageKind::Network);
+    }
+
+    // Partitions don't have a queue of their own, the rotational flag is found on the disk that
+    // contains them
+    let device = Path::new("/sys/dev/block").join(&mount.device);
+    let rotational = std::fs::read_to_string(device.join("queue/rotational"))
+        .or_else(|_| std::fs::read_to_string(device.join("../queue/rotational")))
+        .ok()?;
+    match rotational.trim() {
+        "0" => Some(StorageKind::Ssd),
+        "1" => Some(StorageKind::Hdd),
+        _ => None,
+    }
+}
+
+/// The number of threads to use for the given paths when no number is specified. The slowest
+/// storage type determines the number, if the storage type is unknown as many threads as virtual
+/// CPU cores are used
+pub fn default_threads(mounts: &[Mount], paths: &[PathBuf]) -> usize {
+    paths
+        .iter()
+        .filter_map(|p| storage_kind(mounts, p))
+        .map(StorageKind::threads)
+        .min()
+        .unwrap_or_else(num_cpus::get)
+}
@@ -0,0 +1,179 @@
+//! Selective cleaning of target directories, which deletes everything except the entries that
+//! match the `--keep-target-subdir` patterns, or only specific entries like `target/package`.
+
+use crate::ProjectTargetAnalysis;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// A glob pattern for paths relative to the target directory, like `release` or `*/release`. Each
+/// path component is matched separately, so `*` never matches across directories.
+#[derive(Clone, Debug)]
+pub struct TargetPattern {
+    components: Vec<glob::Pattern>,
+}
+
+impl TargetPattern {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let components = s
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty())
+            .map(|c| glob::Pattern::new(c).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if components.is_empty() {
+            return Err("The pattern must not be empty".to_string());
+        }
+        Ok(Self { components })
+    }
+
+    /// Check if the leading components of the pattern match the given relative path
+    fn matches_prefix(&self, rel_path: &[String]) -> bool {
+        rel_path.len() <= self.components.len()
+            && self
+                .components
+                .iter()
+                .zip(rel_path)
+                .all(|(pattern, component)| pattern.matches(component))
+    }
+}
+
+/// Which entries of the target directories are deleted. Entries that match a delete pattern are
+/// always deleted. If there are only delete patterns, everything else is kept, otherwise
+/// everything that doesn't match a keep pattern is deleted as well
+#[derive(Clone, Debug, Default)]
+pub struct TargetFilter {
+    pub keep: Vec<TargetPattern>,
+    pub delete: Vec<TargetPattern>,
+}
+
+impl TargetFilter {
+    /// Check if the whole target directory is deleted
+    pub fn deletes_all(&self) -> bool {
+        self.keep.is_empty() && self.delete.is_empty()
+    }
+
+    fn action(&self, rel_path: &[String]) -> Action {
+        let deleted = || self.delete.iter().filter(|p| p.matches_prefix(rel_path));
+        if deleted().any(|p| p.components.len() == rel_path.len()) {
+            return Action::Delete;
+        }
+        if deleted().next().is_some() {
+            return Action::Descend;
+        }
+        if self.keep.is_empty() && !self.delete.is_empty() {
+            return Action::Keep;
+        }
+
+        let kept = || self.keep.iter().filter(|p| p.matches_prefix(rel_path));
+        if kept().any(|p| p.components.len() == rel_path.len()) {
+            Action::Keep
+        } else if kept().next().is_some() {
+            Action::Descend
+        } else {
+            Action::Delete
+        }
+    }
+}
+
+/// What to do with an entry in the target directory
+enum Action {
+    /// The entry matches a pattern and is kept entirely
+    Keep,
+    /// The entry is a parent of potentially matching entries
+    Descend,
+    Delete,
+}
+
+/// Delete the contents of the target directory that are not kept by the filter. Returns the size
+/// and the number of the deleted files.
+pub fn clean_target_except(target_path: &Path, filter: &TargetFilter) -> io::Result<(u64, u64)> {
+    let mut deleted = (0, 0);
+    clean_dir_except(target_path, &mut Vec::new(), filter, &mut deleted)?;
+    Ok(deleted)
+}
+
+fn clean_dir_except(
+    dir: &Path,
+    rel_path: &mut Vec<String>,
+    filter: &TargetFilter,
+    deleted: &mut (u64, u64),
+) -> io::Result<()> {
+    for entry in dir.read_dir()? {
+        let entry = entry?;
+        let path = entry.path();
+        let is_dir = entry.file_type()?.is_dir();
+
+        rel_path.push(entry.file_name().to_string_lossy().to_string());
+        match filter.action(rel_path) {
+            Action::Keep => (),
+            Action::Descend if is_dir => {
+                clean_dir_except(&path, rel_path, filter, deleted)?;
+                // Remove the directory if nothing in it was kept, this fails if it is not empty
+                let _ = std::fs::remove_dir(&path);
+            }
+            _ => {
+                let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(&path);
+                if is_dir {
+                    remove_dir_all::remove_dir_all(&path)?;
+                } else {
+                    std::fs::remove_file(&path)?;
+                }
+                deleted.0 += size;
+                deleted.1 += file_count;
+            }
+        }
+        rel_path.pop();
+    }
+    Ok(())
+}
+
+/// List the files in the target directory that are deleted by the filter, together with their
+/// sizes and modification times. With an empty filter, all files are listed.
+pub fn deleted_files(target_path: &Path, filter: &TargetFilter) -> Vec<(PathBuf, u64, SystemTime)> {
+    let mut files = Vec::new();
+    list_dir_except(target_path, &mut Vec::new(), filter, &mut files);
+    files
+}
+
+fn list_dir_except(
+    dir: &Path,
+    rel_path: &mut Vec<String>,
+    filter: &TargetFilter,
+    files: &mut Vec<(PathBuf, u64, SystemTime)>,
+) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+
+    for entry in entries.filter_map(|it| it.ok()) {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        rel_path.push(entry.file_name().to_string_lossy().to_string());
+        match filter.action(rel_path) {
+            Action::Keep => (),
+            Action::Descend if file_type.is_dir() => {
+                list_dir_except(&entry.path(), rel_path, filter, files)
+            }
+            // Everything in a deleted directory is deleted as well, which is what the empty
+            // filter results in
+            _ if file_type.is_dir() => {
+                list_dir_except(&entry.path(), rel_path, &TargetFilter::default(), files)
+            }
+            _ => {
+                let md = entry.metadata().ok();
+                files.push((
+                    entry.path(),
+                    md.as_ref().map(|md| md.len()).unwrap_or(0),
+                    md.and_then(|md| md.modified().ok())
+                        .unwrap_or(SystemTime::UNIX_EPOCH),
+                ));
+            }
+        }
+        rel_path.pop();
+    }
+}
@@ -0,0 +1,200 @@
+//! Detection of the toolchains that produced a target directory, based on the `.rustc_info.json`
+//! file that cargo stores in the target directory, and of the toolchains that are installed.
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::OnceLock,
+};
+
+/// A rustc version as reported by `rustc -vV`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RustcVersion {
+    /// The release, for example `1.75.0` or `1.76.0-nightly`
+    pub release: String,
+    /// The date of the commit the compiler was built from, for example `2023-12-21`
+    pub commit_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RustcInfo {
+    outputs: HashMap<String, RustcOutput>,
+}
+
+#[derive(Deserialize)]
+struct RustcOutput {
+    success: bool,
+    stdout: String,
+}
+
+impl RustcVersion {
+    /// Parse the output of `rustc -vV`
+    pub fn from_verbose_version(output: &str) -> Option<Self> {
+        let field = |name: &str| {
+            output
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(": "))
+                .map(|value| value.trim().to_string())
+        };
+
+        Some(Self {
+            release: field("release")?,
+            commit_date: field("commit-date").filter(|d| d != "unknown"),
+        })
+    }
+
+    /// The release channel, which is `stable` for releases without a suffix
+    pub fn channel(&self) -> &str {
+        match self.release.split_once('-') {
+            Some((_, channel)) => channel,
+            None => "stable",
+        }
+    }
+
+    /// The names this toolchain can be referred to, for example `1.75.0` and `stable-1.75.0` for a
+    /// stable release or `1.76.0-nightly` and `nightly-2023-11-30` for a nightly build
+    pub fn identifiers(&self) -> Vec<String> {
+        let mut ids = vec![self.release.clone()];
+        match (self.channel(), &self.commit_date) {
+            ("stable", _) => ids.push(format!("stable-{}", self.release)),
+            (channel, Some(date)) => ids.push(format!("{}-{}", channel, date)),
+            (channel, None) => ids.push(channel.to_string()),
+        }
+        ids
+    }
+
+    /// Check if any of the names of this toolchain matches the glob pattern
+    pub fn matches(&self, pattern: &glob::Pattern) -> bool {
+        self.identifiers().iter().any(|id| pattern.matches(id))
+    }
+}
+
+impl std::fmt::Display for RustcVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.channel(), &self.commit_date) {
+            ("stable", _) | (_, None) => write!(f, "{}", self.release),
+            (channel, Some(date)) => write!(f, "{}-{}", channel, date),
+        }
+    }
+}
+
+/// Read the rustc versions that were used to build the given target directory
+pub fn target_toolchains(target_path: &Path) -> Vec<RustcVersion> {
+    let Ok(content) = std::fs::read_to_string(target_path.join(".rustc_info.json")) else {
+        return Vec::new();
+    };
+    let Ok(info) = serde_json::from_str::<RustcInfo>(&content) else {
+        return Vec::new();
+    };
+
+    let mut versions: Vec<_> = info
+        .outputs
+        .values()
+        .filter(|out| out.success)
+        .filter_map(|out| RustcVersion::from_verbose_version(&out.stdout))
+        .collect();
+    versions.sort_by(|a, b| a.release.cmp(&b.release));
+    versions.dedup();
+    versions
+}
+
+/// Run `rustc -V` and return its output, like `rustc 1.75.0 (82e1608df 2023-12-21)`
+fn rustc_version(rustc: &Path, dir: Option<&Path>) -> Option<String> {
+    let mut command = Command::new(rustc);
+    command.arg("-V");
+    if let Some(dir) = dir {
+        // Set by rustup for the processes it starts, which would override the toolchain file
+        command
+            .current_dir(dir)
+            .env_remove("RUSTUP_TOOLCHAIN")
+            .env("RUSTUP_AUTO_INSTALL", "0");
+    }
+    let output = command.output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|version| version.starts_with("rustc "))
+}
+
+/// The `rustc -V` outputs of the toolchains installed with rustup and of the rustc in the PATH.
+/// They are only detected once per run
+pub fn installed_versions() -> &'static [String] {
+    static INSTALLED: OnceLock<Vec<String>> = OnceLock::new();
+    INSTALLED.get_or_init(detect_installed_versions)
+}
+
+fn detect_installed_versions() -> Vec<String> {
+    let rustup_home = std::env::var_os("RUSTUP_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::home_dir().map(|home| home.join(".rustup")));
+    let rustc = if cfg!(windows) { "rustc.exe" } else { "rustc" };
+
+    let mut versions: Vec<String> = rustup_home
+        .and_then(|home| home.join("toolchains").read_dir().ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| rustc_version(&entry.path().join("bin").join(rustc), None))
+        .chain(rustc_version(Path::new("rustc"), None))
+        .collect();
+    versions.sort_unstable();
+    versions.dedup();
+    versions
+}
+
+/// The `rustc -V` output of the toolchain that is used in the directory, which can be selected by
+/// a `rust-toolchain.toml` file or a rustup override
+pub fn active_version(dir: &Path) -> Option<String> {
+    rustc_version(Path::new("rustc"), Some(dir))
+}
+
+/// How much of a compiled crate is searched for its rustc version. The version is stored at the
+/// start of the metadata, which is the first member of rlibs
+const EMBEDDED_VERSION_SEARCH_LEN: u64 = 64 * 1024;
+
+/// The version of the rustc that compiled the crate, which is embedded in the metadata of `.rmeta`
+/// and `.rlib` files like in the output of `rustc -V`
+pub fn embedded_version(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(EMBEDDED_VERSION_SEARCH_LEN)
+        .read_to_end(&mut head)
+        .ok()?;
+    let start = head.windows(7).position(|w| w == b"rustc 1")?;
+    let len = head[start..].iter().position(|&b| b == b')')? + 1;
+    String::from_utf8(head[start..start + len].to_vec()).ok()
+}
+
+/// Which toolchains the artifacts are kept for by --prune-toolchains
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum PruneToolchains {
+    /// Delete the artifacts of toolchains that are not installed anymore
+    Uninstalled,
+    /// Delete the artifacts of all toolchains except the one that is active in the project
+    Inactive,
+}
+
+/// The versions of the toolchains whose artifacts are kept in the project
+pub fn kept_versions(mode: PruneToolchains, project_path: &Path) -> io::Result<Vec<String>> {
+    let kept = match mode {
+        PruneToolchains::Uninstalled => installed_versions().to_vec(),
+        PruneToolchains::Inactive => active_version(project_path).into_iter().collect(),
+    };
+    if kept.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            match mode {
+                PruneToolchains::Uninstalled => "No installed toolchains were found",
+                PruneToolchains::Inactive => "The active toolchain could not be determined",
+            },
+        ));
+    }
+    Ok(kept)
+}
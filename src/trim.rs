@@ -0,0 +1,149 @@
+//! Trimming of target directories to a size budget or by age. Instead of deleting the whole
+//! target, the least recently used artifacts are deleted until the rest fits into the budget, or
+//! the ones that were not used for some time, so that the recently used parts don't have to be
+//! rebuilt.
+//!
+//! The artifacts are deleted in units that cargo rebuilds as a whole: the files of a compilation
+//! unit that share its metadata hash, the incremental compilation sessions of a crate and the
+//! other contents of the target, like the generated documentation.
+
+use crate::{
+    prune::{artifact_hash, dir_entries, file_name, profile_dirs},
+    ProjectTargetAnalysis,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Files in the target directory that are never trimmed, since they are tiny and used by other
+/// tools, like `CACHEDIR.TAG` for backup exclusion
+const KEPT_FILES: &[&str] = &["CACHEDIR.TAG", ".rustc_info.json"];
+
+/// Artifacts that are deleted together
+struct Unit {
+    paths: Vec<PathBuf>,
+    size: u64,
+    last_modified: SystemTime,
+}
+
+impl Unit {
+    fn new() -> Self {
+        Self {
+            paths: Vec::new(),
+            size: 0,
+            last_modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn add(&mut self, path: PathBuf) {
+        let (size, last_modified, _) = ProjectTargetAnalysis::recursive_scan_target(&path);
+        self.size += size;
+        self.last_modified = self.last_modified.max(last_modified);
+        self.paths.push(path);
+    }
+}
+
+fn single_unit(path: PathBuf) -> Unit {
+    let mut unit = Unit::new();
+    unit.add(path);
+    unit
+}
+
+/// Split the contents of the target directory into units
+fn units(target_path: &Path) -> Vec<Unit> {
+    let profile_dirs = profile_dirs(target_path);
+    let mut units = Vec::new();
+
+    // The build outputs outside of the profile directories, like `target/doc`. Directories of
+    // cross compilation targets only contain profile directories
+    for entry in dir_entries(target_path) {
+        let is_parent_of_profile = profile_dirs.iter().any(|dir| dir.starts_with(&entry));
+        if !is_parent_of_profile && !KEPT_FILES.contains(&file_name(&entry)) {
+            units.push(single_unit(entry));
+        }
+    }
+
+    for profile_dir in &profile_dirs {
+        let mut by_hash: HashMap<String, Unit> = HashMap::new();
+        for dir in [".fingerprint", "build", "deps"] {
+            for path in dir_entries(&profile_dir.join(dir)) {
+                match artifact_hash(file_name(&path)) {
+                    Some(hash) => by_hash
+                        .entry(hash.to_string())
+                        .or_insert_with(Unit::new)
+                        .add(path),
+                    None => units.push(single_unit(path)),
+                }
+            }
+        }
+        units.extend(by_hash.into_values());
+
+        units.extend(
+            dir_entries(&profile_dir.join("incremental"))
+                .into_iter()
+                .map(single_unit),
+        );
+
+        // The final outputs next to the artifact directories, like the binaries, and the other
+        // directories like `examples`
+        units.extend(
+            dir_entries(profile_dir)
+                .into_iter()
+                .filter(|path| {
+                    ![".fingerprint", "build", "deps", "incremental"].contains(&file_name(path))
+                        && !KEPT_FILES.contains(&file_name(path))
+                })
+                .map(single_unit),
+        );
+    }
+    units
+}
+
+/// The last time the files of the path were used. This is the latest access time, since cargo
+/// reads the artifacts it reuses without modifying them, or the modification time if it is later.
+/// The times of the directories are not used, since listing them for the scan updates them
+fn last_used(path: &Path) -> SystemTime {
+    let Ok(md) = path.symlink_metadata() else {
+        return SystemTime::UNIX_EPOCH;
+    };
+    if md.is_dir() {
+        return dir_entries(path)
+            .iter()
+            .map(|entry| last_used(entry))
+            .fold(SystemTime::UNIX_EPOCH, SystemTime::max);
+    }
+    md.accessed()
+        .into_iter()
+        .chain(md.modified())
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// The artifacts of the target that were not used since the given time
+pub fn stale_artifacts(target_path: &Path, since: SystemTime) -> Vec<PathBuf> {
+    units(target_path)
+        .into_iter()
+        .filter(|unit| unit.paths.iter().map(|path| last_used(path)).max() < Some(since))
+        .flat_map(|unit| unit.paths)
+        .collect()
+}
+
+/// The least recently used artifacts of the target that have to be deleted to bring its size
+/// down to the budget
+pub fn trimmed_artifacts(target_path: &Path, budget: u64) -> Vec<PathBuf> {
+    let mut units = units(target_path);
+    units.sort_by_key(|unit| unit.last_modified);
+
+    let mut size: u64 = units.iter().map(|unit| unit.size).sum();
+    let mut trimmed = Vec::new();
+    for unit in units {
+        if size <= budget {
+            break;
+        }
+        size -= unit.size;
+        trimmed.extend(unit.paths);
+    }
+    trimmed
+}
@@ -0,0 +1,475 @@
+//! The full-screen project table of `--tui`, for machines with too many projects for the
+//! interactive selection. The table can be scrolled and searched, shows the details of the
+//! highlighted project and the cleanup is confirmed directly in it.
+
+use crate::{
+    canonicalize_or_not,
+    i18n::{self, tr},
+    pretty_format_path,
+    selector::{fuzzy_matches, Item, Mark, Selection},
+    AppArgs, ProjectTargetAnalysis,
+};
+use dialoguer::console::measure_text_width;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
+    DefaultTerminal, Frame,
+};
+use std::io::{self, IsTerminal};
+
+const HELP: &str = "[↑↓] move, [space] toggle, [a] toggle all shown, [p] ignore permanently, \
+                    [/] search, [enter] confirm, [esc] cancel";
+
+const SEARCH_HELP: &str = "[enter] keep the search, [esc] clear the search";
+
+/// The number of lines of the details pane, including its border
+const DETAILS_HEIGHT: u16 = 9;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Browse,
+    /// Typing the search query
+    Search,
+    /// Asking whether the selected projects should be cleaned
+    Confirm,
+}
+
+pub struct Tui<'a> {
+    prompt: &'a str,
+    projects: &'a [ProjectTargetAnalysis],
+    marks: Vec<Mark>,
+    /// Only the projects whose name or path fuzzy matches the query are shown
+    query: String,
+    /// The indices of the shown projects
+    shown: Vec<usize>,
+    table: TableState,
+    mode: Mode,
+    /// Confirming only ends the selection, since nothing is deleted
+    dry_run: bool,
+    /// The --max-free limit, which stops the cleanup before all selected projects are cleaned
+    max_free: Option<u64>,
+}
+
+/// The time since the target was last modified in days
+fn age(project: &ProjectTargetAnalysis) -> String {
+    let days = project
+        .last_modified
+        .elapsed()
+        .unwrap_or_default()
+        .as_secs()
+        / (60 * 60 * 24);
+    match days {
+        0 => "today".to_string(),
+        1 => "1 day".to_string(),
+        days => format!("{} days", days),
+    }
+}
+
+fn path(project: &ProjectTargetAnalysis) -> String {
+    pretty_format_path(&canonicalize_or_not(&project.project_path))
+}
+
+impl<'a> Tui<'a> {
+    pub fn new(
+        prompt: &'a str,
+        projects: &'a [ProjectTargetAnalysis],
+        defaults: &[bool],
+        args: &AppArgs,
+    ) -> Self {
+        let marks = (0..projects.len())
+            .map(|i| match defaults.get(i) {
+                Some(true) => Mark::Selected,
+                _ => Mark::Unselected,
+            })
+            .collect();
+        let mut tui = Self {
+            prompt,
+            projects,
+            marks,
+            query: String::new(),
+            shown: Vec::new(),
+            table: TableState::default(),
+            mode: Mode::Browse,
+            dry_run: args.dry_run,
+            max_free: args.max_free,
+        };
+        tui.update_shown();
+        tui
+    }
+
+    /// Show the table until the cleanup is confirmed or cancelled. Returns `None` if it was
+    /// cancelled
+    pub fn interact(mut self) -> io::Result<Option<Selection>> {
+        if !io::stdout().is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "The TUI requires a terminal",
+            ));
+        }
+
+        let mut terminal = ratatui::try_init()?;
+        let result = self.run(&mut terminal);
+        ratatui::try_restore()?;
+        result
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<Option<Selection>> {
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            // Raw mode turns Ctrl+C into a key press instead of a signal
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(None);
+            }
+            let page_size = terminal.size()?.height.saturating_sub(DETAILS_HEIGHT + 4) as isize;
+
+            match self.mode {
+                Mode::Search => match key.code {
+                    KeyCode::Char(c) => {
+                        self.query.push(c);
+                        self.update_shown();
+                    }
+                    KeyCode::Backspace => {
+                        self.query.pop();
+                        self.update_shown();
+                    }
+                    KeyCode::Enter | KeyCode::Down => self.mode = Mode::Browse,
+                    KeyCode::Esc => {
+                        self.query.clear();
+                        self.update_shown();
+                        self.mode = Mode::Browse;
+                    }
+                    _ => (),
+                },
+                Mode::Confirm => match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => return Ok(Some(self.selection())),
+                    _ => self.mode = Mode::Browse,
+                },
+                Mode::Browse => match key.code {
+                    KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+                    KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+                    KeyCode::PageUp => self.move_cursor(-page_size.max(1)),
+                    KeyCode::PageDown => self.move_cursor(page_size.max(1)),
+                    KeyCode::Home => self.move_cursor(isize::MIN),
+                    KeyCode::End => self.move_cursor(isize::MAX),
+                    KeyCode::Char(' ') => self.toggle(),
+                    KeyCode::Char('a') => self.toggle_all_shown(),
+                    KeyCode::Char('p') => {
+                        if let Some(mark) = self.highlighted().map(|idx| &mut self.marks[idx]) {
+                            *mark = match mark {
+                                Mark::IgnoredPermanently => Mark::Unselected,
+                                _ => Mark::IgnoredPermanently,
+                            };
+                        }
+                    }
+                    KeyCode::Char('/') => self.mode = Mode::Search,
+                    KeyCode::Enter => self.mode = Mode::Confirm,
+                    KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+                    _ => (),
+                },
+            }
+        }
+    }
+
+    fn selection(&self) -> Selection {
+        let with_mark = |mark| {
+            self.marks
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| **m == mark)
+                .map(|(i, _)| i)
+                .collect()
+        };
+        Selection {
+            selected: with_mark(Mark::Selected),
+            ignored_permanently: with_mark(Mark::IgnoredPermanently),
+        }
+    }
+
+    /// Show the projects that match the query and keep the highlighted project if it still matches
+    fn update_shown(&mut self) {
+        let highlighted = self.highlighted();
+        self.shown = (0..self.projects.len())
+            .filter(|&idx| fuzzy_matches(&self.projects[idx].search_text(), &self.query))
+            .collect();
+
+        let position = highlighted.and_then(|idx| self.shown.iter().position(|&i| i == idx));
+        self.table.select(match position {
+            Some(position) => Some(position),
+            None if self.shown.is_empty() => None,
+            None => Some(0),
+        });
+    }
+
+    /// The index of the highlighted project
+    fn highlighted(&self) -> Option<usize> {
+        self.table
+            .selected()
+            .and_then(|row| self.shown.get(row).copied())
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if let Some(row) = self.table.selected() {
+            let last = self.shown.len().saturating_sub(1) as isize;
+            self.table.select(Some(
+                (row as isize).saturating_add(delta).clamp(0, last) as usize
+            ));
+        }
+    }
+
+    fn toggle(&mut self) {
+        if let Some(idx) = self.highlighted() {
+            self.marks[idx] = match self.marks[idx] {
+                Mark::Selected => Mark::Unselected,
+                Mark::Unselected | Mark::IgnoredPermanently => Mark::Selected,
+            };
+        }
+    }
+
+    /// Select all shown projects, or unselect them if all of them are selected already
+    fn toggle_all_shown(&mut self) {
+        let select = self
+            .shown
+            .iter()
+            .any(|&idx| self.marks[idx] == Mark::Unselected);
+        for &idx in &self.shown {
+            if self.marks[idx] != Mark::IgnoredPermanently {
+                self.marks[idx] = if select {
+                    Mark::Selected
+                } else {
+                    Mark::Unselected
+                };
+            }
+        }
+    }
+
+    /// The number of selected projects and their combined size. The projects that are configured
+    /// to never be cleaned are not counted, since they are kept anyway
+    fn selected_total(&self) -> (usize, u64) {
+        self.projects
+            .iter()
+            .zip(&self.marks)
+            .filter(|(project, mark)| **mark == Mark::Selected && !project.policy.never_clean)
+            .fold((0, 0), |(count, size), (project, _)| {
+                (count + 1, size + project.total_size())
+            })
+    }
+
+    /// The number of selected projects that are configured to never be cleaned
+    fn selected_never_clean(&self) -> usize {
+        self.projects
+            .iter()
+            .zip(&self.marks)
+            .filter(|(project, mark)| **mark == Mark::Selected && project.policy.never_clean)
+            .count()
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [title_area, table_area, details_area, status_area] = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(DETAILS_HEIGHT),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let help = match self.mode {
+            Mode::Search => SEARCH_HELP,
+            _ => HELP,
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(self.prompt).bold(),
+                Line::from(i18n::translate(help)).dim(),
+            ]),
+            title_area,
+        );
+
+        self.render_table(frame, table_area);
+        self.render_details(frame, details_area);
+
+        let (count, size) = self.selected_total();
+        let status = match self.mode {
+            Mode::Search => Line::from(format!("/{}", self.query)).fg(Color::Cyan),
+            _ if !self.query.is_empty() => Line::from(format!(
+                "{} (search: {})",
+                tr!(
+                    "Selected {}/{} projects, cleaning will free: {}",
+                    count,
+                    self.projects.len(),
+                    i18n::format_size(size)
+                ),
+                self.query
+            )),
+            _ => Line::from(tr!(
+                "Selected {}/{} projects, cleaning will free: {}",
+                count,
+                self.projects.len(),
+                i18n::format_size(size)
+            )),
+        };
+        frame.render_widget(Paragraph::new(status), status_area);
+        if self.mode == Mode::Search {
+            let query_width = measure_text_width(&self.query) as u16;
+            frame.set_cursor_position((status_area.x + 1 + query_width, status_area.y));
+        }
+
+        if self.mode == Mode::Confirm {
+            self.render_confirmation(frame, count, size);
+        }
+    }
+
+    fn render_table(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.shown.iter().map(|&idx| {
+            let project = &self.projects[idx];
+            let (mark, style) = match self.marks[idx] {
+                Mark::Selected => ("[x]", Style::new().fg(Color::Green)),
+                Mark::Unselected => ("[ ]", Style::new()),
+                Mark::IgnoredPermanently => ("[-]", Style::new().fg(Color::Yellow)),
+            };
+            Row::new([
+                Cell::from(mark),
+                Cell::from(project.name().into_owned()),
+                Cell::from(i18n::format_size(project.total_size())),
+                Cell::from(age(project)),
+                Cell::from(path(project)),
+            ])
+            .style(style)
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Max(30),
+                Constraint::Length(10),
+                Constraint::Length(9),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(["", "Name", "Size", "Age", "Path"]).bold())
+        .block(Block::bordered())
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+        frame.render_stateful_widget(table, area, &mut self.table);
+    }
+
+    fn render_details(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title("Details");
+        let Some(project) = self.highlighted().map(|idx| &self.projects[idx]) else {
+            frame.render_widget(Paragraph::new("No matching projects").block(block), area);
+            return;
+        };
+
+        let last_modified: chrono::DateTime<chrono::Local> = project.last_modified.into();
+        let toolchains: Vec<_> = project.toolchains.iter().map(|tc| tc.to_string()).collect();
+        let mut lines = vec![
+            Line::from(format!("Project: {}", path(project))),
+            Line::from(format!(
+                "Target: {}",
+                pretty_format_path(&project.target_path)
+            )),
+            Line::from(format!(
+                "Size: {} in {} files",
+                i18n::format_size(project.size),
+                project.file_count
+            )),
+            Line::from(format!(
+                "Last modified: {}",
+                i18n::format_datetime(&last_modified)
+            )),
+            Line::from(format!("Toolchains: {}", toolchains.join(", "))),
+        ];
+        if project.total_size() > project.size {
+            lines.push(Line::from(format!(
+                "Outside of the target: {} of coverage data and web outputs",
+                i18n::format_size(project.total_size() - project.size)
+            )));
+        }
+        if project.policy.never_clean {
+            lines.push(Line::from("Configured to never be cleaned").fg(Color::Yellow));
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn render_confirmation(&self, frame: &mut Frame, count: usize, size: u64) {
+        let mut lines = vec![if self.dry_run {
+            Line::from(format!(
+                "Dry run, select {} projects that would free {}?",
+                count,
+                i18n::format_size(size)
+            ))
+        } else {
+            Line::from(format!(
+                "Clean {} projects, freeing {}?",
+                count,
+                i18n::format_size(size)
+            ))
+        }];
+        if let Some(max_free) = self.max_free.filter(|&max_free| size > max_free) {
+            lines.push(Line::from(format!(
+                "The cleanup stops once {} are freed (--max-free)",
+                i18n::format_size(max_free)
+            )));
+        }
+        let never_clean = self.selected_never_clean();
+        if never_clean > 0 {
+            lines.push(
+                Line::from(format!(
+                    "{} selected projects are kept, since they are configured to never be cleaned",
+                    never_clean
+                ))
+                .fg(Color::Yellow),
+            );
+        }
+        lines.push(Line::from(""));
+        lines.push(
+            Line::from(if self.dry_run {
+                "[y] confirm, [n] back to the table"
+            } else {
+                "[y] clean, [n] back to the table"
+            })
+            .dim(),
+        );
+
+        let area = frame.area();
+        let width = area.width.min(60);
+        // The lines may wrap, the border takes up 2 lines
+        let height = lines
+            .iter()
+            .map(|line| {
+                (line.width() as u16)
+                    .div_ceil(width.saturating_sub(2).max(1))
+                    .max(1)
+            })
+            .sum::<u16>()
+            + 2;
+        let area = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height.min(area.height),
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title("Confirm"))
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Detection of the output directories of the Rust web toolchains next to the target directory,
+//! `pkg` of wasm-pack and `dist` of trunk. Those are build outputs just like the target directory,
+//! but the scan doesn't see them on its own.
+
+use crate::ProjectTargetAnalysis;
+use std::path::{Path, PathBuf};
+
+/// Check if the directory contains a `.wasm` file
+fn contains_wasm(dir: &Path) -> bool {
+    dir.read_dir().is_ok_and(|entries| {
+        entries
+            .filter_map(|it| it.ok())
+            .any(|it| it.path().extension().is_some_and(|ext| ext == "wasm"))
+    })
+}
+
+/// Find the wasm-pack and trunk output directories of the project, together with their sizes and
+/// the number of files in them
+pub fn find_web_outputs(project_path: &Path) -> Vec<(PathBuf, u64, u64)> {
+    let pkg = project_path.join("pkg");
+    let is_wasm_pack_output = pkg.join("package.json").is_file() && contains_wasm(&pkg);
+
+    let dist = project_path.join("dist");
+    let is_trunk_project =
+        project_path.join("Trunk.toml").is_file() || project_path.join("index.html").is_file();
+    let is_trunk_output = is_trunk_project && contains_wasm(&dist);
+
+    [(pkg, is_wasm_pack_output), (dist, is_trunk_output)]
+        .into_iter()
+        .filter(|(_, is_output)| *is_output)
+        .map(|(dir, _)| {
+            let (size, _, file_count) = ProjectTargetAnalysis::recursive_scan_target(&dir);
+            (dir, size, file_count)
+        })
+        .collect()
+}
@@ -0,0 +1,25 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Call `f` for every item using the specified number of worker threads. Each worker picks the
+/// next unprocessed item, so long running items don't block the others.
+///
+/// When the number of threads is 0, use as many threads as virtual CPU cores.
+pub fn for_each_parallel<T: Sync>(items: &[T], mut num_threads: usize, f: impl Fn(&T) + Sync) {
+    if num_threads == 0 {
+        num_threads = num_cpus::get();
+    }
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..num_threads.min(items.len()) {
+            scope.spawn(|| {
+                while let Some(item) = items.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    f(item);
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,49 @@
+//! Support for running inside of WSL, where the Windows drives are accessed through a slow
+//! filesystem bridge.
+
+use crate::mounts::Mount;
+use std::{
+    io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Check if the program is running inside of the Windows Subsystem for Linux
+pub fn is_wsl() -> bool {
+    std::env::var_os("WSL_DISTRO_NAME").is_some()
+        || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// Check if the mount is a Windows drive that is mounted into WSL, like `/mnt/c`
+pub fn is_windows_drive(mount: &Mount) -> bool {
+    matches!(mount.fs_type.as_str(), "9p" | "drvfs" | "v9fs")
+}
+
+/// Delete the directory using the Windows `rmdir` command, which is much faster than deleting the
+/// files one by one through the filesystem bridge
+pub fn delete_natively(dir: &Path) -> io::Result<()> {
+    let output = Command::new("wslpath").arg("-w").arg(dir).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "wslpath failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    let windows_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let status = Command::new("cmd.exe")
+        .args(["/c", "rmdir", "/s", "/q"])
+        .arg(&windows_path)
+        .stdout(Stdio::null())
+        .status()?;
+
+    // rmdir doesn't reliably report failures using the exit code, so the result is checked as well
+    if !status.success() || dir.exists() {
+        return Err(io::Error::other(format!(
+            "Deleting '{}' using the Windows rmdir command failed",
+            windows_path
+        )));
+    }
+    Ok(())
+}